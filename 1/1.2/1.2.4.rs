@@ -1,17 +1,23 @@
 use num_integer::Integer;
 use num_traits::{Signed, ToPrimitive};
 
+use crate::error::Error;
 use crate::group::GroupElement;
 
-pub fn left_right_base_2k_power<G, I>(g: G, n: I, k: usize) -> G
+// the table of odd powers has 2^(k-1) entries
+const MAX_K: usize = 16;
+
+pub fn left_right_base_2k_power<G, I>(g: G, n: I, k: usize) -> Result<G, Error>
 where
     G: GroupElement,
     I: Integer + Signed + Clone + ToPrimitive,
 {
-    assert!(k > 0);
+    if !(1..=MAX_K).contains(&k) {
+        return Err(Error::InvalidArgument("k must be in 1..=16"));
+    }
 
     if n.is_zero() {
-        return G::identity();
+        return Ok(G::identity());
     }
 
     let n_abs = n.abs();
@@ -54,7 +60,7 @@ where
         }
 
         if f == 0 {
-            return y;
+            return Ok(y);
         }
         f -= 1;
     }
@@ -105,3 +111,35 @@ where
 
     table
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FpContext;
+    use num_bigint::BigInt;
+
+    #[test]
+    fn base_2k_power_agrees_with_modpow() {
+        let p = BigInt::from(10_007);
+        let ctx = FpContext::new(&p);
+        let g = ctx.elem(&BigInt::from(3));
+        for n in [0i64, 1, 2, 17, 1_000_003, -45] {
+            let expected =
+                ctx.elem(&BigInt::from(3).modpow(&BigInt::from(n).mod_floor(&10_006.into()), &p));
+            for k in 1..=6 {
+                assert_eq!(
+                    left_right_base_2k_power(g.clone(), BigInt::from(n), k).unwrap(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zero_and_oversized_k_are_rejected() {
+        let ctx = FpContext::new(&BigInt::from(101));
+        let g = ctx.elem(&BigInt::from(2));
+        assert!(left_right_base_2k_power(g.clone(), 5i64, 0).is_err());
+        assert!(left_right_base_2k_power(g, 5i64, 64).is_err());
+    }
+}
@@ -1,18 +1,17 @@
-use num_integer::Integer;
-use num_traits::Signed;
+use crate::ring::EuclideanDomain;
 
-pub fn euclid_gcd<I>(a: I, b: I) -> I
+pub fn euclid_gcd<R>(a: R, b: R) -> R
 where
-    I: Integer + Signed + Clone,
+    R: EuclideanDomain,
 {
-    let mut a = a.abs();
-    let mut b = b.abs();
+    let mut a = a;
+    let mut b = b;
 
     while !b.is_zero() {
-        let r = a.mod_floor(&b);
+        let (_q, r) = a.quo_rem(&b);
         a = b;
         b = r;
     }
 
-    a
+    a.normalize()
 }
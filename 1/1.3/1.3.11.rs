@@ -2,11 +2,12 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::One;
 
+use crate::error::Error;
 use crate::extended_euclid;
 
-pub fn chinese_remainder_pairwise(mi: &[BigInt], xi: &[BigInt]) -> Option<BigInt> {
+pub fn chinese_remainder_pairwise(mi: &[BigInt], xi: &[BigInt]) -> Result<BigInt, Error> {
     if mi.len() != xi.len() || mi.is_empty() {
-        return None;
+        return Err(Error::DimensionMismatch);
     }
 
     let mut c: Vec<BigInt> = Vec::with_capacity(mi.len());
@@ -16,7 +17,7 @@ pub fn chinese_remainder_pairwise(mi: &[BigInt], xi: &[BigInt]) -> Option<BigInt
     for j in 1..mi.len() {
         let (u, _v, d) = extended_euclid(p.clone(), mi[j].clone());
         if !d.is_one() {
-            return None;
+            return Err(Error::NotInvertible);
         }
         c.push(u);
         p *= &mi[j];
@@ -34,5 +35,5 @@ pub fn chinese_remainder_pairwise(mi: &[BigInt], xi: &[BigInt]) -> Option<BigInt
         m_prod *= m_j;
     }
 
-    Some(y.mod_floor(&m_prod))
+    Ok(y.mod_floor(&m_prod))
 }
@@ -2,11 +2,12 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::One;
 
+use crate::error::Error;
 use crate::extended_euclid;
 
-pub fn chinese_remainder_inductive(mi: &[BigInt], xi: &[BigInt]) -> Option<BigInt> {
+pub fn chinese_remainder_inductive(mi: &[BigInt], xi: &[BigInt]) -> Result<BigInt, Error> {
     if mi.len() != xi.len() || mi.is_empty() {
-        return None;
+        return Err(Error::DimensionMismatch);
     }
 
     let mut m = mi[0].clone();
@@ -16,12 +17,12 @@ pub fn chinese_remainder_inductive(mi: &[BigInt], xi: &[BigInt]) -> Option<BigIn
         let m_i = mi[i].clone();
         let (u, v, d) = extended_euclid(m.clone(), m_i.clone());
         if !d.is_one() {
-            return None;
+            return Err(Error::NotInvertible);
         }
         let term = &u * m.clone() * xi[i].mod_floor(&m_i) + v * m_i.clone() * x;
         m *= &m_i;
         x = term.mod_floor(&m);
     }
 
-    Some(x.mod_floor(&m))
+    Ok(x.mod_floor(&m))
 }
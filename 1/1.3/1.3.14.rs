@@ -2,10 +2,15 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{Signed, Zero};
 
-pub fn gauss_reduce(a: &[BigInt], b: &[BigInt]) -> (Vec<BigInt>, Vec<BigInt>) {
-    assert_eq!(a.len(), b.len());
-    assert!(!a.iter().all(|x| x.is_zero()));
-    assert!(!b.iter().all(|x| x.is_zero()));
+use crate::error::Error;
+
+pub fn gauss_reduce(a: &[BigInt], b: &[BigInt]) -> Result<(Vec<BigInt>, Vec<BigInt>), Error> {
+    if a.len() != b.len() {
+        return Err(Error::DimensionMismatch);
+    }
+    if a.iter().all(|x| x.is_zero()) || b.iter().all(|x| x.is_zero()) {
+        return Err(Error::InvalidArgument("basis vectors must be nonzero"));
+    }
 
     let mut a_vec = a.to_vec();
     let mut b_vec = b.to_vec();
@@ -26,7 +31,10 @@ pub fn gauss_reduce(a: &[BigInt], b: &[BigInt]) -> (Vec<BigInt>, Vec<BigInt>) {
         let t_scalar = &a_norm - two_r_n + r_sq_b;
 
         if t_scalar >= b_norm {
-            return (a_vec, b_vec);
+            return Ok((a_vec, b_vec));
+        }
+        if t_scalar.is_zero() {
+            return Err(Error::InvalidArgument("basis vectors are linearly dependent"));
         }
 
         let t_vec = sub_scaled(&a_vec, &b_vec, &r);
@@ -1,29 +1,31 @@
-use num_bigint::BigInt;
-use num_traits::{One, Signed, Zero};
+use crate::ring::EuclideanDomain;
 
-pub fn extended_euclid(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
-    let mut old_r = a.abs();
-    let mut r = b.abs();
-    let mut old_s = BigInt::one();
-    let mut s = BigInt::zero();
-    let mut old_t = BigInt::zero();
-    let mut t = BigInt::one();
+pub fn extended_euclid<R>(a: R, b: R) -> (R, R, R)
+where
+    R: EuclideanDomain,
+{
+    let mut old_r = a;
+    let mut r = b;
+    let mut old_s = R::one();
+    let mut s = R::zero();
+    let mut old_t = R::zero();
+    let mut t = R::one();
 
     while !r.is_zero() {
-        let q = &old_r / &r;
-
-        let temp_r = &old_r - &q * &r;
+        let (q, temp_r) = old_r.quo_rem(&r);
         old_r = r;
         r = temp_r;
 
-        let temp_s = &old_s - &q * &s;
+        let temp_s = old_s - q.clone() * s.clone();
         old_s = s;
         s = temp_s;
 
-        let temp_t = &old_t - &q * &t;
+        let temp_t = old_t - q * t.clone();
         old_t = t;
         t = temp_t;
     }
 
-    (old_s, old_t, old_r)
+    // scale so that d is the normalized gcd and u*a + v*b = d still holds
+    let unit = old_r.normal_unit();
+    (old_s * unit.clone(), old_t * unit.clone(), old_r * unit)
 }
@@ -3,6 +3,10 @@ use num_integer::Integer;
 use num_traits::{One, Signed, Zero};
 
 pub fn lehmer_extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
+    if a.is_negative() || b.is_negative() {
+        let (u, v, d) = lehmer_extended_gcd(a.abs(), b.abs());
+        return (u * a.signum(), v * b.signum(), d);
+    }
     if b.is_zero() {
         return (BigInt::one(), BigInt::zero(), a.abs());
     }
@@ -40,8 +44,15 @@ pub fn lehmer_extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
             continue;
         }
 
-        let mut a_hat = leading_digit(&r0, base) as i128;
-        let mut b_hat = leading_digit(&r1, base) as i128;
+        // the digit of r1 in the place of the leading digit of r0
+        let d0 = base_digits(&r0, base);
+        let d1 = base_digits(&r1, base);
+        let mut a_hat = d0[d0.len() - 1] as i128;
+        let mut b_hat = if d1.len() == d0.len() {
+            d1[d1.len() - 1] as i128
+        } else {
+            0
+        };
         let mut a_a: i128 = 1;
         let mut b_b: i128 = 0;
         let mut c_c: i128 = 0;
@@ -104,11 +115,6 @@ pub fn lehmer_extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
     (s0, t0, r0)
 }
 
-fn leading_digit(n: &BigInt, base: u64) -> u64 {
-    let digits = base_digits(n, base);
-    *digits.last().unwrap_or(&0)
-}
-
 fn base_digits(n: &BigInt, base: u64) -> Vec<u64> {
     let mut digits = Vec::new();
     let mut x = n.abs().to_biguint().unwrap_or_default();
@@ -127,3 +133,37 @@ fn base_digits(n: &BigInt, base: u64) -> Vec<u64> {
 
     digits
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bezout_for_all_signs() {
+        let values = [
+            BigInt::zero(),
+            BigInt::from(6),
+            BigInt::from(1_000_000_007),
+            BigInt::from(10u32).pow(40) + 12u32,
+            BigInt::from(3u32).pow(60) * 4u32,
+        ];
+        for a in &values {
+            for b in &values {
+                for (a, b) in [
+                    (a.clone(), b.clone()),
+                    (-a, b.clone()),
+                    (a.clone(), -b),
+                    (-a, -b),
+                ] {
+                    let (u, v, d) = lehmer_extended_gcd(a.clone(), b.clone());
+                    assert_eq!(d, a.gcd(&b), "({}, {})", a, b);
+                    assert_eq!(&u * &a + &v * &b, d, "({}, {})", a, b);
+                }
+            }
+        }
+        assert_eq!(
+            lehmer_extended_gcd(BigInt::from(-6), BigInt::zero()),
+            (BigInt::from(-1), BigInt::zero(), BigInt::from(6))
+        );
+    }
+}
@@ -1,7 +1,15 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
 use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive};
 
+use crate::factor::factor;
 use crate::{right_left_binary_power, GroupElement};
 
+const PRIMORIAL_PRIMES: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+
 pub fn order_of_element<G>(g: G, h: &BigInt, factors: &[(BigInt, u32)]) -> BigInt
 where
     G: GroupElement,
@@ -18,3 +26,192 @@ where
     }
     e
 }
+
+// as above, factoring the group order h itself
+pub fn order_of_element_auto<G>(g: G, h: &BigInt) -> BigInt
+where
+    G: GroupElement,
+{
+    order_of_element(g, h, &factor(h))
+}
+
+// the order of g given only that a multiple of it lies in [lo, hi], as for
+// the order of a group known to lie in the Hasse interval: baby-step
+// giant-step finds such a multiple and it is then reduced to the exact order
+pub fn order_of_element_bsgs<G>(g: G, lo: &BigInt, hi: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    if lo > hi {
+        return None;
+    }
+    let w: BigInt = hi - lo + 1u32;
+    let mut m = w.sqrt();
+    if &m * &m < w {
+        m += 1u32;
+    }
+    let m_usize = m.to_usize()?;
+
+    // hashed elements all carry the representation of g
+    let mut baby: HashMap<G, usize> = HashMap::with_capacity(m_usize);
+    let mut x = g.identity_of();
+    for j in 0..m_usize {
+        baby.entry(x.clone()).or_insert(j);
+        x = x.mul(&g);
+    }
+    // x = g^m; the baby steps reach back m - 1 from each base, so the last
+    // giant step is the first base at or past hi
+    let mut y = right_left_binary_power(g.clone(), lo.clone());
+    let mut base = lo.clone();
+    let end = hi + &m;
+    while base < end {
+        // g^(base - j) = 1
+        if let Some(&j) = baby.get(&y) {
+            let n = &base - j;
+            if n.is_positive() {
+                return Some(order_of_element(g, &n, &factor(&n)));
+            }
+        }
+        y = y.mul(&x);
+        base += &m;
+    }
+    None
+}
+
+// the order of g given only an upper bound on it, by Sutherland's primorial
+// steps: g^E for E made of the small primes has order prime to the primorial
+// P, so only the baby steps j prime to P need storing
+pub fn order_of_element_primorial<G>(g: G, bound: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    // P as large as the number of baby steps allows
+    let mut primes = Vec::new();
+    let mut p_prod = 1u64;
+    for &p in PRIMORIAL_PRIMES.iter() {
+        if BigInt::from(p_prod * p).pow(2) > *bound {
+            break;
+        }
+        p_prod *= p;
+        primes.push(p);
+    }
+    let phi: u64 = primes.iter().map(|p| p - 1).product();
+
+    // E = prod p^e with p^e <= bound
+    let mut e_factors = Vec::new();
+    let mut e = BigInt::one();
+    for &p in &primes {
+        let p = BigInt::from(p);
+        let mut k = 0u32;
+        let mut pk = p.clone();
+        while &pk <= bound {
+            pk *= &p;
+            k += 1;
+        }
+        if k > 0 {
+            e *= p.pow(k);
+            e_factors.push((p, k));
+        }
+    }
+    let beta = right_left_binary_power(g.clone(), e.clone());
+
+    let beta_order = if beta == G::identity() {
+        BigInt::one()
+    } else {
+        // steps of M, a multiple of P, with about as many baby steps M phi(P) / P
+        // as giant steps bound / M
+        let m = (bound * p_prod / phi).sqrt() / p_prod + 1u32;
+        let m = (m * p_prod).to_u64()?;
+        let coprime: Vec<u64> = (1..=m)
+            .filter(|j| primes.iter().all(|p| j % p != 0))
+            .collect();
+
+        // walk the residues prime to P using a table of the gaps between them
+        let max_gap = coprime.windows(2).map(|w| w[1] - w[0]).max().unwrap_or(1);
+        let mut gaps = vec![beta.identity_of()];
+        for i in 1..=max_gap as usize {
+            gaps.push(gaps[i - 1].mul(&beta));
+        }
+        let mut baby: HashMap<G, u64> = HashMap::with_capacity(coprime.len());
+        let mut x = beta.clone();
+        let mut prev = 1;
+        for &j in &coprime {
+            x = x.mul(&gaps[(j - prev) as usize]);
+            prev = j;
+            baby.entry(x.clone()).or_insert(j);
+        }
+
+        // the order N is prime to P, so N = i M - j for some baby step j
+        let giant = right_left_binary_power(beta.clone(), BigInt::from(m));
+        let mut y = giant.clone();
+        let mut i = BigInt::one();
+        loop {
+            if let Some(&j) = baby.get(&y) {
+                let n = &i * m - j;
+                if n.is_positive() {
+                    break order_of_element(beta.clone(), &n, &factor(&n));
+                }
+            }
+            if &i * m > bound + m {
+                return None;
+            }
+            y = y.mul(&giant);
+            i += 1u32;
+        }
+    };
+
+    // what remains has order dividing E
+    let rest = right_left_binary_power(g, beta_order.clone());
+    Some(beta_order * order_of_element(rest, &e, &e_factors))
+}
+
+// the exponent of the group generated by gens, the lcm of their orders, each
+// at most bound
+pub fn exponent_of_group<G>(gens: &[G], bound: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    let mut exponent = BigInt::one();
+    for g in gens {
+        exponent = exponent.lcm(&order_of_element_primorial(g.clone(), bound)?);
+    }
+    Some(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FpContext;
+
+    #[test]
+    fn bsgs_finds_a_multiple_at_the_top_of_the_interval() {
+        let ctx = FpContext::new(&BigInt::from(29));
+        // 16 = 2^4 has order 7, and 14 is the only multiple in [10, 14]
+        let g = ctx.elem(&BigInt::from(16));
+        let lo = BigInt::from(10);
+        let hi = BigInt::from(14);
+        assert_eq!(order_of_element_bsgs(g, &lo, &hi), Some(BigInt::from(7)));
+    }
+
+    #[test]
+    fn bsgs_matches_brute_force_near_the_order() {
+        let p = 1009u32;
+        let ctx = FpContext::new(&BigInt::from(p));
+        for a in 2..60u32 {
+            let g = ctx.elem(&BigInt::from(a));
+            let mut ord = 1u32;
+            let mut x = g.clone();
+            while !x.is_one() {
+                x = &x * &g;
+                ord += 1;
+            }
+            let ord = BigInt::from(ord);
+            let lo = (&ord - 3u32).max(BigInt::one());
+            assert_eq!(
+                order_of_element_bsgs(g.clone(), &lo, &ord),
+                Some(ord.clone())
+            );
+            assert_eq!(order_of_element_primorial(g, &BigInt::from(p)), Some(ord));
+        }
+    }
+}
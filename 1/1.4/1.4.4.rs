@@ -1,44 +1,43 @@
 use num_bigint::BigInt;
-use num_integer::Integer;
-use num_traits::{One, Zero};
+use num_traits::One;
 
-pub fn primitive_root_mod_p(p: &BigInt, factors: &[(BigInt, u32)]) -> BigInt {
-    assert!(p > &BigInt::from(2));
+use crate::error::Error;
+use crate::factor::factor;
+use crate::fp::FpContext;
+use crate::primality::is_probable_prime;
+
+pub fn primitive_root_mod_p(p: &BigInt, factors: &[(BigInt, u32)]) -> Result<BigInt, Error> {
+    if p <= &BigInt::from(2) {
+        return Err(Error::InvalidArgument("p must be an odd prime"));
+    }
+    let ctx = FpContext::new(p);
     let phi = p - BigInt::one();
     let mut a = BigInt::one();
 
     loop {
         a += 1;
+        if &a >= p {
+            return Err(Error::NotPrime);
+        }
+        let g = ctx.elem(&a);
         let mut ok = true;
         for (prime, _) in factors {
             let exp = &phi / prime;
-            if mod_pow(&a, &exp, p).is_one() {
+            if g.pow(&exp).is_one() {
                 ok = false;
                 break;
             }
         }
         if ok {
-            return a;
+            return Ok(a);
         }
     }
 }
 
-fn mod_pow(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> BigInt {
-    if modulus.is_one() {
-        return BigInt::zero();
-    }
-    let mut result = BigInt::one();
-    let mut base = base.mod_floor(modulus);
-    let mut e = exp.clone();
-    while e > BigInt::zero() {
-        if e.is_odd() {
-            result = (result * &base).mod_floor(modulus);
-        }
-        e >>= 1;
-        if e.is_zero() {
-            break;
-        }
-        base = (&base * &base).mod_floor(modulus);
+// as above, factoring p - 1 itself
+pub fn primitive_root_mod_p_auto(p: &BigInt) -> Result<BigInt, Error> {
+    if p > &BigInt::from(2) && !is_probable_prime(p) {
+        return Err(Error::NotPrime);
     }
-    result
+    primitive_root_mod_p(p, &factor(&(p - BigInt::one())))
 }
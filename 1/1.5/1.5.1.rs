@@ -2,17 +2,21 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Zero};
 
-use crate::arith::mod_pow;
+use crate::error::Error;
+use crate::fp::FpContext;
 use crate::kronecker;
 
-pub fn sqrt_mod_prime(a: BigInt, p: BigInt) -> Option<BigInt> {
-    assert!(p > BigInt::from(2) && p.is_odd());
-    let a = a.mod_floor(&p);
+pub fn sqrt_mod_prime(a: BigInt, p: BigInt) -> Result<Option<BigInt>, Error> {
+    if p <= BigInt::from(2) || p.is_even() {
+        return Err(Error::InvalidArgument("p must be an odd prime"));
+    }
+    let ctx = FpContext::new(&p);
+    let a = ctx.elem(&a);
     if a.is_zero() {
-        return Some(BigInt::zero());
+        return Ok(Some(BigInt::zero()));
     }
-    if kronecker(a.clone(), p.clone()) != 1 {
-        return None;
+    if kronecker(ctx.residue(&a), p.clone()) != 1 {
+        return Ok(None);
     }
 
     // factor p-1 = 2^e * q
@@ -27,38 +31,41 @@ pub fn sqrt_mod_prime(a: BigInt, p: BigInt) -> Option<BigInt> {
     let mut n = BigInt::from(2);
     while kronecker(n.clone(), p.clone()) != -1 {
         n += 1;
+        if n >= p {
+            return Err(Error::NotPrime);
+        }
     }
-    let z = mod_pow(&n, &q, &p);
+    let z = ctx.elem(&n).pow(&q);
 
-    let mut y = z.clone();
+    let mut y = z;
     let mut r = e;
-    let mut x = mod_pow(&a, &((&q - BigInt::one()) >> 1), &p);
-    let mut b = (&a * &x * &x).mod_floor(&p);
-    x = (&a * x).mod_floor(&p);
+    let mut x = a.pow(&((&q - BigInt::one()) >> 1));
+    let mut b = &(&a * &x) * &x;
+    x = &a * &x;
 
     loop {
         if b.is_one() {
-            return Some(x);
+            return Ok(Some(ctx.residue(&x)));
         }
         let mut m = 1usize;
-        let mut b2m = (&b * &b).mod_floor(&p);
+        let mut b2m = &b * &b;
         while !b2m.is_one() {
             m += 1;
-            b2m = (&b2m * &b2m).mod_floor(&p);
+            b2m = &b2m * &b2m;
             if m == r {
-                return None;
+                return Err(Error::NotPrime);
             }
         }
 
         let mut t_exp = r - m - 1;
         let mut t = y.clone();
         while t_exp > 0 {
-            t = (&t * &t).mod_floor(&p);
+            t = &t * &t;
             t_exp -= 1;
         }
-        y = (&t * &t).mod_floor(&p);
+        y = &t * &t;
         r = m;
-        x = (x * t).mod_floor(&p);
-        b = (b * &y).mod_floor(&p);
+        x = x * t;
+        b = b * y.clone();
     }
 }
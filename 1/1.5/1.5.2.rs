@@ -2,15 +2,21 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{Signed, Zero};
 
+use crate::error::Error;
 use crate::{integer_sqrt, kronecker_binary, sqrt_mod_prime};
 
-pub fn cornacchia(p: BigInt, d: BigInt) -> Option<(BigInt, BigInt)> {
-    assert!(p.is_positive() && d.is_positive() && d < p);
+pub fn cornacchia(p: BigInt, d: BigInt) -> Result<Option<(BigInt, BigInt)>, Error> {
+    if !p.is_positive() || !d.is_positive() || d >= p {
+        return Err(Error::InvalidArgument("need 0 < d < p"));
+    }
     if kronecker_binary(-d.clone(), p.clone()) == -1 {
-        return None;
+        return Ok(None);
     }
 
-    let mut x0 = sqrt_mod_prime((-d.clone()).mod_floor(&p), p.clone())?;
+    let mut x0 = match sqrt_mod_prime((-d.clone()).mod_floor(&p), p.clone())? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
     if x0 < p.clone() / 2 {
         x0 = p.clone() - x0;
     }
@@ -26,12 +32,12 @@ pub fn cornacchia(p: BigInt, d: BigInt) -> Option<(BigInt, BigInt)> {
 
     let diff = p - &b * &b;
     if diff.mod_floor(&d) != BigInt::zero() {
-        return None;
+        return Ok(None);
     }
     let c = diff / d;
     let y = integer_sqrt(c.clone());
     if &y * &y != c {
-        return None;
+        return Ok(None);
     }
-    Some((b, y))
+    Ok(Some((b, y)))
 }
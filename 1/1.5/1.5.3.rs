@@ -2,30 +2,38 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Signed, Zero};
 
+use crate::error::Error;
 use crate::{integer_sqrt, kronecker_binary, sqrt_mod_prime};
 
-pub fn cornacchia_modified(p: BigInt, d: BigInt) -> Option<(BigInt, BigInt)> {
-    assert!(p.is_positive());
-    assert!(d.is_negative());
+pub fn cornacchia_modified(p: BigInt, d: BigInt) -> Result<Option<(BigInt, BigInt)>, Error> {
+    if !p.is_positive() {
+        return Err(Error::InvalidArgument("p must be positive"));
+    }
+    if !d.is_negative() {
+        return Err(Error::InvalidArgument("d must be negative"));
+    }
 
     if p == BigInt::from(2) {
         let candidate = &d + BigInt::from(8);
         if candidate.is_negative() {
-            return None;
+            return Ok(None);
         }
         let s = integer_sqrt(candidate.clone());
         if &s * &s == candidate {
-            return Some((s, BigInt::one()));
+            return Ok(Some((s, BigInt::one())));
         } else {
-            return None;
+            return Ok(None);
         }
     }
 
     if kronecker_binary(d.clone(), p.clone()) == -1 {
-        return None;
+        return Ok(None);
     }
 
-    let mut x0 = sqrt_mod_prime(d.mod_floor(&p), p.clone())?;
+    let mut x0 = match sqrt_mod_prime(d.mod_floor(&p), p.clone())? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
     if (&x0 - &d).mod_floor(&BigInt::from(2)) != BigInt::zero() {
         x0 = p.clone() - x0;
     }
@@ -42,12 +50,12 @@ pub fn cornacchia_modified(p: BigInt, d: BigInt) -> Option<(BigInt, BigInt)> {
     let diff: BigInt = (&p * BigInt::from(4)) - &b * &b;
     let d_abs = d.abs();
     if diff.mod_floor(&d_abs) != BigInt::zero() {
-        return None;
+        return Ok(None);
     }
     let c = diff / d_abs;
     let y = integer_sqrt(c.clone());
     if &y * &y != c {
-        return None;
+        return Ok(None);
     }
-    Some((b, y))
+    Ok(Some((b, y)))
 }
@@ -1,14 +1,14 @@
 use num_bigint::BigInt;
+use num_integer::Integer;
 use num_traits::{One, Zero};
 
-use crate::arith::int_sqrt;
+use crate::primality::{is_probable_prime, is_strong_probable_prime};
 
 pub fn prime_power_test(n: BigInt) -> Option<BigInt> {
     if n <= BigInt::one() {
         return None;
     }
 
-    // find smallest prime divisor
     if (n.clone() % 2u32).is_zero() {
         return if is_power_of(&n, &BigInt::from(2)) {
             Some(BigInt::from(2))
@@ -17,42 +17,41 @@ pub fn prime_power_test(n: BigInt) -> Option<BigInt> {
         };
     }
 
-    let mut p = BigInt::from(3);
-    let limit = int_sqrt(&n);
-    while &p <= &limit {
-        if (n.clone() % &p).is_zero() {
-            if is_prime(&p) && is_power_of(&n, &p) {
-                return Some(p);
-            } else {
-                return None;
-            }
-        }
-        p += 2u32;
-    }
-
     // n is prime, so not a prime power with exponent>1
-    None
-}
-
-fn is_prime(n: &BigInt) -> bool {
-    if *n < BigInt::from(2) {
-        return false;
-    }
-    if *n == BigInt::from(2) || *n == BigInt::from(3) {
-        return true;
-    }
-    if (n % 2u32).is_zero() || (n % 3u32).is_zero() {
-        return false;
+    if is_probable_prime(&n) {
+        return None;
     }
-    let mut i = BigInt::from(5);
-    let limit = int_sqrt(n);
-    while &i <= &limit {
-        if (n % &i).is_zero() || (n % (&i + 2u32)).is_zero() {
-            return false;
+
+    // if n = p^k then a^q = a (mod p) for every power q of p, so gcd(a^q - a, q)
+    // shrinks q towards p until it is prime
+    let mut q = n.clone();
+    let p = 'outer: loop {
+        if is_probable_prime(&q) {
+            break q;
         }
-        i += 6u32;
+        // d = q happens for every base when q is a Carmichael number, but only for
+        // rare Wieferich-type bases when q is a prime power
+        for a in 2u32..64 {
+            let a = BigInt::from(a);
+            if !is_strong_probable_prime(&q, &a) {
+                let d = (a.modpow(&q, &q) - &a).gcd(&q);
+                if d.is_one() {
+                    return None;
+                }
+                if d != q {
+                    q = d;
+                    continue 'outer;
+                }
+            }
+        }
+        return None;
+    };
+
+    if is_power_of(&n, &p) {
+        Some(p)
+    } else {
+        None
     }
-    true
 }
 
 fn is_power_of(n: &BigInt, p: &BigInt) -> bool {
@@ -1,12 +1,11 @@
-use num_rational::BigRational;
-use num_traits::Zero;
-
+use crate::error::Error;
 use crate::matrix::{Matrix, Vector};
+use crate::ring::Field;
 
-pub fn solve_linear_system(mut m: Matrix, mut b: Vector) -> Option<Vector> {
-    let n = m.len();
-    if m.iter().any(|row| row.len() != n) || b.len() != n {
-        return None;
+pub fn solve_linear_system<F: Field>(mut m: Matrix<F>, mut b: Vector<F>) -> Result<Vector<F>, Error> {
+    let n = m.nrows();
+    if !m.is_square() || b.len() != n {
+        return Err(Error::DimensionMismatch);
     }
 
     let mut j: isize = -1;
@@ -22,39 +21,38 @@ pub fn solve_linear_system(mut m: Matrix, mut b: Vector) -> Option<Vector> {
                 break;
             }
         }
-        let i = pivot?;
+        let i = pivot.ok_or(Error::NotInvertible)?;
 
         if i > col {
-            m.swap(i, col);
+            m.swap_rows(i, col);
             b.swap(i, col);
         }
 
-        let mjj = m[col][col].clone();
-        let d = mjj.recip();
-        let mut c: Vec<BigRational> = vec![BigRational::zero(); n];
+        let d = m[col][col].inv().ok_or(Error::NotInvertible)?;
+        let mut c: Vec<F> = vec![F::zero(); n];
         for k in (col + 1)..n {
-            c[k] = &d * &m[k][col];
+            c[k] = d.clone() * m[k][col].clone();
         }
 
         for k in (col + 1)..n {
             for l in (col + 1)..n {
-                m[k][l] = &m[k][l] - &c[k] * &m[col][l];
+                m[k][l] = m[k][l].clone() - c[k].clone() * m[col][l].clone();
             }
-            b[k] = &b[k] - &c[k] * &b[col];
+            b[k] = b[k].clone() - c[k].clone() * b[col].clone();
         }
     }
 
-    let mut x = vec![BigRational::zero(); n];
+    let mut x = vec![F::zero(); n];
     for i_rev in 0..n {
         let i = n - 1 - i_rev;
-        let mut sum = BigRational::zero();
+        let mut sum = F::zero();
         for j in (i + 1)..n {
-            sum += &m[i][j] * &x[j];
+            sum = sum + m[i][j].clone() * x[j].clone();
         }
         if m[i][i].is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
-        x[i] = (&b[i] - sum) / m[i][i].clone();
+        x[i] = (b[i].clone() - sum) / m[i][i].clone();
     }
-    Some(x)
+    Ok(x)
 }
@@ -1,14 +1,13 @@
-use num_rational::BigRational;
-use num_traits::Zero;
+use crate::error::Error;
+use crate::matrix::Matrix;
+use crate::ring::Field;
 
-use crate::matrix::{identity, Matrix};
-
-pub fn inverse(mut m: Matrix) -> Option<Matrix> {
-    let n = m.len();
-    if m.iter().any(|row| row.len() != n) {
-        return None;
+pub fn inverse<F: Field>(mut m: Matrix<F>) -> Result<Matrix<F>, Error> {
+    let n = m.nrows();
+    if !m.is_square() {
+        return Err(Error::DimensionMismatch);
     }
-    let mut b = identity(n);
+    let mut b: Matrix<F> = Matrix::identity(n);
 
     let mut j: isize = -1;
     while {
@@ -23,43 +22,42 @@ pub fn inverse(mut m: Matrix) -> Option<Matrix> {
                 break;
             }
         }
-        let i = pivot?;
+        let i = pivot.ok_or(Error::NotInvertible)?;
 
         if i > col {
-            m.swap(i, col);
-            b.swap(i, col);
+            m.swap_rows(i, col);
+            b.swap_rows(i, col);
         }
 
-        let mjj = m[col][col].clone();
-        let d = mjj.recip();
-        let mut c: Vec<BigRational> = vec![BigRational::zero(); n];
+        let d = m[col][col].inv().ok_or(Error::NotInvertible)?;
+        let mut c: Vec<F> = vec![F::zero(); n];
         for k in (col + 1)..n {
-            c[k] = &d * &m[k][col];
+            c[k] = d.clone() * m[k][col].clone();
         }
 
         for k in (col + 1)..n {
             for l in (col + 1)..n {
-                m[k][l] = &m[k][l] - &c[k] * &m[col][l];
+                m[k][l] = m[k][l].clone() - c[k].clone() * m[col][l].clone();
             }
             for l in 0..n {
-                b[k][l] = &b[k][l] - &c[k] * &b[col][l];
+                b[k][l] = b[k][l].clone() - c[k].clone() * b[col][l].clone();
             }
         }
     }
 
-    let mut x = vec![vec![BigRational::zero(); n]; n];
+    let mut x: Matrix<F> = Matrix::zeros(n, n);
     for i_rev in 0..n {
         let i = n - 1 - i_rev;
         for r in 0..n {
-            let mut sum = BigRational::zero();
+            let mut sum = F::zero();
             for j in (i + 1)..n {
-                sum += &m[i][j] * &x[j][r];
+                sum = sum + m[i][j].clone() * x[j][r].clone();
             }
             if m[i][i].is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
-            x[i][r] = (&b[i][r] - sum) / m[i][i].clone();
+            x[i][r] = (b[i][r].clone() - sum) / m[i][i].clone();
         }
     }
-    Some(x)
+    Ok(x)
 }
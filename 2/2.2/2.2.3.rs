@@ -1,15 +1,14 @@
-use num_rational::BigRational;
-use num_traits::{One, Zero};
-
+use crate::error::Error;
 use crate::matrix::Matrix;
+use crate::ring::Field;
 
-pub fn determinant(mut m: Matrix) -> Option<BigRational> {
-    let n = m.len();
-    if m.iter().any(|row| row.len() != n) {
-        return None;
+pub fn determinant<F: Field>(mut m: Matrix<F>) -> Result<F, Error> {
+    let n = m.nrows();
+    if !m.is_square() {
+        return Err(Error::DimensionMismatch);
     }
-    let mut det = BigRational::one();
-    let mut sign = BigRational::one();
+    let mut det = F::one();
+    let mut sign = F::one();
     for j in 0..n {
         let mut pivot = None;
         for i in j..n {
@@ -20,21 +19,21 @@ pub fn determinant(mut m: Matrix) -> Option<BigRational> {
         }
         let i = match pivot {
             Some(v) => v,
-            None => return Some(BigRational::zero()),
+            None => return Ok(F::zero()),
         };
         if i > j {
-            m.swap(i, j);
+            m.swap_rows(i, j);
             sign = -sign;
         }
         let mjj = m[j][j].clone();
-        det *= &mjj;
-        let d = mjj.recip();
+        let d = mjj.inv().ok_or(Error::NotInvertible)?;
+        det = det * mjj;
         for k in (j + 1)..n {
-            let c = &d * &m[k][j];
+            let c = d.clone() * m[k][j].clone();
             for l in (j + 1)..n {
-                m[k][l] = &m[k][l] - &c * &m[j][l];
+                m[k][l] = m[k][l].clone() - c.clone() * m[j][l].clone();
             }
         }
     }
-    Some(det * sign)
+    Ok(det * sign)
 }
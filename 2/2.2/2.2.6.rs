@@ -1,18 +1,18 @@
-use num_bigint::BigInt;
-use num_integer::Integer;
-use num_traits::{One, Zero};
+use crate::error::Error;
+use crate::matrix::Matrix;
+use crate::ring::EuclideanDomain;
 
-pub fn gauss_bareiss_det(mut m: Vec<Vec<BigInt>>) -> Option<BigInt> {
-    let n = m.len();
-    if n == 0 || m.iter().any(|row| row.len() != n) {
-        return None;
+pub fn gauss_bareiss_det<R: EuclideanDomain>(mut m: Matrix<R>) -> Result<R, Error> {
+    let n = m.nrows();
+    if !m.is_square() {
+        return Err(Error::DimensionMismatch);
     }
-    let mut c = BigInt::one();
-    let mut sign = BigInt::one();
-    for k in 0..n {
-        if k == n - 1 {
-            return Some(sign * m[k][k].clone());
-        }
+    if n == 0 {
+        return Ok(R::one());
+    }
+    let mut c = R::one();
+    let mut sign = R::one();
+    for k in 0..(n - 1) {
         if m[k][k].is_zero() {
             let mut pivot = None;
             for i in (k + 1)..n {
@@ -22,22 +22,23 @@ pub fn gauss_bareiss_det(mut m: Vec<Vec<BigInt>>) -> Option<BigInt> {
                 }
             }
             if let Some(i) = pivot {
-                m.swap(k, i);
+                m.swap_rows(k, i);
                 sign = -sign;
             } else {
-                return Some(BigInt::zero());
+                return Ok(R::zero());
             }
         }
         let p = m[k][k].clone();
         for i in (k + 1)..n {
             for j in (k + 1)..n {
-                let t = &p * &m[i][j] - &m[i][k] * &m[k][j];
+                let t = p.clone() * m[i][j].clone() - m[i][k].clone() * m[k][j].clone();
                 if c.is_one() {
                     m[i][j] = t;
                 } else {
-                    let (q, r) = t.div_rem(&c);
+                    // exact division by the previous pivot
+                    let (q, r) = t.quo_rem(&c);
                     if !r.is_zero() {
-                        return None;
+                        return Err(Error::InvalidArgument("inexact division by the previous pivot"));
                     }
                     m[i][j] = q;
                 }
@@ -45,5 +46,5 @@ pub fn gauss_bareiss_det(mut m: Vec<Vec<BigInt>>) -> Option<BigInt> {
         }
         c = p;
     }
-    None
+    Ok(sign * m[n - 1][n - 1].clone())
 }
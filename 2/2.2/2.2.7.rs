@@ -1,37 +1,35 @@
-use num_traits::{One, Signed};
+use crate::error::Error;
+use crate::matrix::Matrix;
+use crate::poly::Poly;
+use crate::ring::Field;
 
-use crate::matrix::{identity, mat_add, mat_mul, scalar_identity, trace, Matrix};
-
-pub fn characteristic_polynomial_and_adjoint(
-    m: Matrix,
-) -> Option<(Vec<num_rational::BigRational>, Matrix)> {
-    let n = m.len();
-    if m.iter().any(|row| row.len() != n) {
-        return None;
+pub fn characteristic_polynomial_and_adjoint<F: Field>(
+    m: Matrix<F>,
+) -> Result<(Poly<F>, Matrix<F>), Error> {
+    let n = m.nrows();
+    if !m.is_square() {
+        return Err(Error::DimensionMismatch);
     }
-    let mut c = identity(n);
+    let mut c: Matrix<F> = Matrix::identity(n);
     let mut coeffs = Vec::with_capacity(n + 1);
-    coeffs.push(num_rational::BigRational::one());
+    coeffs.push(F::one());
+    let mut denom = F::zero();
+    // C_(n-1), kept since C_n = M C_(n-1) + a_n I vanishes by Cayley-Hamilton
+    let mut last = c.clone();
     for i in 1..=n {
-        c = mat_mul(&m, &c);
-        let tr = trace(&c);
-        let denom = num_rational::BigRational::from_integer((i as i64).into());
-        let ai = -&tr / denom;
-        coeffs.push(ai.clone());
-        c = mat_add(&c, &scalar_identity(ai.clone(), n));
-    }
-    let adj_sign = if (n - 1) % 2 == 0 {
-        num_rational::BigRational::one()
-    } else {
-        -num_rational::BigRational::one()
-    };
-    let mut adj = c;
-    if adj_sign.is_negative() {
-        for row in adj.iter_mut() {
-            for x in row.iter_mut() {
-                *x = -x.clone();
-            }
+        if i == n {
+            last = c.clone();
         }
+        c = &m * &c;
+        let tr = c.trace()?;
+        denom = denom + F::one();
+        let ai = -tr * denom.inv().ok_or(Error::NotInvertible)?;
+        coeffs.push(ai.clone());
+        c = &c + &Matrix::scalar(ai, n);
     }
-    Some((coeffs, adj))
+    // adjoint is (-1)^(n-1) C_(n-1)
+    let adj = if n.is_multiple_of(2) { -last } else { last };
+    // coeffs holds a_0 = 1, a_1, ..., a_n from X^n down to X^0
+    coeffs.reverse();
+    Ok((Poly::new(coeffs), adj))
 }
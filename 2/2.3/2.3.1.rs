@@ -1,19 +1,17 @@
-use num_rational::BigRational;
-use num_traits::{One, Zero};
+use crate::matrix::{Matrix, Vector};
+use crate::ring::Field;
 
-use crate::matrix::Matrix;
-
-pub fn kernel_basis(m: Matrix) -> Vec<Vec<BigRational>> {
+pub fn kernel_basis<F: Field>(m: Matrix<F>) -> Vec<Vector<F>> {
     let (rref, pivots) = rref_with_pivots(m);
-    let n_cols = if let Some(row) = rref.first() { row.len() } else { 0 };
+    let n_cols = rref.ncols();
     let pivot_set: std::collections::HashSet<usize> = pivots.iter().cloned().collect();
     let mut basis = Vec::new();
     for free_col in 0..n_cols {
         if pivot_set.contains(&free_col) {
             continue;
         }
-        let mut v = vec![BigRational::zero(); n_cols];
-        v[free_col] = BigRational::one();
+        let mut v = vec![F::zero(); n_cols];
+        v[free_col] = F::one();
         for (row, &pivot_col) in pivots.iter().enumerate() {
             v[pivot_col] = -rref[row][free_col].clone();
         }
@@ -22,12 +20,8 @@ pub fn kernel_basis(m: Matrix) -> Vec<Vec<BigRational>> {
     basis
 }
 
-fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
-    let m = a.len();
-    if m == 0 {
-        return (a, Vec::new());
-    }
-    let n = a[0].len();
+fn rref_with_pivots<F: Field>(mut a: Matrix<F>) -> (Matrix<F>, Vec<usize>) {
+    let (m, n) = a.shape();
     let mut row = 0;
     let mut pivots = Vec::new();
     for col in 0..n {
@@ -46,11 +40,11 @@ fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
             None => continue,
         };
         if pivot_row != row {
-            a.swap(pivot_row, row);
+            a.swap_rows(pivot_row, row);
         }
-        let inv = a[row][col].clone().recip();
+        let inv = F::one() / a[row][col].clone();
         for c in col..n {
-            a[row][c] *= inv.clone();
+            a[row][c] = a[row][c].clone() * inv.clone();
         }
         for r in 0..m {
             if r == row {
@@ -61,7 +55,7 @@ fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
             }
             let factor = a[r][col].clone();
             for c in col..n {
-                a[r][c] = a[r][c].clone() - &factor * &a[row][c];
+                a[r][c] = a[r][c].clone() - factor.clone() * a[row][c].clone();
             }
         }
         pivots.push(col);
@@ -69,3 +63,123 @@ fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
     }
     (a, pivots)
 }
+
+// the same elimination over F_2 with rows packed into 64-bit words, as used
+// by sieve factoring methods; kernel vectors are returned packed the same way
+pub fn kernel_basis_mod2(rows: &[Vec<u64>], n_cols: usize) -> Vec<Vec<u64>> {
+    let words = n_cols.div_ceil(64);
+    let mut a: Vec<Vec<u64>> = rows.to_vec();
+    let m = a.len();
+    let bit = |v: &[u64], c: usize| (v[c / 64] >> (c % 64)) & 1 == 1;
+
+    let mut row = 0;
+    let mut pivots = Vec::new();
+    for col in 0..n_cols {
+        if row >= m {
+            break;
+        }
+        let pivot_row = match (row..m).find(|&r| bit(&a[r], col)) {
+            Some(r) => r,
+            None => continue,
+        };
+        a.swap(pivot_row, row);
+        let pivot = a[row].clone();
+        for (r, other) in a.iter_mut().enumerate() {
+            if r != row && bit(other, col) {
+                for (w, p) in other.iter_mut().zip(&pivot) {
+                    *w ^= p;
+                }
+            }
+        }
+        pivots.push(col);
+        row += 1;
+    }
+
+    let mut is_pivot = vec![false; n_cols];
+    for &c in &pivots {
+        is_pivot[c] = true;
+    }
+    let mut basis = Vec::new();
+    for free_col in 0..n_cols {
+        if is_pivot[free_col] {
+            continue;
+        }
+        let mut v = vec![0u64; words];
+        v[free_col / 64] |= 1 << (free_col % 64);
+        for (r, &pivot_col) in pivots.iter().enumerate() {
+            if bit(&a[r], free_col) {
+                v[pivot_col / 64] |= 1 << (pivot_col % 64);
+            }
+        }
+        basis.push(v);
+    }
+    basis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(rows: &[Vec<u64>], v: &[u64]) -> bool {
+        rows.iter().all(|r| {
+            r.iter()
+                .zip(v)
+                .map(|(a, b)| (a & b).count_ones())
+                .sum::<u32>()
+                % 2
+                == 0
+        })
+    }
+
+    #[test]
+    fn kernel_mod2_matches_enumeration() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for n_cols in 1..=12 {
+            for n_rows in 0..=10 {
+                let rows: Vec<Vec<u64>> = (0..n_rows)
+                    .map(|_| vec![next() & next() & ((1 << n_cols) - 1)])
+                    .collect();
+                let basis = kernel_basis_mod2(&rows, n_cols);
+                let kernel = (0..1u64 << n_cols).filter(|&x| times(&rows, &[x])).count();
+                assert_eq!(kernel, 1 << basis.len());
+                // the 2^k sums of basis vectors are distinct kernel vectors
+                let mut span: Vec<u64> = (0..1u64 << basis.len())
+                    .map(|s| {
+                        (0..basis.len())
+                            .filter(|&i| s >> i & 1 == 1)
+                            .fold(0, |x, i| x ^ basis[i][0])
+                    })
+                    .collect();
+                assert!(span.iter().all(|&x| times(&rows, &[x])));
+                span.sort();
+                span.dedup();
+                assert_eq!(span.len(), kernel);
+            }
+        }
+    }
+
+    #[test]
+    fn kernel_mod2_across_words() {
+        // row r is e_r + e_(r + 130), truncated to 150 columns, so the kernel
+        // is spanned by the e_c + e_(c + 130) for c < 20
+        let rows: Vec<Vec<u64>> = (0..130)
+            .map(|r| {
+                let mut v = vec![0u64; 3];
+                for c in [r, r + 130].into_iter().filter(|&c| c < 150) {
+                    v[c / 64] |= 1 << (c % 64);
+                }
+                v
+            })
+            .collect();
+        let basis = kernel_basis_mod2(&rows, 150);
+        assert_eq!(basis.len(), 20);
+        assert!(basis.iter().all(|v| times(&rows, v)));
+        assert_eq!(kernel_basis_mod2(&[], 70).len(), 70);
+    }
+}
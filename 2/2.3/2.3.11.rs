@@ -1,13 +1,8 @@
-use num_traits::Zero;
+use crate::matrix::Matrix;
+use crate::ring::Field;
 
-use crate::Matrix;
-
-pub fn column_echelon_form(mut m: Matrix) -> Matrix {
-    let rows = m.len();
-    let n_cols = m.first().map(|row| row.len()).unwrap_or(0);
-    if m.iter().any(|row| row.len() != n_cols) {
-        return m;
-    }
+pub fn column_echelon_form<F: Field>(mut m: Matrix<F>) -> Matrix<F> {
+    let (rows, n_cols) = m.shape();
     if rows == 0 {
         return m;
     }
@@ -45,17 +40,17 @@ pub fn column_echelon_form(mut m: Matrix) -> Matrix {
             }
         };
         let k_idx = k as usize;
-        let d = m[i as usize][pivot_col].clone().recip();
+        let d = F::one() / m[i as usize][pivot_col].clone();
 
         for l in 0..=i as usize {
-            let t = &d * &m[l][pivot_col];
+            let t = d.clone() * m[l][pivot_col].clone();
             if pivot_col != k_idx {
                 m[l][pivot_col] = m[l][k_idx].clone();
             }
             m[l][k_idx] = t;
         }
 
-        let pivot_row = m[i as usize].clone();
+        let pivot_row = m.row(i as usize).to_vec();
         for j2 in 0..n_cols {
             if j2 == k_idx {
                 continue;
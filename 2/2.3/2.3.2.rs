@@ -1,23 +1,17 @@
-use num_traits::Zero;
-
 use crate::matrix::{Matrix, Vector};
+use crate::ring::Field;
 
-pub fn image_basis(m: Matrix) -> Vec<Vector> {
+pub fn image_basis<F: Field>(m: Matrix<F>) -> Vec<Vector<F>> {
     let (_rref, pivots) = rref_with_pivots(m.clone());
     let mut basis = Vec::new();
     for &pivot_col in pivots.iter() {
-        let col_vec: Vector = m.iter().map(|row| row[pivot_col].clone()).collect();
-        basis.push(col_vec);
+        basis.push(m.column(pivot_col));
     }
     basis
 }
 
-fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
-    let m_rows = a.len();
-    if m_rows == 0 {
-        return (a, Vec::new());
-    }
-    let n_cols = a[0].len();
+fn rref_with_pivots<F: Field>(mut a: Matrix<F>) -> (Matrix<F>, Vec<usize>) {
+    let (m_rows, n_cols) = a.shape();
     let mut row = 0usize;
     let mut pivots = Vec::new();
     for col in 0..n_cols {
@@ -36,11 +30,11 @@ fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
             None => continue,
         };
         if pivot_row != row {
-            a.swap(pivot_row, row);
+            a.swap_rows(pivot_row, row);
         }
-        let inv = a[row][col].clone().recip();
+        let inv = F::one() / a[row][col].clone();
         for c in col..n_cols {
-            a[row][c] *= inv.clone();
+            a[row][c] = a[row][c].clone() * inv.clone();
         }
         for r in 0..m_rows {
             if r == row {
@@ -51,7 +45,7 @@ fn rref_with_pivots(mut a: Matrix) -> (Matrix, Vec<usize>) {
             }
             let factor = a[r][col].clone();
             for c in col..n_cols {
-                a[r][c] = a[r][c].clone() - &factor * &a[row][c];
+                a[r][c] = a[r][c].clone() - factor.clone() * a[row][c].clone();
             }
         }
         pivots.push(col);
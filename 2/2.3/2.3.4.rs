@@ -1,24 +1,18 @@
-use num_rational::BigRational;
-use num_traits::Zero;
-
+use crate::error::Error;
+use crate::ring::Field;
 use crate::{kernel_basis, Matrix, Vector};
 
-pub fn inverse_image_vector(m: Matrix, b: Vector) -> Option<Vector> {
-    let m_rows = m.len();
+pub fn inverse_image_vector<F: Field>(m: Matrix<F>, b: Vector<F>) -> Result<Option<Vector<F>>, Error> {
+    let m_rows = m.nrows();
     if m_rows == 0 || b.len() != m_rows {
-        return None;
-    }
-    let n = m[0].len();
-    let mut m1 = Vec::with_capacity(m_rows);
-    for (i, row) in m.into_iter().enumerate() {
-        let mut new_row = row;
-        new_row.push(b[i].clone());
-        m1.push(new_row);
+        return Err(Error::DimensionMismatch);
     }
+    let n = m.ncols();
+    let m1 = m.augment(&Matrix::from_columns(&[b])?)?;
 
     let ker = kernel_basis(m1);
     if ker.is_empty() {
-        return None;
+        return Ok(None);
     }
     // find a vector with last entry != 0
     for v in ker {
@@ -26,12 +20,12 @@ pub fn inverse_image_vector(m: Matrix, b: Vector) -> Option<Vector> {
         if last.is_zero() {
             continue;
         }
-        let d = -last.recip();
-        let mut x = vec![BigRational::zero(); n];
+        let d = -(F::one() / last.clone());
+        let mut x = vec![F::zero(); n];
         for i in 0..n {
-            x[i] = &d * &v[i];
+            x[i] = d.clone() * v[i].clone();
         }
-        return Some(x);
+        return Ok(Some(x));
     }
-    None
+    Ok(None)
 }
@@ -1,15 +1,13 @@
-use num_rational::BigRational;
-use num_traits::Zero;
+use crate::error::Error;
+use crate::ring::Field;
+use crate::Matrix;
 
-use crate::{Matrix, Vector};
-
-pub fn inverse_image_matrix(m: Matrix, v: Matrix) -> Option<Matrix> {
-    let m_rows = m.len();
-    if m_rows == 0 || v.len() != m_rows || m[0].len() < v[0].len() {
-        return None;
+pub fn inverse_image_matrix<F: Field>(m: Matrix<F>, v: Matrix<F>) -> Result<Option<Matrix<F>>, Error> {
+    let (m_rows, n) = m.shape();
+    let r = v.ncols();
+    if m_rows == 0 || v.nrows() != m_rows || n < r {
+        return Err(Error::DimensionMismatch);
     }
-    let n = m[0].len();
-    let r = v[0].len();
 
     // copy M to work on, and B <- V
     let mut m_work = m.clone();
@@ -28,60 +26,58 @@ pub fn inverse_image_matrix(m: Matrix, v: Matrix) -> Option<Matrix> {
                 break;
             }
         }
-        let i = match pivot {
-            Some(v) => v,
-            None => return None,
-        };
+        // M must have rank n
+        let i = pivot.ok_or(Error::NotInvertible)?;
 
         if i > col {
-            m_work.swap(i, col);
-            b_work.swap(i, col);
+            m_work.swap_rows(i, col);
+            b_work.swap_rows(i, col);
         }
 
-        let mjj_inv = m_work[col][col].clone().recip();
-        let mut c: Vec<BigRational> = vec![BigRational::zero(); m_rows];
+        let mjj_inv = m_work[col][col].inv().ok_or(Error::NotInvertible)?;
+        let mut c: Vec<F> = vec![F::zero(); m_rows];
         for k in (col + 1)..m_rows {
-            c[k] = &mjj_inv * &m_work[k][col];
+            c[k] = mjj_inv.clone() * m_work[k][col].clone();
         }
 
         for k in (col + 1)..m_rows {
             for l in (col + 1)..n {
-                m_work[k][l] = &m_work[k][l] - &c[k] * &m_work[col][l];
+                m_work[k][l] = m_work[k][l].clone() - c[k].clone() * m_work[col][l].clone();
             }
             for l in 0..r {
-                b_work[k][l] = &b_work[k][l] - &c[k] * &b_work[col][l];
+                b_work[k][l] = b_work[k][l].clone() - c[k].clone() * b_work[col][l].clone();
             }
         }
     }
 
     // Back substitution on first n rows (upper triangular)
-    let mut x = vec![vec![BigRational::zero(); r]; n];
+    let mut x: Matrix<F> = Matrix::zeros(n, r);
     for i_rev in 0..n {
         let i = n - 1 - i_rev;
         for col_r in 0..r {
-            let mut sum = BigRational::zero();
+            let mut sum = F::zero();
             for j in (i + 1)..n {
-                sum += &m_work[i][j] * &x[j][col_r];
+                sum = sum + m_work[i][j].clone() * x[j][col_r].clone();
             }
             if m_work[i][i].is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
-            x[i][col_r] = (&b_work[i][col_r] - sum) / m_work[i][i].clone();
+            x[i][col_r] = (b_work[i][col_r].clone() - sum) / m_work[i][i].clone();
         }
     }
 
     // Check rest of rows
     for k in n..m_rows {
         for col_r in 0..r {
-            let mut sum = BigRational::zero();
+            let mut sum = F::zero();
             for j in 0..n {
-                sum += &m[k][j] * &x[j][col_r];
+                sum = sum + m[k][j].clone() * x[j][col_r].clone();
             }
             if sum != b_work[k][col_r] {
-                return None;
+                return Ok(None);
             }
         }
     }
 
-    Some(x)
+    Ok(Some(x))
 }
@@ -1,16 +1,15 @@
-use num_traits::Zero;
+use crate::error::Error;
+use crate::ring::Field;
+use crate::Matrix;
 
-use crate::{identity, Matrix};
-
-pub fn supplement_basis(m: Matrix) -> Option<Matrix> {
-    let n = m.len();
-    let k = m.first().map(|row| row.len()).unwrap_or(0);
-    if k > n || m.iter().any(|row| row.len() != k) {
-        return None;
+pub fn supplement_basis<F: Field>(m: Matrix<F>) -> Result<Matrix<F>, Error> {
+    let (n, k) = m.shape();
+    if k > n {
+        return Err(Error::DimensionMismatch);
     }
 
     let mut m_work = m.clone();
-    let mut b = identity(n);
+    let mut b: Matrix<F> = Matrix::identity(n);
 
     for s in 0..k {
         let mut t_opt = None;
@@ -20,11 +19,9 @@ pub fn supplement_basis(m: Matrix) -> Option<Matrix> {
                 break;
             }
         }
-        let t = match t_opt {
-            Some(idx) => idx,
-            None => return None,
-        };
-        let d = m_work[t][s].clone().recip();
+        // the columns of M must be linearly independent
+        let t = t_opt.ok_or(Error::NotInvertible)?;
+        let d = m_work[t][s].inv().ok_or(Error::NotInvertible)?;
 
         if t != s {
             for row in 0..n {
@@ -36,7 +33,7 @@ pub fn supplement_basis(m: Matrix) -> Option<Matrix> {
         }
 
         for j in (s + 1)..k {
-            let msj = &d * &m_work[t][j];
+            let msj = d.clone() * m_work[t][j].clone();
             m_work[s][j] = msj.clone();
             if t != s {
                 m_work[t][j] = msj.clone();
@@ -51,5 +48,5 @@ pub fn supplement_basis(m: Matrix) -> Option<Matrix> {
         }
     }
 
-    Some(b)
+    Ok(b)
 }
@@ -1,36 +1,18 @@
-use num_rational::BigRational;
-use num_traits::Zero;
+use crate::error::Error;
+use crate::ring::Field;
+use crate::{inverse_image_matrix, supplement_basis, Matrix};
 
-use crate::{
-    inverse_image_matrix,
-    matrix::{mat_mul_rect},
-    supplement_basis,
-    Matrix,
-};
-
-pub fn supplement_subspace(v: Matrix, m: Matrix) -> Option<Matrix> {
-    if v.len() != m.len() {
-        return None;
-    }
-    let n = m.first().map(|row| row.len()).unwrap_or(0);
-    let r = v.first().map(|row| row.len()).unwrap_or(0);
-    if r > n {
-        return None;
-    }
-    if m.iter().any(|row| row.len() != n) || v.iter().any(|row| row.len() != r) {
-        return None;
+pub fn supplement_subspace<F: Field>(v: Matrix<F>, m: Matrix<F>) -> Result<Matrix<F>, Error> {
+    let n = m.ncols();
+    let r = v.ncols();
+    if v.nrows() != m.nrows() || r > n {
+        return Err(Error::DimensionMismatch);
     }
 
-    let x = inverse_image_matrix(m.clone(), v)?;
+    let x = inverse_image_matrix(m.clone(), v)?
+        .ok_or(Error::InvalidArgument("V is not contained in the image of M"))?;
     let b = supplement_basis(x)?;
 
-    let extra = n - r;
-    let mut c = vec![vec![BigRational::zero(); extra]; n];
-    for i in 0..n {
-        for j in 0..extra {
-            c[i][j] = b[i][r + j].clone();
-        }
-    }
-
-    mat_mul_rect(&m, &c)
+    let c = b.submatrix(0..n, r..n);
+    m.checked_mul(&c)
 }
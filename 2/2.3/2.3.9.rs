@@ -1,45 +1,24 @@
-use num_rational::BigRational;
-use num_traits::Zero;
+use crate::error::Error;
+use crate::ring::Field;
+use crate::{image_basis, kernel_basis, Matrix};
 
-use crate::{
-    image_basis, kernel_basis,
-    matrix::{columns_to_matrix, mat_mul_rect},
-    Matrix,
-};
-
-pub fn intersection_subspaces(m: Matrix, m_prime: Matrix) -> Option<Matrix> {
-    if m.len() != m_prime.len() {
-        return None;
-    }
-    let rows = m.len();
-    let n = m.first().map(|row| row.len()).unwrap_or(0);
-    let n_prime = m_prime.first().map(|row| row.len()).unwrap_or(0);
-    if m.iter().any(|row| row.len() != n) || m_prime.iter().any(|row| row.len() != n_prime) {
-        return None;
-    }
-
-    let mut m1 = Vec::with_capacity(rows);
-    for i in 0..rows {
-        let mut row = m[i].clone();
-        row.extend_from_slice(&m_prime[i]);
-        m1.push(row);
-    }
+pub fn intersection_subspaces<F: Field>(m: Matrix<F>, m_prime: Matrix<F>) -> Result<Matrix<F>, Error> {
+    let rows = m.nrows();
+    let m1 = m.augment(&m_prime)?;
 
     let ker = kernel_basis(m1);
-    let p = ker.len();
-    let total_cols = n + n_prime;
-    let mut n_mat = vec![vec![BigRational::zero(); p]; total_cols];
-    for (col_idx, v) in ker.iter().enumerate() {
-        for row_idx in 0..total_cols {
-            n_mat[row_idx][col_idx] = v[row_idx].clone();
-        }
-    }
-
-    let n1: Matrix = n_mat.into_iter().take(n).collect();
-    let m2 = mat_mul_rect(&m, &n1)?;
+    let n = m.ncols();
+    // top n rows of the kernel basis, written as columns
+    let n1: Vec<Vec<F>> = ker.iter().map(|v| v[..n].to_vec()).collect();
+    let n1 = if n1.is_empty() {
+        Matrix::zeros(n, 0)
+    } else {
+        Matrix::from_columns(&n1)?
+    };
+    let m2 = m.checked_mul(&n1)?;
     let basis = image_basis(m2);
     if basis.is_empty() {
-        return Some(vec![vec![]; rows]);
+        return Ok(Matrix::zeros(rows, 0));
     }
-    Some(columns_to_matrix(&basis))
+    Matrix::from_columns(&basis)
 }
@@ -1,28 +1,18 @@
 use num_bigint::BigInt;
 use num_integer::Integer;
-use num_traits::{One, Signed, Zero};
+use num_traits::{Signed, Zero};
 
+use crate::error::Error;
 use crate::extended_euclid;
+use crate::matrix::Matrix;
 
-fn identity_bigint(n: usize) -> Vec<Vec<BigInt>> {
-    let mut m = vec![vec![BigInt::zero(); n]; n];
-    for i in 0..n {
-        m[i][i] = BigInt::one();
-    }
-    m
-}
-
-pub fn kernel_integer(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>> {
-    let m = a.len();
+pub fn kernel_integer(mut a: Matrix<BigInt>) -> Result<Matrix<BigInt>, Error> {
+    let (m, n) = a.shape();
     if m == 0 {
-        return Some(Vec::new());
-    }
-    let n = a[0].len();
-    if a.iter().any(|row| row.len() != n) {
-        return None;
+        return Ok(Matrix::identity(n));
     }
 
-    let mut u = identity_bigint(n);
+    let mut u: Matrix<BigInt> = Matrix::identity(n);
     let l = if m <= n { 0isize } else { (m - n) as isize };
     let mut i = m as isize - 1;
     let mut k = n as isize - 1;
@@ -32,7 +22,7 @@ pub fn kernel_integer(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>> {
         if j <= 0 {
             let mut b = a[i as usize][k as usize].clone();
             if b.is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
             if b.is_negative() {
                 for row in 0..m {
@@ -56,14 +46,7 @@ pub fn kernel_integer(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>> {
             }
 
             if i as usize == l as usize {
-                let basis_cols = k as usize;
-                let mut m_out = vec![vec![BigInt::zero(); basis_cols]; n];
-                for row in 0..n {
-                    for col in 0..basis_cols {
-                        m_out[row][col] = u[row][col].clone();
-                    }
-                }
-                return Some(m_out);
+                return Ok(u.submatrix(0..n, 0..k as usize));
             } else {
                 i -= 1;
                 k -= 1;
@@ -81,7 +64,7 @@ pub fn kernel_integer(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>> {
         let aij = a[i as usize][j as usize].clone();
         let (u_c, v_c, d) = extended_euclid(aik.clone(), aij.clone());
         if d.is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
         let factor_k = aik.div_floor(&d);
         let factor_j = aij.div_floor(&d);
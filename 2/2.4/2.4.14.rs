@@ -2,6 +2,8 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{Signed, Zero};
 
+use crate::error::Error;
+use crate::matrix::Matrix;
 use crate::{extended_euclid, gauss_bareiss_det};
 
 fn mod_pos(a: BigInt, m: &BigInt) -> BigInt {
@@ -11,20 +13,23 @@ fn mod_pos(a: BigInt, m: &BigInt) -> BigInt {
     a.mod_floor(m)
 }
 
-pub fn smith_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<BigInt>> {
-    let n = a.len();
-    if n == 0 || a.iter().any(|row| row.len() != n) {
-        return None;
+pub fn smith_normal_form(mut a: Matrix<BigInt>) -> Result<Vec<BigInt>, Error> {
+    let n = a.nrows();
+    if !a.is_square() {
+        return Err(Error::DimensionMismatch);
+    }
+    if n == 0 {
+        return Ok(Vec::new());
     }
 
     if n == 1 {
         let det = a[0][0].abs();
-        return Some(vec![det]);
+        return Ok(vec![det]);
     }
 
     let det = gauss_bareiss_det(a.clone())?.abs();
     if det.is_zero() {
-        return None;
+        return Err(Error::NotInvertible);
     }
     let mut r = det;
     let mut i: isize = n as isize - 1;
@@ -45,7 +50,7 @@ pub fn smith_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<BigInt>> {
             let aij = a[i as usize][j as usize].clone();
             let (u, v, d) = extended_euclid(aii.clone(), aij.clone());
             if d.is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
             let factor_i = aii.div_floor(&d);
             let factor_j = aij.div_floor(&d);
@@ -79,7 +84,7 @@ pub fn smith_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<BigInt>> {
             let aji = a[j as usize][i as usize].clone();
             let (u, v, d) = extended_euclid(aii.clone(), aji.clone());
             if d.is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
             let factor_i = aii.div_floor(&d);
             let factor_j = aji.div_floor(&d);
@@ -137,11 +142,11 @@ pub fn smith_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<BigInt>> {
             let d1 = num_integer::Integer::gcd(&a[0][0], &r).abs();
             diag.push(d1);
             diag.reverse();
-            return Some(diag);
+            return Ok(diag);
         } else {
             i -= 1;
         }
     }
 
-    None
+    Err(Error::NotInvertible)
 }
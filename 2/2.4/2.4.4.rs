@@ -2,14 +2,13 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{Signed, Zero};
 
-pub fn hermite_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>> {
-    let m = a.len();
+use crate::error::Error;
+use crate::matrix::Matrix;
+
+pub fn hermite_normal_form(mut a: Matrix<BigInt>) -> Result<Matrix<BigInt>, Error> {
+    let (m, n) = a.shape();
     if m == 0 {
-        return Some(a);
-    }
-    let n = a[0].len();
-    if a.iter().any(|row| row.len() != n) {
-        return None;
+        return Ok(a);
     }
 
     let l = if m <= n { 0isize } else { (m - n) as isize };
@@ -18,7 +17,7 @@ pub fn hermite_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>>
 
     loop {
         if k < 0 {
-            return Some(a);
+            return Ok(a);
         }
 
         let mut row_finished = true;
@@ -49,14 +48,7 @@ pub fn hermite_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>>
                 }
             }
             if i == l {
-                let start = k as usize;
-                let mut w = vec![vec![BigInt::zero(); n - start]; m];
-                for row in 0..m {
-                    for col in start..n {
-                        w[row][col - start] = a[row][col].clone();
-                    }
-                }
-                return Some(w);
+                return Ok(a.submatrix(0..m, k as usize..n));
             } else {
                 i -= 1;
                 k -= 1;
@@ -85,12 +77,10 @@ pub fn hermite_normal_form(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>>
                 }
             }
         }
-        let j0 = j0?;
+        let j0 = j0.expect("row i has a nonzero entry left of k");
 
         if j0 < k as usize {
-            for row in 0..m {
-                a[row].swap(j0, k as usize);
-            }
+            a.swap_columns(j0, k as usize);
         }
         if a[i as usize][k as usize].is_negative() {
             for row in 0..m {
@@ -2,6 +2,9 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{Signed, Zero};
 
+use crate::error::Error;
+use crate::matrix::Matrix;
+
 fn extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
     let mut old_r = a;
     let mut r = b;
@@ -32,14 +35,10 @@ fn extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
     }
 }
 
-pub fn hermite_normal_form_euclid(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<BigInt>>> {
-    let m = a.len();
+pub fn hermite_normal_form_euclid(mut a: Matrix<BigInt>) -> Result<Matrix<BigInt>, Error> {
+    let (m, n) = a.shape();
     if m == 0 {
-        return Some(a);
-    }
-    let n = a[0].len();
-    if a.iter().any(|row| row.len() != n) {
-        return None;
+        return Ok(a);
     }
 
     let l = if m <= n { 0isize } else { (m - n) as isize };
@@ -49,7 +48,7 @@ pub fn hermite_normal_form_euclid(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<Big
 
     loop {
         if k < 0 {
-            return Some(a);
+            return Ok(a);
         }
 
         loop {
@@ -107,19 +106,12 @@ pub fn hermite_normal_form_euclid(mut a: Vec<Vec<BigInt>>) -> Option<Vec<Vec<Big
         }
 
         if i == l {
-            let start = k as usize;
-            let mut w = vec![vec![BigInt::zero(); n - start]; m];
-            for row in 0..m {
-                for col in start..n {
-                    w[row][col - start] = a[row][col].clone();
-                }
-            }
-            return Some(w);
+            return Ok(a.submatrix(0..m, k as usize..n));
         } else {
             i -= 1;
             k -= 1;
             if k < 0 {
-                return Some(a);
+                return Ok(a);
             }
             j = k;
         }
@@ -2,7 +2,9 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::Zero;
 
+use crate::error::Error;
 use crate::extended_euclid;
+use crate::matrix::Matrix;
 
 fn mod_pos(a: BigInt, m: &BigInt) -> BigInt {
     if m.is_zero() {
@@ -11,10 +13,13 @@ fn mod_pos(a: BigInt, m: &BigInt) -> BigInt {
     a.mod_floor(m)
 }
 
-pub fn hermite_modulo_d(l: Vec<Vec<BigInt>>, d: BigInt) -> Option<Vec<Vec<BigInt>>> {
-    let m = l.len();
-    if m == 0 || l.iter().any(|row| row.len() != m) || d.is_zero() {
-        return None;
+pub fn hermite_modulo_d(l: Matrix<BigInt>, d: BigInt) -> Result<Matrix<BigInt>, Error> {
+    let m = l.nrows();
+    if m == 0 || !l.is_square() {
+        return Err(Error::DimensionMismatch);
+    }
+    if d.is_zero() {
+        return Err(Error::InvalidArgument("d must be a nonzero multiple of the determinant"));
     }
     let mut w = l.clone();
     let mut b = d;
@@ -33,7 +38,7 @@ pub fn hermite_modulo_d(l: Vec<Vec<BigInt>>, d: BigInt) -> Option<Vec<Vec<BigInt
 
         if i > 0 {
             if g.is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
             b = b / g;
             i -= 1;
@@ -46,7 +51,7 @@ pub fn hermite_modulo_d(l: Vec<Vec<BigInt>>, d: BigInt) -> Option<Vec<Vec<BigInt
     for ii in (0..m.saturating_sub(1)).rev() {
         let diag = w[ii][ii].clone();
         if diag.is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
         for j in (ii + 1)..m {
             let q = w[ii][j].div_floor(&diag);
@@ -59,5 +64,5 @@ pub fn hermite_modulo_d(l: Vec<Vec<BigInt>>, d: BigInt) -> Option<Vec<Vec<BigInt
         }
     }
 
-    Some(w)
+    Ok(w)
 }
@@ -2,7 +2,9 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::Zero;
 
+use crate::error::Error;
 use crate::extended_euclid;
+use crate::matrix::Matrix;
 
 fn mod_pos(a: BigInt, m: &BigInt) -> BigInt {
     if m.is_zero() {
@@ -11,14 +13,16 @@ fn mod_pos(a: BigInt, m: &BigInt) -> BigInt {
     a.mod_floor(m)
 }
 
-pub fn hermite_modulo_d_general(mut a: Vec<Vec<BigInt>>, d: BigInt) -> Option<Vec<Vec<BigInt>>> {
-    let m = a.len();
-    if m == 0 || d.is_zero() {
-        return None;
+pub fn hermite_modulo_d_general(mut a: Matrix<BigInt>, d: BigInt) -> Result<Matrix<BigInt>, Error> {
+    let (m, n) = a.shape();
+    if m == 0 {
+        return Err(Error::DimensionMismatch);
     }
-    let n = a[0].len();
-    if a.iter().any(|row| row.len() != n) || n < m {
-        return None;
+    if d.is_zero() {
+        return Err(Error::InvalidArgument("d must be a nonzero multiple of the determinant"));
+    }
+    if n < m {
+        return Err(Error::DimensionMismatch);
     }
 
     let mut r_mod = d;
@@ -43,7 +47,7 @@ pub fn hermite_modulo_d_general(mut a: Vec<Vec<BigInt>>, d: BigInt) -> Option<Ve
 
             let pivot = wi[i as usize].clone();
             if pivot.is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
             for col in (i as usize + 1)..m {
                 let q = a[col][i as usize].div_floor(&pivot);
@@ -56,17 +60,11 @@ pub fn hermite_modulo_d_general(mut a: Vec<Vec<BigInt>>, d: BigInt) -> Option<Ve
             }
 
             if i == 0 {
-                let mut w = vec![vec![BigInt::zero(); m]; m];
-                for row in 0..m {
-                    for col in 0..m {
-                        w[row][col] = a[row][col].clone();
-                    }
-                }
-                return Some(w);
+                return Ok(a.submatrix(0..m, 0..m));
             }
 
             if g.is_zero() {
-                return None;
+                return Err(Error::NotInvertible);
             }
             r_mod = r_mod / g;
             i -= 1;
@@ -101,5 +99,5 @@ pub fn hermite_modulo_d_general(mut a: Vec<Vec<BigInt>>, d: BigInt) -> Option<Ve
         }
     }
 
-    None
+    Err(Error::NotInvertible)
 }
@@ -3,13 +3,8 @@ use num_integer::Integer;
 use num_rational::BigRational;
 use num_traits::{One, Signed, Zero};
 
-fn identity_bigint(n: usize) -> Vec<Vec<BigInt>> {
-    let mut m = vec![vec![BigInt::zero(); n]; n];
-    for i in 0..n {
-        m[i][i] = BigInt::one();
-    }
-    m
-}
+use crate::error::Error;
+use crate::matrix::Matrix;
 
 fn dot_int(a: &[BigInt], b: &[BigInt]) -> BigInt {
     let mut s = BigInt::zero();
@@ -57,12 +52,12 @@ fn nearest_integer(r: &BigRational) -> BigInt {
 }
 
 fn recompute(
-    b: &Vec<Vec<BigInt>>,
+    b: &Matrix<BigInt>,
     upto: usize,
-    b_star: &mut Vec<Vec<BigRational>>,
-    b_norm: &mut Vec<BigRational>,
-    mu: &mut Vec<Vec<BigRational>>,
-) -> Option<()> {
+    b_star: &mut Matrix<BigRational>,
+    b_norm: &mut [BigRational],
+    mu: &mut Matrix<BigRational>,
+) -> Result<(), Error> {
     for i in 0..=upto {
         let mut bi_star = to_rat(&b[i]);
         for j in 0..i {
@@ -75,39 +70,33 @@ fn recompute(
         }
         let norm = dot_rat(&bi_star, &bi_star);
         if norm.is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
-        b_star[i] = bi_star;
+        b_star.row_mut(i).clone_from_slice(&bi_star);
         b_norm[i] = norm;
     }
-    Some(())
+    Ok(())
 }
 
 pub fn lll_reduction(
-    basis: Vec<Vec<BigInt>>,
-) -> Option<(Vec<Vec<BigInt>>, Vec<Vec<BigInt>>)> {
-    let n = basis.len();
+    basis: Matrix<BigInt>,
+) -> Result<(Matrix<BigInt>, Matrix<BigInt>), Error> {
+    let (n, m) = basis.shape();
     if n == 0 {
-        return Some((basis, Vec::new()));
-    }
-    let m = basis[0].len();
-    if basis.iter().any(|v| v.len() != m) {
-        return None;
+        return Ok((basis, Matrix::zeros(0, 0)));
     }
 
     let delta = BigRational::new(BigInt::from(3), BigInt::from(4));
     let mut b = basis;
-    let mut h = identity_bigint(n);
+    let mut h = Matrix::identity(n);
 
-    let mut b_star = vec![vec![BigRational::zero(); m]; n];
+    let mut b_star = Matrix::zeros(n, m);
     let mut b_norm = vec![BigRational::zero(); n];
-    let mut mu = vec![vec![BigRational::zero(); n]; n];
+    let mut mu = Matrix::zeros(n, n);
 
     let mut k: usize = 1;
     while k < n {
-        if recompute(&b, k, &mut b_star, &mut b_norm, &mut mu).is_none() {
-            return None;
-        }
+        recompute(&b, k, &mut b_star, &mut b_norm, &mut mu)?;
 
         if !mu[k][k - 1].is_zero() {
             let q = nearest_integer(&mu[k][k - 1]);
@@ -120,9 +109,7 @@ pub fn lll_reduction(
                     let t = &q * &h[i][k - 1];
                     h[i][k] -= t;
                 }
-                if recompute(&b, k, &mut b_star, &mut b_norm, &mut mu).is_none() {
-                    return None;
-                }
+                recompute(&b, k, &mut b_star, &mut b_norm, &mut mu)?;
             }
         }
 
@@ -131,8 +118,8 @@ pub fn lll_reduction(
         let rhs = (delta.clone() - mu_sq) * b_norm[k - 1].clone();
 
         if lhs < rhs {
-            b.swap(k, k - 1);
-            h.swap(k, k - 1);
+            b.swap_rows(k, k - 1);
+            h.swap_rows(k, k - 1);
             if k > 1 {
                 k -= 1;
             }
@@ -155,13 +142,11 @@ pub fn lll_reduction(
                 let t = &q * &h[i][l];
                 h[i][k] -= t;
             }
-            if recompute(&b, k, &mut b_star, &mut b_norm, &mut mu).is_none() {
-                return None;
-            }
+            recompute(&b, k, &mut b_star, &mut b_norm, &mut mu)?;
         }
 
         k += 1;
     }
 
-    Some((b, h))
+    Ok((b, h))
 }
@@ -3,13 +3,8 @@ use num_integer::Integer;
 use num_rational::BigRational;
 use num_traits::{One, Signed, Zero};
 
-fn identity_bigint(n: usize) -> Vec<Vec<BigInt>> {
-    let mut m = vec![vec![BigInt::zero(); n]; n];
-    for i in 0..n {
-        m[i][i] = BigInt::one();
-    }
-    m
-}
+use crate::error::Error;
+use crate::matrix::Matrix;
 
 fn dot_int(a: &[BigInt], b: &[BigInt]) -> BigInt {
     let mut s = BigInt::zero();
@@ -56,13 +51,13 @@ fn nearest_integer(r: &BigRational) -> BigInt {
 }
 
 fn recompute(
-    b: &Vec<Vec<BigInt>>,
+    b: &Matrix<BigInt>,
     upto: usize,
-    b_star: &mut Vec<Vec<BigRational>>,
-    b_norm: &mut Vec<BigRational>,
-    mu: &mut Vec<Vec<BigRational>>,
-) -> Option<()> {
-    let m = b.first().map(|v| v.len()).unwrap_or(0);
+    b_star: &mut Matrix<BigRational>,
+    b_norm: &mut [BigRational],
+    mu: &mut Matrix<BigRational>,
+) -> Result<(), Error> {
+    let m = b.ncols();
     for i in 0..=upto {
         let mut bi_star = to_rat(&b[i]);
         for j in 0..i {
@@ -75,18 +70,18 @@ fn recompute(
         }
         let norm = dot_rat(&bi_star, &bi_star);
         if norm.is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
-        b_star[i] = bi_star;
+        b_star.row_mut(i).clone_from_slice(&bi_star);
         b_norm[i] = norm;
     }
-    Some(())
+    Ok(())
 }
 
 fn red(
-    b: &mut Vec<Vec<BigInt>>,
-    h: &mut Vec<Vec<BigInt>>,
-    mu: &mut Vec<Vec<BigRational>>,
+    b: &mut Matrix<BigInt>,
+    h: &mut Matrix<BigInt>,
+    mu: &mut Matrix<BigRational>,
     k: usize,
     l: usize,
 ) {
@@ -94,76 +89,51 @@ fn red(
     if q.is_zero() {
         return;
     }
-    let bl = b[l].clone();
-    let bk = &mut b[k];
-    for i in 0..bk.len() {
-        bk[i] -= &q * &bl[i];
-    }
-    let hl: Vec<BigInt> = h.iter().map(|row| row[l].clone()).collect();
-    for (idx, row) in h.iter_mut().enumerate() {
-        row[k] -= &q * &hl[idx];
-    }
+    b.add_row_multiple(k, l, &-&q);
+    h.add_column_multiple(k, l, &-&q);
 }
 
 fn insert(
-    b: &mut Vec<Vec<BigInt>>,
-    h: &mut Vec<Vec<BigInt>>,
+    b: &mut Matrix<BigInt>,
+    h: &mut Matrix<BigInt>,
     k: usize,
     i: usize,
 ) {
     if k <= i {
         return;
     }
-    let bk = b[k].clone();
-    let mut hk = vec![BigInt::zero(); h.len()];
-    for row in 0..h.len() {
-        hk[row] = h[row][k].clone();
-    }
+    // rotate b_k into position i, shifting b_i..b_(k-1) up by one
     for idx in (i + 1..=k).rev() {
-        b[idx] = b[idx - 1].clone();
-        for row in 0..h.len() {
-            h[row][idx] = h[row][idx - 1].clone();
-        }
-    }
-    b[i] = bk;
-    for row in 0..h.len() {
-        h[row][i] = hk[row].clone();
+        b.swap_rows(idx, idx - 1);
+        h.swap_columns(idx, idx - 1);
     }
 }
 
 pub fn lll_deep(
-    basis: Vec<Vec<BigInt>>,
-) -> Option<(Vec<Vec<BigInt>>, Vec<Vec<BigInt>>)> {
-    let n = basis.len();
+    basis: Matrix<BigInt>,
+) -> Result<(Matrix<BigInt>, Matrix<BigInt>), Error> {
+    let (n, m) = basis.shape();
     if n == 0 {
-        return Some((basis, Vec::new()));
-    }
-    let m = basis[0].len();
-    if basis.iter().any(|v| v.len() != m) {
-        return None;
+        return Ok((basis, Matrix::zeros(0, 0)));
     }
 
     let delta = BigRational::new(BigInt::from(3), BigInt::from(4));
     let mut b = basis;
-    let mut h = identity_bigint(n);
-    let mut b_star = vec![vec![BigRational::zero(); m]; n];
+    let mut h = Matrix::identity(n);
+    let mut b_star = Matrix::zeros(n, m);
     let mut b_norm = vec![BigRational::zero(); n];
-    let mut mu = vec![vec![BigRational::zero(); n]; n];
+    let mut mu = Matrix::zeros(n, n);
 
     let mut k: usize = 1;
     while k < n {
-        if recompute(&b, k, &mut b_star, &mut b_norm, &mut mu).is_none() {
-            return None;
-        }
+        recompute(&b, k, &mut b_star, &mut b_norm, &mut mu)?;
         if b_norm[k].is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
 
         for l in (0..k).rev() {
             red(&mut b, &mut h, &mut mu, k, l);
-            if recompute(&b, k, &mut b_star, &mut b_norm, &mut mu).is_none() {
-                return None;
-            }
+            recompute(&b, k, &mut b_star, &mut b_norm, &mut mu)?;
         }
 
         let mut b_proj = BigRational::from_integer(dot_int(&b[k], &b[k]));
@@ -185,9 +155,7 @@ pub fn lll_deep(
                     k = i - 1;
                     b_proj = BigRational::from_integer(dot_int(&b[k], &b[k]));
                     i = 0;
-                    if recompute(&b, k, &mut b_star, &mut b_norm, &mut mu).is_none() {
-                        return None;
-                    }
+                    recompute(&b, k, &mut b_star, &mut b_norm, &mut mu)?;
                     continue;
                 } else {
                     k = 0;
@@ -197,5 +165,5 @@ pub fn lll_deep(
         }
     }
 
-    Some((b, h))
+    Ok((b, h))
 }
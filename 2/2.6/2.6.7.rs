@@ -3,13 +3,8 @@ use num_integer::Integer;
 use num_rational::BigRational;
 use num_traits::{One, Signed, Zero};
 
-fn identity_bigint(n: usize) -> Vec<Vec<BigInt>> {
-    let mut m = vec![vec![BigInt::zero(); n]; n];
-    for i in 0..n {
-        m[i][i] = BigInt::one();
-    }
-    m
-}
+use crate::error::Error;
+use crate::matrix::Matrix;
 
 fn dot(a: &[BigInt], b: &[BigInt]) -> BigInt {
     let mut s = BigInt::zero();
@@ -42,18 +37,18 @@ fn nearest_integer(r: &BigRational) -> BigInt {
 }
 
 fn recompute(
-    b: &Vec<Vec<BigInt>>,
+    b: &Matrix<BigInt>,
     upto: usize,
-    lambda: &mut Vec<Vec<BigInt>>,
-    d: &mut Vec<BigInt>,
-) -> Option<()> {
+    lambda: &mut Matrix<BigInt>,
+    d: &mut [BigInt],
+) -> Result<(), Error> {
     let n = upto + 1;
-    if b.is_empty() {
-        return Some(());
+    if b.nrows() == 0 {
+        return Ok(());
     }
     d[0] = dot(&b[0], &b[0]);
     if d[0].is_zero() {
-        return None;
+        return Err(Error::NotInvertible);
     }
     for k in 1..n {
         for j in 0..=k {
@@ -73,89 +68,70 @@ fn recompute(
             }
         }
         if d[k].is_zero() {
-            return None;
+            return Err(Error::NotInvertible);
         }
     }
-    Some(())
+    Ok(())
 }
 
 fn redi(
-    b: &mut Vec<Vec<BigInt>>,
-    h: &mut Vec<Vec<BigInt>>,
-    lambda: &mut Vec<Vec<BigInt>>,
-    d: &mut Vec<BigInt>,
+    b: &mut Matrix<BigInt>,
+    h: &mut Matrix<BigInt>,
+    lambda: &mut Matrix<BigInt>,
+    d: &mut [BigInt],
     k: usize,
     l: usize,
-) -> Option<()> {
+) -> Result<(), Error> {
     let denom = d[l].clone();
     if denom.is_zero() {
-        return None;
+        return Err(Error::NotInvertible);
     }
     let mu = BigRational::from_integer(lambda[k][l].clone()) / BigRational::from_integer(denom);
     let q = nearest_integer(&mu);
     if q.is_zero() {
-        return Some(());
-    }
-    let bl = b[l].clone();
-    let bk = &mut b[k];
-    for idx in 0..bk.len() {
-        bk[idx] -= &q * &bl[idx];
-    }
-    let hl: Vec<BigInt> = h.iter().map(|row| row[l].clone()).collect();
-    for (idx, row) in h.iter_mut().enumerate() {
-        row[k] -= &q * &hl[idx];
+        return Ok(());
     }
+    b.add_row_multiple(k, l, &-&q);
+    h.add_column_multiple(k, l, &-&q);
     recompute(b, k, lambda, d)
 }
 
 fn swap(
-    b: &mut Vec<Vec<BigInt>>,
-    h: &mut Vec<Vec<BigInt>>,
-    lambda: &mut Vec<Vec<BigInt>>,
-    d: &mut Vec<BigInt>,
+    b: &mut Matrix<BigInt>,
+    h: &mut Matrix<BigInt>,
+    lambda: &mut Matrix<BigInt>,
+    d: &mut [BigInt],
     k: usize,
-) -> Option<()> {
-    b.swap(k, k - 1);
-    for row in 0..h.len() {
-        h[row].swap(k, k - 1);
-    }
+) -> Result<(), Error> {
+    b.swap_rows(k, k - 1);
+    h.swap_columns(k, k - 1);
     recompute(b, k, lambda, d)
 }
 
 pub fn lll_integral(
-    basis: Vec<Vec<BigInt>>,
-) -> Option<(Vec<Vec<BigInt>>, Vec<Vec<BigInt>>)> {
-    let n = basis.len();
+    basis: Matrix<BigInt>,
+) -> Result<(Matrix<BigInt>, Matrix<BigInt>), Error> {
+    let n = basis.nrows();
     if n == 0 {
-        return Some((basis, Vec::new()));
-    }
-    let m = basis[0].len();
-    if basis.iter().any(|v| v.len() != m) {
-        return None;
+        return Ok((basis, Matrix::zeros(0, 0)));
     }
 
     let mut b = basis;
-    let mut h = identity_bigint(n);
-    let mut lambda = vec![vec![BigInt::zero(); n]; n];
+    let mut h = Matrix::identity(n);
+    let mut lambda = Matrix::zeros(n, n);
     let mut d = vec![BigInt::zero(); n];
 
     let mut k: usize = 1;
-    if recompute(&b, 1.min(n - 1), &mut lambda, &mut d).is_none() {
-        return None;
-    }
+    recompute(&b, 1.min(n - 1), &mut lambda, &mut d)?;
     let mut k_max = 1usize;
 
     while k < n {
         if k > k_max {
-            if recompute(&b, k, &mut lambda, &mut d).is_none() {
-                return None;
-            }
+            recompute(&b, k, &mut lambda, &mut d)?;
             k_max = k;
         }
 
-        if redi(&mut b, &mut h, &mut lambda, &mut d, k, k - 1).is_none() {
-            return None;
-        }
+        redi(&mut b, &mut h, &mut lambda, &mut d, k, k - 1)?;
 
         let condition = if k >= 2 {
             let left = BigRational::from_integer(d[k].clone() * d[k - 2].clone());
@@ -170,9 +146,7 @@ pub fn lll_integral(
         };
 
         if condition {
-            if swap(&mut b, &mut h, &mut lambda, &mut d, k).is_none() {
-                return None;
-            }
+            swap(&mut b, &mut h, &mut lambda, &mut d, k)?;
             k_max = k;
             if k > 1 {
                 k -= 1;
@@ -181,13 +155,11 @@ pub fn lll_integral(
         }
 
         for l in (0..k - 1).rev() {
-            if redi(&mut b, &mut h, &mut lambda, &mut d, k, l).is_none() {
-                return None;
-            }
+            redi(&mut b, &mut h, &mut lambda, &mut d, k, l)?;
         }
 
         k += 1;
     }
 
-    Some((b, h))
+    Ok((b, h))
 }
@@ -3,13 +3,8 @@ use num_integer::Integer;
 use num_rational::BigRational;
 use num_traits::{One, Signed, Zero};
 
-fn identity_bigint(n: usize) -> Vec<Vec<BigInt>> {
-    let mut m = vec![vec![BigInt::zero(); n]; n];
-    for i in 0..n {
-        m[i][i] = BigInt::one();
-    }
-    m
-}
+use crate::error::Error;
+use crate::matrix::Matrix;
 
 fn to_rat(v: &[BigInt]) -> Vec<BigRational> {
     v.iter()
@@ -56,13 +51,13 @@ fn nearest_integer(r: &BigRational) -> BigInt {
 }
 
 fn recompute(
-    b: &Vec<Vec<BigInt>>,
+    b: &Matrix<BigInt>,
     upto: usize,
-    b_star: &mut Vec<Vec<BigRational>>,
-    norms: &mut Vec<BigRational>,
-    mu: &mut Vec<Vec<BigRational>>,
-) -> Option<()> {
-    let dim = b.first().map(|v| v.len()).unwrap_or(0);
+    b_star: &mut Matrix<BigRational>,
+    norms: &mut [BigRational],
+    mu: &mut Matrix<BigRational>,
+) -> Result<(), Error> {
+    let dim = b.ncols();
     for i in 0..=upto {
         let mut bi_star = to_rat(&b[i]);
         for j in 0..i {
@@ -78,16 +73,16 @@ fn recompute(
             }
         }
         let norm = dot_rat(&bi_star, &bi_star);
-        b_star[i] = bi_star;
+        b_star.row_mut(i).clone_from_slice(&bi_star);
         norms[i] = norm;
     }
-    Some(())
+    Ok(())
 }
 
 fn red(
-    b: &mut Vec<Vec<BigInt>>,
-    h: &mut Vec<Vec<BigInt>>,
-    mu: &mut Vec<Vec<BigRational>>,
+    b: &mut Matrix<BigInt>,
+    h: &mut Matrix<BigInt>,
+    mu: &mut Matrix<BigRational>,
     k: usize,
     l: usize,
 ) {
@@ -95,54 +90,37 @@ fn red(
     if q.is_zero() {
         return;
     }
-    let bl = b[l].clone();
-    let bk = &mut b[k];
-    for i in 0..bk.len() {
-        bk[i] -= &q * &bl[i];
-    }
-    let hl: Vec<BigInt> = h.iter().map(|row| row[l].clone()).collect();
-    for (idx, row) in h.iter_mut().enumerate() {
-        row[k] -= &q * &hl[idx];
-    }
+    b.add_row_multiple(k, l, &-&q);
+    h.add_column_multiple(k, l, &-&q);
 }
 
-fn swapg(b: &mut Vec<Vec<BigInt>>, h: &mut Vec<Vec<BigInt>>, k: usize) {
-    b.swap(k, k - 1);
-    for row in h.iter_mut() {
-        row.swap(k, k - 1);
-    }
+fn swapg(b: &mut Matrix<BigInt>, h: &mut Matrix<BigInt>, k: usize) {
+    b.swap_rows(k, k - 1);
+    h.swap_columns(k, k - 1);
 }
 
 pub fn lll_dependent(
-    basis: Vec<Vec<BigInt>>,
-) -> Option<(Vec<Vec<BigInt>>, Vec<Vec<BigInt>>, usize)> {
-    let n = basis.len();
+    basis: Matrix<BigInt>,
+) -> Result<(Matrix<BigInt>, Matrix<BigInt>, usize), Error> {
+    let (n, dim) = basis.shape();
     if n == 0 {
-        return Some((basis, Vec::new(), 0));
-    }
-    let dim = basis[0].len();
-    if basis.iter().any(|v| v.len() != dim) {
-        return None;
+        return Ok((basis, Matrix::zeros(0, 0), 0));
     }
 
     let delta = BigRational::new(BigInt::from(3), BigInt::from(4));
     let mut b = basis;
-    let mut h = identity_bigint(n);
-    let mut b_star = vec![vec![BigRational::zero(); dim]; n];
+    let mut h = Matrix::identity(n);
+    let mut b_star = Matrix::zeros(n, dim);
     let mut norms = vec![BigRational::zero(); n];
-    let mut mu = vec![vec![BigRational::zero(); n]; n];
+    let mut mu = Matrix::zeros(n, n);
 
-    if recompute(&b, 0, &mut b_star, &mut norms, &mut mu).is_none() {
-        return None;
-    }
+    recompute(&b, 0, &mut b_star, &mut norms, &mut mu)?;
     let mut k: usize = 1;
     let mut k_max: usize = 0;
 
     while k < n {
         if k > k_max {
-            if recompute(&b, k, &mut b_star, &mut norms, &mut mu).is_none() {
-                return None;
-            }
+            recompute(&b, k, &mut b_star, &mut norms, &mut mu)?;
             k_max = k;
         }
 
@@ -151,9 +129,7 @@ pub fn lll_dependent(
             continue;
         }
         red(&mut b, &mut h, &mut mu, k, k - 1);
-        if recompute(&b, k, &mut b_star, &mut norms, &mut mu).is_none() {
-            return None;
-        }
+        recompute(&b, k, &mut b_star, &mut norms, &mut mu)?;
 
         let lovasz = if norms[k - 1].is_zero() {
             false
@@ -175,9 +151,7 @@ pub fn lll_dependent(
 
         for l in (0..k - 1).rev() {
             red(&mut b, &mut h, &mut mu, k, l);
-            if recompute(&b, k, &mut b_star, &mut norms, &mut mu).is_none() {
-                return None;
-            }
+            recompute(&b, k, &mut b_star, &mut norms, &mut mu)?;
         }
 
         k += 1;
@@ -185,5 +159,5 @@ pub fn lll_dependent(
 
     let r = norms.iter().filter(|x| x.is_zero()).count();
     let p = n - r;
-    Some((b, h, p))
+    Ok((b, h, p))
 }
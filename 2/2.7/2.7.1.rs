@@ -3,42 +3,8 @@ use num_integer::Integer;
 use num_rational::BigRational;
 use num_traits::Zero;
 
-use crate::{
-    inverse,
-    lll_dependent,
-    lll_integral,
-    Matrix,
-};
-
-fn to_columns(a: Vec<Vec<BigInt>>) -> Vec<Vec<BigInt>> {
-    if a.is_empty() {
-        return Vec::new();
-    }
-    let m = a.len();
-    let n = a[0].len();
-    let mut cols = vec![vec![BigInt::zero(); m]; n];
-    for i in 0..m {
-        for j in 0..n {
-            cols[j][i] = a[i][j].clone();
-        }
-    }
-    cols
-}
-
-fn columns_to_rows(cols: Vec<Vec<BigInt>>) -> Vec<Vec<BigInt>> {
-    if cols.is_empty() {
-        return Vec::new();
-    }
-    let n = cols.len();
-    let m = cols[0].len();
-    let mut rows = vec![vec![BigInt::zero(); n]; m];
-    for j in 0..n {
-        for i in 0..m {
-            rows[i][j] = cols[j][i].clone();
-        }
-    }
-    rows
-}
+use crate::error::Error;
+use crate::{inverse, lll_dependent, lll_integral, Matrix};
 
 fn dot(a: &[BigInt], b: &[BigInt]) -> BigInt {
     let mut s = BigInt::zero();
@@ -48,26 +14,21 @@ fn dot(a: &[BigInt], b: &[BigInt]) -> BigInt {
     s
 }
 
-pub fn kernel_image_lll(a: Vec<Vec<BigInt>>) -> Option<(Vec<Vec<BigInt>>, usize)> {
-    let m = a.len();
+pub fn kernel_image_lll(a: Matrix<BigInt>) -> Result<(Matrix<BigInt>, usize), Error> {
+    let (m, n) = a.shape();
     if m == 0 {
-        return None;
-    }
-    let n = a[0].len();
-    if a.iter().any(|row| row.len() != n) {
-        return None;
+        return Err(Error::DimensionMismatch);
     }
 
-    let cols = to_columns(a);
-    let (_b_lll, mut h, p) = lll_dependent(cols)?;
+    // the LLL routines take the lattice vectors as rows
+    let (_b_lll, mut h, p) = lll_dependent(a.transpose())?;
     let r = n - p;
 
     if r > 0 {
-        let h_cols = to_columns(h.clone());
-        let kernel_basis: Vec<Vec<BigInt>> = h_cols.iter().take(r).cloned().collect();
-        if let Some((reduced_kernel, _)) = lll_integral(kernel_basis) {
-            let kernel_rows = columns_to_rows(reduced_kernel);
-            for i in 0..h.len() {
+        let kernel_basis = h.transpose().submatrix(0..r, 0..h.nrows());
+        if let Ok((reduced_kernel, _)) = lll_integral(kernel_basis) {
+            let kernel_rows = reduced_kernel.transpose();
+            for i in 0..h.nrows() {
                 for j in 0..r {
                     h[i][j] = kernel_rows[i][j].clone();
                 }
@@ -76,11 +37,11 @@ pub fn kernel_image_lll(a: Vec<Vec<BigInt>>) -> Option<(Vec<Vec<BigInt>>, usize)
     }
 
     if r == 0 {
-        return Some((h, p));
+        return Ok((h, p));
     }
 
-    let mut gram: Matrix = vec![vec![BigRational::zero(); r]; r];
-    let h_cols = to_columns(h.clone());
+    let mut gram: Matrix<BigRational> = Matrix::zeros(r, r);
+    let h_cols = h.columns();
     for j in 0..r {
         for k in 0..r {
             gram[j][k] = BigRational::from_integer(dot(&h_cols[j], &h_cols[k]));
@@ -89,7 +50,7 @@ pub fn kernel_image_lll(a: Vec<Vec<BigInt>>) -> Option<(Vec<Vec<BigInt>>, usize)
     let d_inv = inverse(gram)?;
 
     let h_cols_mut = h_cols;
-    let mut h_rows = columns_to_rows(h_cols_mut.clone());
+    let mut h_rows = h;
 
     for i_idx in r..n {
         let hi = &h_cols_mut[i_idx];
@@ -121,5 +82,5 @@ pub fn kernel_image_lll(a: Vec<Vec<BigInt>>) -> Option<(Vec<Vec<BigInt>>, usize)
     }
 
     let h_final = h_rows;
-    Some((h_final, p))
+    Ok((h_final, p))
 }
@@ -1,12 +1,13 @@
 use num_bigint::BigInt;
 use num_traits::{One, Zero};
 
-use crate::lll_reduction;
+use crate::error::Error;
+use crate::{lll_reduction, Matrix};
 
-pub fn linear_dependence_real(z: Vec<f64>, n_scale: i64) -> Option<Vec<Vec<BigInt>>> {
+pub fn linear_dependence_real(z: Vec<f64>, n_scale: i64) -> Result<Matrix<BigInt>, Error> {
     let n = z.len();
     if n == 0 {
-        return Some(Vec::new());
+        return Ok(Matrix::zeros(0, 1));
     }
     let scale = BigInt::from(n_scale);
     let mut basis: Vec<Vec<BigInt>> = Vec::with_capacity(n);
@@ -17,6 +18,6 @@ pub fn linear_dependence_real(z: Vec<f64>, n_scale: i64) -> Option<Vec<Vec<BigIn
         v[n] = BigInt::from(coef) * &scale;
         basis.push(v);
     }
-    let (b_reduced, _h) = lll_reduction(basis)?;
-    Some(b_reduced)
+    let (b_reduced, _h) = lll_reduction(Matrix::from_rows(basis)?)?;
+    Ok(b_reduced)
 }
@@ -1,11 +1,13 @@
 use num_bigint::BigInt;
 
+use crate::matrix::Matrix;
+
 pub fn short_vectors(
-    q: Vec<Vec<f64>>,
+    q: Matrix<f64>,
     c: f64,
 ) -> Vec<(Vec<BigInt>, f64)> {
-    let n = q.len();
-    if n == 0 || q.iter().any(|row| row.len() != n) {
+    let n = q.nrows();
+    if n == 0 || !q.is_square() {
         return Vec::new();
     }
     let mut res = Vec::new();
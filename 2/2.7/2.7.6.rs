@@ -1,10 +1,13 @@
-pub fn cholesky_decomposition(a: Vec<Vec<f64>>) -> Option<(Vec<Vec<f64>>, Vec<Vec<f64>>)> {
-    let n = a.len();
-    if n == 0 || a.iter().any(|row| row.len() != n) {
-        return None;
+use crate::error::Error;
+use crate::matrix::Matrix;
+
+pub fn cholesky_decomposition(a: Matrix<f64>) -> Result<(Matrix<f64>, Matrix<f64>), Error> {
+    let n = a.nrows();
+    if n == 0 || !a.is_square() {
+        return Err(Error::DimensionMismatch);
     }
     let mut q = a;
-    let mut r = vec![vec![0f64; n]; n];
+    let mut r = Matrix::zeros(n, n);
     let mut i = 0usize;
     loop {
         if i == n {
@@ -27,7 +30,7 @@ pub fn cholesky_decomposition(a: Vec<Vec<f64>>) -> Option<(Vec<Vec<f64>>, Vec<Ve
 
     for i in 0..n {
         if q[i][i] <= 0.0 {
-            return None;
+            return Err(Error::InvalidArgument("matrix is not positive definite"));
         }
         r[i][i] = q[i][i].sqrt();
         for j in (i + 1)..n {
@@ -35,5 +38,5 @@ pub fn cholesky_decomposition(a: Vec<Vec<f64>>) -> Option<(Vec<Vec<f64>>, Vec<Ve
         }
     }
 
-    Some((q, r))
+    Ok((q, r))
 }
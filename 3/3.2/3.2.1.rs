@@ -1,32 +1,20 @@
 use num_traits::Zero;
-use std::ops::{Add, Div, Mul, Sub};
 
 use crate::euclidean_division;
+use crate::poly::Poly;
+use crate::ring::Field;
 
-fn trim<F: Zero + PartialEq + Clone>(mut p: Vec<F>) -> Vec<F> {
-    while let Some(last) = p.last() {
-        if last.is_zero() {
-            p.pop();
-        } else {
-            break;
-        }
-    }
-    p
-}
-
-pub fn polynomial_gcd<F>(mut a: Vec<F>, mut b: Vec<F>) -> Vec<F>
+pub fn polynomial_gcd<F>(mut a: Poly<F>, mut b: Poly<F>) -> Poly<F>
 where
-    F: Zero + PartialEq + Clone + Add<Output = F> + Sub<Output = F> + Mul<Output = F> + Div<Output = F>,
+    F: Field,
 {
-    a = trim(a);
-    b = trim(b);
-    while !b.is_empty() {
+    while !b.is_zero() {
         let r = match euclidean_division(a.clone(), b.clone()) {
-            Some((_q, r)) => r,
-            None => Vec::new(),
+            Ok((_q, r)) => r,
+            Err(_) => Poly::zero(),
         };
         a = b;
-        b = trim(r);
+        b = r;
     }
-    trim(a)
+    a
 }
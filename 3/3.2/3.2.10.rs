@@ -2,79 +2,35 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Zero};
 
+use crate::poly::Poly;
 use crate::pseudo_division;
 
-fn trim(mut p: Vec<BigInt>) -> Vec<BigInt> {
-    while let Some(last) = p.last() {
-        if last.is_zero() {
-            p.pop();
-        } else {
-            break;
-        }
-    }
-    p
-}
-
-fn degree(p: &Vec<BigInt>) -> isize {
-    if p.is_empty() {
-        -1
-    } else {
-        (p.len() - 1) as isize
-    }
-}
-
-fn content(p: &Vec<BigInt>) -> BigInt {
-    let mut g = BigInt::zero();
-    for c in p.iter() {
-        g = g.gcd(c);
-    }
-    g
-}
-
-fn scalar_div(vec: Vec<BigInt>, d: &BigInt) -> Vec<BigInt> {
-    vec.into_iter().map(|c| c / d).collect()
-}
-
-fn primitive_part(p: Vec<BigInt>) -> Vec<BigInt> {
-    let g = content(&p);
-    if g.is_zero() || g.is_one() {
-        return trim(p);
-    }
-    trim(scalar_div(p, &g))
-}
-
-pub fn primitive_polynomial_gcd(mut a: Vec<BigInt>, mut b: Vec<BigInt>) -> Vec<BigInt> {
-    a = trim(a);
-    b = trim(b);
-    if b.is_empty() {
+pub fn primitive_polynomial_gcd(mut a: Poly<BigInt>, mut b: Poly<BigInt>) -> Poly<BigInt> {
+    if b.is_zero() {
         return a;
     }
-    let ca0 = content(&a);
-    let cb0 = content(&b);
-    let mut d = ca0.gcd(&cb0);
-    a = scalar_div(a.clone(), &ca0);
-    b = scalar_div(b.clone(), &cb0);
+    if a.is_zero() {
+        return b;
+    }
+    let d = a.content().gcd(&b.content());
+    a = a.primitive_part();
+    b = b.primitive_part();
 
     loop {
         let (_q, r) = match pseudo_division(a.clone(), b.clone()) {
-            Some(res) => res,
-            None => return Vec::new(),
+            Ok(res) => res,
+            Err(_) => return Poly::zero(),
         };
-        let r_trim = trim(r);
-        if r_trim.is_empty() {
+        if r.is_zero() {
             break;
         }
-        if degree(&r_trim) == 0 {
-            b = vec![BigInt::one()];
+        if r.degree() == 0 {
+            b = Poly::one();
             break;
         }
         a = b;
-        b = primitive_part(r_trim);
+        b = r.primitive_part();
     }
 
-    trim(
-        b.into_iter()
-            .map(|c| c * d.clone())
-            .collect(),
-    )
+    b.scale(&d)
 }
@@ -1,120 +1,65 @@
 use num_bigint::BigInt;
-use num_integer::Integer;
 use num_traits::{One, Zero};
 
+use crate::poly::Poly;
 use crate::pseudo_division;
 
-fn trim(mut p: Vec<BigInt>) -> Vec<BigInt> {
-    while let Some(last) = p.last() {
-        if last.is_zero() {
-            p.pop();
-        } else {
-            break;
-        }
-    }
-    p
-}
-
-fn degree(p: &Vec<BigInt>) -> isize {
-    if p.is_empty() {
-        -1
-    } else {
-        (p.len() - 1) as isize
-    }
-}
-
-fn content(p: &Vec<BigInt>) -> BigInt {
-    let mut g = BigInt::zero();
-    for c in p.iter() {
-        g = g.gcd(c);
-    }
-    g
-}
-
-fn scalar_div(vec: Vec<BigInt>, d: &BigInt) -> Vec<BigInt> {
-    vec.into_iter().map(|c| c / d).collect()
-}
-
-pub fn resultant_subresultant(mut a: Vec<BigInt>, mut b: Vec<BigInt>) -> BigInt {
-    a = trim(a);
-    b = trim(b);
-    if a.is_empty() || b.is_empty() {
+pub fn resultant_subresultant(mut a: Poly<BigInt>, mut b: Poly<BigInt>) -> BigInt {
+    if a.is_zero() || b.is_zero() {
         return BigInt::zero();
     }
 
-    let mut ca = content(&a);
-    let mut cb = content(&b);
-    if !ca.is_zero() {
-        a = scalar_div(a, &ca);
-    }
-    if !cb.is_zero() {
-        b = scalar_div(b, &cb);
-    }
+    let ca = a.content();
+    let cb = b.content();
+    a = a.primitive_part();
+    b = b.primitive_part();
 
     let mut g = BigInt::one();
     let mut h = BigInt::one();
     let mut s = BigInt::one();
-    let mut t = ca.pow(degree(&b).max(0) as u32) * cb.pow(degree(&a).max(0) as u32);
+    let t = ca.pow(b.degree() as u32) * cb.pow(a.degree() as u32);
 
-    if degree(&a) < degree(&b) {
+    if a.degree() < b.degree() {
         std::mem::swap(&mut a, &mut b);
-        let deg_a = degree(&a);
-        let deg_b = degree(&b);
-        if deg_a % 2 != 0 && deg_b % 2 != 0 {
+        if a.degree() % 2 != 0 && b.degree() % 2 != 0 {
             s = -s;
         }
     }
 
-    loop {
-        let deg_a = degree(&a);
-        let deg_b = degree(&b);
-        if deg_b < 0 {
-            break;
-        }
-        if deg_b == 0 {
-            break;
-        }
-
+    while b.degree() > 0 {
+        let deg_a = a.degree();
+        let deg_b = b.degree();
         if deg_a % 2 != 0 && deg_b % 2 != 0 {
             s = -s;
         }
 
         let delta = deg_a - deg_b;
-        let (_q, mut r) = match pseudo_division(a.clone(), b.clone()) {
-            Some(res) => res,
-            None => return BigInt::zero(),
+        let (_q, r) = match pseudo_division(a.clone(), b.clone()) {
+            Ok(res) => res,
+            Err(_) => return BigInt::zero(),
         };
-        r = trim(r);
-        if r.is_empty() {
-            break;
-        }
 
         a = b;
-        let h_pow = h.pow(delta as u32);
-        let gh_delta = g.clone() * h_pow;
-        b = scalar_div(r, &gh_delta);
-        g = a.last().cloned().unwrap_or_else(BigInt::one);
-        let g_pow = g.pow(delta as u32);
-        let h_div = if delta > 1 {
-            h.pow((delta - 1) as u32)
-        } else {
-            BigInt::one()
-        };
-        h = g_pow / h_div;
-    }
-
-    let deg_b = degree(&b);
-    if deg_b > 0 {
-        return BigInt::zero();
+        let gh_delta = g.clone() * h.pow(delta as u32);
+        b = r.map(|x| x / &gh_delta);
+        // g <- l(A), h <- h^(1 - delta) g^delta
+        g = a.leading_coeff();
+        if delta > 0 {
+            h = g.pow(delta as u32) / h.pow((delta - 1) as u32);
+        }
     }
 
-    let deg_a = degree(&a);
-    if deg_a < 0 {
+    if b.is_zero() {
         return BigInt::zero();
     }
 
-    let h_pow = h.pow(deg_a as u32);
-    let l_b = b.last().cloned().unwrap_or_else(BigInt::one);
-    let l_a = a.last().cloned().unwrap_or_else(BigInt::one);
-    s * t * h_pow * l_b.pow(deg_a as u32) * l_a
+    // h <- l(B)^deg(A) h^(1 - deg(A))
+    let deg_a = a.degree();
+    let l_b = b.leading_coeff();
+    let h = if deg_a > 0 {
+        l_b.pow(deg_a as u32) / h.pow((deg_a - 1) as u32)
+    } else {
+        h
+    };
+    s * t * h
 }
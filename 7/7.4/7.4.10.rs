@@ -2,6 +2,8 @@ use num_bigint::BigInt;
 use num_rational::BigRational;
 use num_traits::{One, Zero};
 
+use crate::error::Error;
+
 pub type CubicCoeff = [BigRational; 10];
 pub type ProjectivePoint = [BigRational; 3];
 
@@ -122,34 +124,10 @@ fn c3(coeffs: &CubicCoeff, u: &BigRational, v: &BigRational) -> BigRational {
         + coeffs[3].clone() * v * v * v
 }
 
-fn poly_add(a: &[BigRational], b: &[BigRational]) -> Vec<BigRational> {
-    let len = a.len().max(b.len());
-    let mut res = vec![BigRational::zero(); len];
-    for i in 0..len {
-        if i < a.len() {
-            res[i] += a[i].clone();
-        }
-        if i < b.len() {
-            res[i] += b[i].clone();
-        }
-    }
-    res
-}
-
-fn poly_mul(a: &[BigRational], b: &[BigRational]) -> Vec<BigRational> {
-    let mut res = vec![BigRational::zero(); a.len() + b.len() - 1];
-    for i in 0..a.len() {
-        for j in 0..b.len() {
-            res[i + j] += a[i].clone() * b[j].clone();
-        }
-    }
-    res
-}
-
 pub fn reduce_general_cubic(
     s: CubicCoeff,
     p0: ProjectivePoint,
-) -> Result<WeierstrassReduction, &'static str> {
+) -> Result<WeierstrassReduction, Error> {
     let mut coeffs = s.clone();
     let _m_forms = identity_forms();
     let _n_forms = identity_forms();
@@ -184,7 +162,7 @@ pub fn reduce_general_cubic(
 
     if coeffs[9].is_zero() {
         if coeffs[8].is_zero() && coeffs[7].is_zero() {
-            return Err("curve singular at P0");
+            return Err(Error::InvalidCurve);
         }
         if coeffs[8].is_zero() {
             coeffs = swap_u_v(coeffs);
@@ -205,7 +183,7 @@ pub fn reduce_general_cubic(
             coeffs = transform_coeffs(coeffs, u_sub.clone(), v_sub.clone(), w_sub.clone());
         } else {
             if c2v.is_zero() {
-                return Err("curve reducible");
+                return Err(Error::InvalidCurve);
             }
             let u_sub = LinearForm([BigRational::zero(), BigRational::zero(), -c2v.clone()]);
             let v_sub = LinearForm([
@@ -217,7 +195,7 @@ pub fn reduce_general_cubic(
             coeffs = transform_coeffs(coeffs, u_sub.clone(), v_sub.clone(), w_sub.clone());
         }
     } else {
-        return Err("unexpected zero s9");
+        return Err(Error::InvalidCurve);
     }
 
     let c1_poly = |u: &BigRational| coeffs[7].clone() * u + coeffs[8].clone();
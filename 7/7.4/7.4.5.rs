@@ -1,6 +1,7 @@
 use num_complex::Complex64;
 use num_traits::Zero;
 
+use crate::error::Error;
 use crate::reduce_upper_half;
 
 fn lattice_wp(z: Complex64, tau: Complex64, _tol: f64, limit: usize) -> (Complex64, Complex64) {
@@ -27,7 +28,7 @@ pub fn weierstrass_p_and_derivative(
     z: Complex64,
     tol: f64,
     limit: usize,
-) -> Option<(Complex64, Complex64)> {
+) -> Result<(Complex64, Complex64), Error> {
     let mut w1 = omega1;
     let mut w2 = omega2;
     if (w2 / w1).im < 0.0 {
@@ -45,8 +46,9 @@ pub fn weierstrass_p_and_derivative(
     zn -= tau_r * n_im;
     zn -= Complex64::new(zn.re.floor(), 0.0);
 
+    // z is (numerically) a lattice point, where wp has a pole
     if zn.norm() <= tol {
-        return None;
+        return Err(Error::InvalidArgument("z lies on the lattice"));
     }
 
     let (wp0, wpp0) = lattice_wp(zn, tau_r, tol, limit);
@@ -54,5 +56,5 @@ pub fn weierstrass_p_and_derivative(
     let scale3 = scale2 * w1_prime;
     let wp = wp0 / scale2;
     let wpp = wpp0 / scale3;
-    Some((wp, wpp))
+    Ok((wp, wpp))
 }
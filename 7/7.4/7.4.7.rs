@@ -2,6 +2,8 @@ use std::f64::consts::PI;
 
 use num_complex::Complex64;
 
+use crate::error::Error;
+
 fn invariants(a: [f64; 6]) -> (f64, f64, f64, f64, f64) {
     let b2 = a[0] * a[0] + 4.0 * a[1];
     let b4 = 2.0 * a[3] + a[0] * a[2];
@@ -75,16 +77,16 @@ pub fn periods_over_reals(
     a: [f64; 6],
     tol: f64,
     max_iter: usize,
-) -> Option<(Complex64, Complex64)> {
+) -> Result<(Complex64, Complex64), Error> {
     let (b2, b4, b6, _b8, delta) = invariants(a);
     if delta == 0.0 {
-        return None;
+        return Err(Error::InvalidCurve);
     }
 
     if delta > 0.0 {
         let roots = cubic_real_roots(b2, b4, b6);
         if roots.len() < 3 {
-            return None;
+            return Err(Error::PrecisionLoss);
         }
         let e1 = roots[0];
         let e2 = roots[1];
@@ -92,35 +94,35 @@ pub fn periods_over_reals(
         let s13 = (e1 - e3).max(0.0).sqrt();
         let s12 = (e1 - e2).max(0.0).sqrt();
         let s23 = (e2 - e3).max(0.0).sqrt();
-        let g1 = agm(s13, s12, tol, max_iter)?;
-        let g2 = agm(s13, s23, tol, max_iter)?;
+        let g1 = agm(s13, s12, tol, max_iter).ok_or(Error::PrecisionLoss)?;
+        let g2 = agm(s13, s23, tol, max_iter).ok_or(Error::PrecisionLoss)?;
         let omega1 = PI / g1;
         let omega2 = Complex64::new(0.0, PI / g2);
-        return Some((Complex64::new(omega1, 0.0), omega2));
+        return Ok((Complex64::new(omega1, 0.0), omega2));
     }
 
     let roots = cubic_real_roots(b2, b4, b6);
     if roots.is_empty() {
-        return None;
+        return Err(Error::PrecisionLoss);
     }
     let e1 = roots[0];
     let a_val = 3.0 * e1 + b2 / 4.0;
     let b_sq = 3.0 * e1 * e1 + (b2 / 2.0) * e1 + b4 / 2.0;
     if b_sq <= 0.0 {
-        return None;
+        return Err(Error::PrecisionLoss);
     }
     let b_val = b_sq.sqrt();
     let u = 2.0 * b_val.sqrt();
     let v_plus_sq = 2.0 * b_val + a_val;
     let v_minus_sq = 2.0 * b_val - a_val;
     if v_plus_sq <= 0.0 || v_minus_sq <= 0.0 || u <= 0.0 {
-        return None;
+        return Err(Error::PrecisionLoss);
     }
     let v_plus = v_plus_sq.sqrt();
     let v_minus = v_minus_sq.sqrt();
-    let g1 = agm(u, v_plus, tol, max_iter)?;
-    let g2 = agm(u, v_minus, tol, max_iter)?;
+    let g1 = agm(u, v_plus, tol, max_iter).ok_or(Error::PrecisionLoss)?;
+    let g2 = agm(u, v_minus, tol, max_iter).ok_or(Error::PrecisionLoss)?;
     let omega1 = 2.0 * PI / g1;
     let omega2 = Complex64::new(-omega1 / 2.0, PI / g2);
-    Some((Complex64::new(omega1, 0.0), omega2))
+    Ok((Complex64::new(omega1, 0.0), omega2))
 }
@@ -2,6 +2,8 @@ use std::f64::consts::PI;
 
 use num_complex::Complex64;
 
+use crate::error::Error;
+
 fn invariants(a: [f64; 6]) -> (f64, f64, f64, f64) {
     let b2 = a[0] * a[0] + 4.0 * a[1];
     let b4 = 2.0 * a[3] + a[0] * a[2];
@@ -104,16 +106,16 @@ pub fn elliptic_logarithm(
     y: f64,
     tol: f64,
     max_iter: usize,
-) -> Option<Complex64> {
+) -> Result<Complex64, Error> {
     let (b2, b4, b6, delta) = invariants(a);
     if delta == 0.0 {
-        return None;
+        return Err(Error::InvalidCurve);
     }
 
     if delta > 0.0 {
         let roots = cubic_real_roots(b2, b4, b6);
         if roots.len() < 3 {
-            return None;
+            return Err(Error::PrecisionLoss);
         }
         let e1 = roots[0];
         let e2 = roots[1];
@@ -129,7 +131,7 @@ pub fn elliptic_logarithm(
             x_cur = lambda * lambda + a[0] * lambda - a[1] - x_cur - e3;
         }
         if x_cur - e3 < 0.0 {
-            return None;
+            return Err(Error::InvalidArgument("point is not on the curve"));
         }
         let mut c_var = (x_cur - e3).sqrt();
 
@@ -145,23 +147,23 @@ pub fn elliptic_logarithm(
             (PI - angle) / a_var
         };
         if f == 0 {
-            return Some(Complex64::new(z, 0.0));
+            return Ok(Complex64::new(z, 0.0));
         }
         let s13 = (e1 - e3).max(0.0).sqrt();
         let s23 = (e2 - e3).max(0.0).sqrt();
-        let g2 = agm(s13, s23, tol, max_iter)?;
+        let g2 = agm(s13, s23, tol, max_iter).ok_or(Error::PrecisionLoss)?;
         let omega2 = Complex64::new(0.0, PI / g2);
-        return Some(Complex64::new(z, 0.0) + omega2 / 2.0);
+        return Ok(Complex64::new(z, 0.0) + omega2 / 2.0);
     }
 
     let roots = cubic_real_roots(b2, b4, b6);
     if roots.is_empty() {
-        return None;
+        return Err(Error::PrecisionLoss);
     }
     let e1 = roots[0];
     let beta_sq = 3.0 * e1 * e1 + (b2 / 2.0) * e1 + b4 / 2.0;
     if beta_sq <= 0.0 {
-        return None;
+        return Err(Error::PrecisionLoss);
     }
     let beta = beta_sq.sqrt();
     let alpha = 3.0 * e1 + b2 / 4.0;
@@ -169,7 +171,7 @@ pub fn elliptic_logarithm(
     let b_var = (alpha + 2.0 * beta).sqrt();
     let x_diff = x - e1;
     if x_diff <= 0.0 || a_var == 0.0 {
-        return None;
+        return Err(Error::InvalidArgument("point is not on the curve"));
     }
     let mut c_var = (x_diff + beta) / x_diff.sqrt();
     let (a_new, _b_new, c_new) = iterate_abc(a_var, b_var, c_var, tol, max_iter);
@@ -187,5 +189,5 @@ pub fn elliptic_logarithm(
     if s > 0.0 {
         z += PI / a_var;
     }
-    Some(Complex64::new(z, 0.0))
+    Ok(Complex64::new(z, 0.0))
 }
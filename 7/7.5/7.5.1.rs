@@ -2,6 +2,9 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Signed, Zero, ToPrimitive};
 
+use crate::error::Error;
+use crate::fp::FpContext;
+
 #[derive(Debug, Clone)]
 pub struct ReductionResult {
     pub kodaira: String,
@@ -35,42 +38,8 @@ fn vp(x: &BigInt, p: &BigInt) -> i64 {
 }
 
 fn legendre_symbol(a: &BigInt, p: &BigInt) -> i32 {
-    let m = mod_norm(a.clone(), p);
-    if m.is_zero() {
-        return 0;
-    }
-    let exp = (p - 1u32) >> 1;
-    let v = mod_pow(m, exp, p);
-    if v.is_one() {
-        1
-    } else {
-        -1
-    }
-}
-
-fn mod_norm(x: BigInt, p: &BigInt) -> BigInt {
-    let r = x.mod_floor(p);
-    if r.is_negative() {
-        r + p
-    } else {
-        r
-    }
-}
-
-fn mod_pow(mut base: BigInt, mut exp: BigInt, p: &BigInt) -> BigInt {
-    base = mod_norm(base, p);
-    let mut res = BigInt::one();
-    while exp > BigInt::zero() {
-        if exp.is_odd() {
-            res = mod_norm(res * &base, p);
-        }
-        exp >>= 1;
-        if exp.is_zero() {
-            break;
-        }
-        base = mod_norm(&base * &base, p);
-    }
-    res
+    let ctx = FpContext::new(p);
+    ctx.legendre(&ctx.elem(a))
 }
 
 fn div_exact(a: &BigInt, b: &BigInt) -> Option<BigInt> {
@@ -151,9 +120,9 @@ fn count_roots_mod_p(coeffs: [BigInt; 4], p: &BigInt) -> Option<usize> {
     for x in 0..p_u {
         let xv = BigInt::from(x);
         let mut val = coeffs[0].clone();
-        val = mod_norm(val + &coeffs[1] * &xv, p);
-        val = mod_norm(val + &coeffs[2] * &xv * &xv, p);
-        val = mod_norm(val + &coeffs[3] * &xv * &xv * &xv, p);
+        val = (val + &coeffs[1] * &xv).mod_floor(p);
+        val = (val + &coeffs[2] * &xv * &xv).mod_floor(p);
+        val = (val + &coeffs[3] * &xv * &xv * &xv).mod_floor(p);
         if val.is_zero() {
             roots += 1;
         }
@@ -161,9 +130,9 @@ fn count_roots_mod_p(coeffs: [BigInt; 4], p: &BigInt) -> Option<usize> {
     Some(roots)
 }
 
-pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<ReductionResult, &'static str> {
+pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<ReductionResult, Error> {
     if p <= BigInt::from(3) {
-        return Err("p must be > 3");
+        return Err(Error::InvalidArgument("p must be greater than 3"));
     }
     let (mut c4, mut c6, mut delta) = invariants_public(&a);
     let j_num = c4.pow(3u32);
@@ -182,12 +151,12 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
         let ku = (k / 12) as u32;
         u = pow_big(&p, ku);
         if a[0].is_odd() {
-            s = div_exact(&(u.clone() - &a[0]), &BigInt::from(2)).ok_or("non-integer s")?;
+            s = div_exact(&(u.clone() - &a[0]), &BigInt::from(2)).ok_or(Error::InvalidCurve)?;
         } else {
-            s = div_exact(&(-&a[0]), &BigInt::from(2)).ok_or("non-integer s")?;
+            s = div_exact(&(-&a[0]), &BigInt::from(2)).ok_or(Error::InvalidCurve)?;
         }
         let a2p = &a[1] - &s * &a[0] - &s * &s;
-        let mod3 = mod_norm(a2p.clone(), &BigInt::from(3)).to_u32().unwrap_or(0) % 3;
+        let mod3 = a2p.mod_floor(&BigInt::from(3)).to_u32().unwrap_or(0) % 3;
         let r_val = match mod3 {
             0 => -a2p / BigInt::from(3),
             1 => (u.clone() * u.clone() - a2p) / BigInt::from(3),
@@ -196,15 +165,15 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
         r = r_val;
         let a3p = &a[2] + &r * &a[0];
         if a3p.is_odd() {
-            t = div_exact(&(u.clone().pow(3u32) - a3p), &BigInt::from(2)).ok_or("non-integer t")?;
+            t = div_exact(&(u.clone().pow(3u32) - a3p), &BigInt::from(2)).ok_or(Error::InvalidCurve)?;
         } else {
-            t = div_exact(&(-a3p), &BigInt::from(2)).ok_or("non-integer t")?;
+            t = div_exact(&(-a3p), &BigInt::from(2)).ok_or(Error::InvalidCurve)?;
         }
 
-        a_new = transform(&a, &u, &r, &s, &t).ok_or("non-integral transform")?;
-        delta = div_exact(&delta, &pow_big(&u, 12)).ok_or("non-integral delta")?;
-        c4 = div_exact(&c4, &pow_big(&u, 4)).ok_or("non-integral c4")?;
-        c6 = div_exact(&c6, &pow_big(&u, 6)).ok_or("non-integral c6")?;
+        a_new = transform(&a, &u, &r, &s, &t).ok_or(Error::InvalidCurve)?;
+        delta = div_exact(&delta, &pow_big(&u, 12)).ok_or(Error::InvalidCurve)?;
+        c4 = div_exact(&c4, &pow_big(&u, 4)).ok_or(Error::InvalidCurve)?;
+        c6 = div_exact(&c6, &pow_big(&u, 6)).ok_or(Error::InvalidCurve)?;
         k = k % 12;
     }
 
@@ -221,7 +190,7 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
         let nu = -j_val;
         if k == 0 {
             let f = 1;
-            let c6d = divide_by_p_pow(&c6, &p, 2).ok_or("c6/p^2 not integral")?;
+            let c6d = divide_by_p_pow(&c6, &p, 2).ok_or(Error::InvalidCurve)?;
             let l = legendre_symbol(&(BigInt::from(-1) * c6d), &p);
             let c_val = if l == 1 { 1 } else { gcd_i64(2, nu) };
             return Ok(ReductionResult {
@@ -240,10 +209,10 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
             let f = 2;
             let c_val: i64 = if nu % 2 == 1 {
                 // need (Δ c6 p^{-9-ν} / p)
-                let div = divide_by_p_pow(&delta, &p, (9 + nu) as u32).ok_or("div failure")?;
+                let div = divide_by_p_pow(&delta, &p, (9 + nu) as u32).ok_or(Error::InvalidCurve)?;
                 3 + legendre_symbol(&(div * &c6), &p) as i64
             } else {
-                let div = divide_by_p_pow(&delta, &p, (6 + nu) as u32).ok_or("div failure")?;
+                let div = divide_by_p_pow(&delta, &p, (6 + nu) as u32).ok_or(Error::InvalidCurve)?;
                 3 + legendre_symbol(&div, &p) as i64
             };
             return Ok(ReductionResult {
@@ -259,7 +228,7 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
                 nu,
             });
         } else {
-            return Err("unexpected k for non-integral j");
+            return Err(Error::InvalidCurve);
         }
     }
 
@@ -281,28 +250,28 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
             "III".to_string()
         }
         4 => {
-            let c6d = divide_by_p_pow(&c6, &p, 2).ok_or("c6/p^2 not integral")?;
+            let c6d = divide_by_p_pow(&c6, &p, 2).ok_or(Error::InvalidCurve)?;
             c_val = 2 + legendre_symbol(&(BigInt::from(-6) * c6d), &p) as i64;
             "IV".to_string()
         }
         6 => {
-            let c4d = divide_by_p_pow(&c4, &p, 2).ok_or("c4/p^2 not integral")?;
-            let c6d3 = divide_by_p_pow(&c6, &p, 3).ok_or("c6/p^3 not integral")?;
+            let c4d = divide_by_p_pow(&c4, &p, 2).ok_or(Error::InvalidCurve)?;
+            let c6d3 = divide_by_p_pow(&c6, &p, 3).ok_or(Error::InvalidCurve)?;
             let roots = count_roots_mod_p(
                 [
-                    mod_norm(-c6d3.clone(), &p),
-                    mod_norm(-BigInt::from(3) * &c4d, &p),
+                    (-&c6d3).mod_floor(&p),
+                    (-BigInt::from(3) * &c4d).mod_floor(&p),
                     BigInt::zero(),
-                    mod_norm(BigInt::from(4), &p),
+                    BigInt::from(4).mod_floor(&p),
                 ],
                 &p,
             )
-            .ok_or("root count failed")?;
+            .ok_or(Error::InvalidCurve)?;
             c_val = 1 + roots as i64;
             "I0*".to_string()
         }
         8 => {
-            let c6d = divide_by_p_pow(&c6, &p, 2).ok_or("c6/p^2 not integral")?;
+            let c6d = divide_by_p_pow(&c6, &p, 2).ok_or(Error::InvalidCurve)?;
             c_val = 2 + legendre_symbol(&(BigInt::from(-6) * c6d), &p) as i64;
             "IV*".to_string()
         }
@@ -314,7 +283,7 @@ pub fn reduce_elliptic_curve_mod_p(a: [BigInt; 6], p: BigInt) -> Result<Reductio
             c_val = 1;
             "II*".to_string()
         }
-        _ => return Err("unexpected k value"),
+        _ => return Err(Error::InvalidCurve),
     };
 
     Ok(ReductionResult {
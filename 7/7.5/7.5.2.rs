@@ -2,6 +2,7 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Signed, Zero, ToPrimitive};
 
+use crate::error::Error;
 use crate::algorithm_7_5_1::ReductionResult;
 
 fn vp(x: &BigInt, p: &BigInt) -> i64 {
@@ -15,45 +16,6 @@ fn vp(x: &BigInt, p: &BigInt) -> i64 {
     n
 }
 
-fn mod_norm(x: BigInt, p: &BigInt) -> BigInt {
-    let r = x.mod_floor(p);
-    if r.is_negative() {
-        r + p
-    } else {
-        r
-    }
-}
-
-fn legendre(a: &BigInt, p: &BigInt) -> i32 {
-    let m = mod_norm(a.clone(), p);
-    if m.is_zero() {
-        return 0;
-    }
-    let exp = (p - 1u32) >> 1;
-    let v = mod_pow(m, exp, p);
-    if v.is_one() {
-        1
-    } else {
-        -1
-    }
-}
-
-fn mod_pow(mut base: BigInt, mut exp: BigInt, p: &BigInt) -> BigInt {
-    base = mod_norm(base, p);
-    let mut res = BigInt::one();
-    while exp > BigInt::zero() {
-        if exp.is_odd() {
-            res = mod_norm(res * &base, p);
-        }
-        exp >>= 1;
-        if exp.is_zero() {
-            break;
-        }
-        base = mod_norm(&base * &base, p);
-    }
-    res
-}
-
 fn div_exact(a: &BigInt, b: &BigInt) -> Option<BigInt> {
     if (a % b).is_zero() {
         Some(a / b)
@@ -127,7 +89,7 @@ fn has_root_quad(a: &BigInt, b: &BigInt, p: &BigInt) -> bool {
     let p_u = p.to_u64().unwrap_or(0);
     for x in 0..p_u {
         let xv = BigInt::from(x);
-        let val = mod_norm(&xv * &xv + a * &xv + b, p);
+        let val = (&xv * &xv + a * &xv + b).mod_floor(p);
         if val.is_zero() {
             return true;
         }
@@ -141,9 +103,9 @@ fn roots_cubic_distinct(coeffs: [BigInt; 4], p: &BigInt) -> (usize, bool) {
     for x in 0..p_u {
         let xv = BigInt::from(x);
         let mut v = coeffs[0].clone();
-        v = mod_norm(v + &coeffs[1] * &xv, p);
-        v = mod_norm(v + &coeffs[2] * &xv * &xv, p);
-        v = mod_norm(v + &coeffs[3] * &xv * &xv * &xv, p);
+        v = (v + &coeffs[1] * &xv).mod_floor(p);
+        v = (v + &coeffs[2] * &xv * &xv).mod_floor(p);
+        v = (v + &coeffs[3] * &xv * &xv * &xv).mod_floor(p);
         if v.is_zero() {
             roots.push(xv.clone());
         }
@@ -163,8 +125,8 @@ fn double_root(coeffs: [BigInt; 3], p: &BigInt) -> Option<BigInt> {
     let p_u = p.to_u64().unwrap_or(0);
     for x in 0..p_u {
         let xv = BigInt::from(x);
-        let val = mod_norm(coeffs[0].clone() + coeffs[1].clone() * &xv + coeffs[2].clone() * &xv * &xv, p);
-        let der = mod_norm(coeffs[1].clone() + BigInt::from(2) * coeffs[2].clone() * &xv, p);
+        let val = (coeffs[0].clone() + coeffs[1].clone() * &xv + coeffs[2].clone() * &xv * &xv).mod_floor(p);
+        let der = (coeffs[1].clone() + BigInt::from(2) * coeffs[2].clone() * &xv).mod_floor(p);
         if val.is_zero() && der.is_zero() {
             return Some(xv);
         }
@@ -172,9 +134,9 @@ fn double_root(coeffs: [BigInt; 3], p: &BigInt) -> Option<BigInt> {
     None
 }
 
-pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result<ReductionResult, &'static str> {
+pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result<ReductionResult, Error> {
     if p != BigInt::from(2) && p != BigInt::from(3) {
-        return Err("p must be 2 or 3");
+        return Err(Error::InvalidArgument("p must be 2 or 3"));
     }
     let mut a = a_in.clone();
     let mut u = BigInt::one();
@@ -223,10 +185,10 @@ pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result
 
     // Step 4 change equation
     if p == BigInt::from(2) {
-        let r1 = mod_norm(-a[3].clone(), &p);
-        let s1 = mod_norm(&r1 + &a[1], &p);
-        let t1 = mod_norm(&a[5] + &r1 * (&a[3] + &s1), &p);
-        a = transform(&a, &BigInt::one(), &r1, &s1, &t1).ok_or("transform failed")?;
+        let r1 = (-&a[3]).mod_floor(&p);
+        let s1 = (&r1 + &a[1]).mod_floor(&p);
+        let t1 = (&a[5] + &r1 * (&a[3] + &s1)).mod_floor(&p);
+        a = transform(&a, &BigInt::one(), &r1, &s1, &t1).ok_or(Error::InvalidCurve)?;
         let (_u, _r, _s, _t) = combine(&u, &r, &s, &t, &BigInt::one(), &r1, &s1, &t1);
         r = _r;
         s = _s;
@@ -234,10 +196,10 @@ pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result
     } else {
         // p=3
         let (_, _, b6, _, _, _, _) = invariants(&a);
-        let r1 = mod_norm(-b6, &p);
-        let s1 = mod_norm(a[0].clone(), &p);
-        let t1 = mod_norm(&a[2] + &r1 * &a[0], &p);
-        a = transform(&a, &BigInt::one(), &r1, &s1, &t1).ok_or("transform failed")?;
+        let r1 = (-b6).mod_floor(&p);
+        let s1 = a[0].mod_floor(&p);
+        let t1 = (&a[2] + &r1 * &a[0]).mod_floor(&p);
+        a = transform(&a, &BigInt::one(), &r1, &s1, &t1).ok_or(Error::InvalidCurve)?;
         let (_u, _r, _s, _t) = combine(&u, &r, &s, &t, &BigInt::one(), &r1, &s1, &t1);
         r = _r;
         s = _s;
@@ -304,9 +266,9 @@ pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result
         let k_val = if p == BigInt::from(2) {
             BigInt::from(2)
         } else {
-            mod_norm(a[2].clone(), &BigInt::from(9))
+            a[2].mod_floor(&BigInt::from(9))
         };
-        a = transform(&a, &BigInt::one(), &BigInt::zero(), &BigInt::zero(), &k_val).ok_or("transform failed")?;
+        a = transform(&a, &BigInt::one(), &BigInt::zero(), &BigInt::zero(), &k_val).ok_or(Error::InvalidCurve)?;
         let (_u, _r, _s, _t) = combine(&u, &r, &s, &t, &BigInt::one(), &BigInt::zero(), &BigInt::zero(), &k_val);
         r = _r;
         s = _s;
@@ -349,7 +311,7 @@ pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result
         if let Some(root) = double_root([a6d.clone(), a4d.clone(), a2d.clone()], &p) {
             if !root.is_zero() {
                 let ap = &root * &p;
-                a = transform(&a, &BigInt::one(), &ap, &BigInt::zero(), &BigInt::zero()).ok_or("transform failed")?;
+                a = transform(&a, &BigInt::one(), &ap, &BigInt::zero(), &BigInt::zero()).ok_or(Error::InvalidCurve)?;
                 let (_u, _r, _s, _t) = combine(&u, &r, &s, &t, &BigInt::one(), &ap, &BigInt::zero(), &BigInt::zero());
                 r = _r;
                 s = _s;
@@ -372,7 +334,7 @@ pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result
         if let Some(dr) = double_root([a6d.clone(), a3d.clone(), BigInt::one()], &p) {
             if !dr.is_zero() {
                 let ap2 = &dr * &pow_big(&p, 2);
-                a = transform(&a, &BigInt::one(), &BigInt::zero(), &BigInt::zero(), &ap2).ok_or("transform failed")?;
+                a = transform(&a, &BigInt::one(), &BigInt::zero(), &BigInt::zero(), &ap2).ok_or(Error::InvalidCurve)?;
                 let (_u, _r, _s, _t) = combine(&u, &r, &s, &t, &BigInt::one(), &BigInt::zero(), &BigInt::zero(), &ap2);
                 r = _r;
                 s = _s;
@@ -428,7 +390,7 @@ pub fn reduce_elliptic_curve_mod_small_p(a_in: [BigInt; 6], p: BigInt) -> Result
     }
 
     // Non minimal
-    a = transform(&a, &p, &BigInt::zero(), &BigInt::zero(), &BigInt::zero()).ok_or("transform failed")?;
+    a = transform(&a, &p, &BigInt::zero(), &BigInt::zero(), &BigInt::zero()).ok_or(Error::InvalidCurve)?;
     let (_u, _r, _s, _t) = combine(&u, &r, &s, &t, &p, &BigInt::zero(), &BigInt::zero(), &BigInt::zero());
     u = _u;
     r = _r;
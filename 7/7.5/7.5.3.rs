@@ -1,6 +1,8 @@
 use num_bigint::BigInt;
 use num_traits::{One, Zero, Signed};
 
+use crate::error::Error;
+use crate::factor::factor;
 use crate::{reduce_elliptic_curve_mod_p, reduce_elliptic_curve_mod_small_p};
 
 fn abs_bigint(x: &BigInt) -> BigInt {
@@ -11,28 +13,6 @@ fn abs_bigint(x: &BigInt) -> BigInt {
     }
 }
 
-fn factors(mut n: BigInt) -> Vec<BigInt> {
-    let mut res = Vec::new();
-    n = abs_bigint(&n);
-    let two = BigInt::from(2);
-    while (&n % &two).is_zero() {
-        res.push(two.clone());
-        n /= &two;
-    }
-    let mut p = BigInt::from(3);
-    while &p * &p <= n {
-        while (&n % &p).is_zero() {
-            res.push(p.clone());
-            n /= &p;
-        }
-        p += 2;
-    }
-    if n > BigInt::one() {
-        res.push(n);
-    }
-    res
-}
-
 fn combine(u0: &BigInt, r0: &BigInt, s0: &BigInt, t0: &BigInt, u1: &BigInt, r1: &BigInt, s1: &BigInt, t1: &BigInt) -> (BigInt, BigInt, BigInt, BigInt) {
     let u = u0 * u1;
     let r = r1 + u1 * u1 * r0;
@@ -49,9 +29,9 @@ pub struct GlobalReductionResult {
     pub t: BigInt,
 }
 
-pub fn global_reduction(a: [BigInt; 6]) -> Result<GlobalReductionResult, &'static str> {
+pub fn global_reduction(a: [BigInt; 6]) -> Result<GlobalReductionResult, Error> {
     let (_, _, delta) = crate::algorithm_7_5_1::invariants_public(&a); // reuse invariant computation
-    let mut d = abs_bigint(&delta);
+    let d = abs_bigint(&delta);
     let mut n = BigInt::one();
     let mut u_acc = BigInt::one();
     let mut r_acc = BigInt::zero();
@@ -59,27 +39,10 @@ pub fn global_reduction(a: [BigInt; 6]) -> Result<GlobalReductionResult, &'stati
     let mut t_acc = BigInt::zero();
 
     if d.is_zero() {
-        return Ok(GlobalReductionResult {
-            n_conductor: BigInt::zero(),
-            u: u_acc,
-            r: r_acc,
-            s: s_acc,
-            t: t_acc,
-        });
+        return Err(Error::InvalidCurve);
     }
 
-    let mut primes = factors(d.clone());
-    primes.sort();
-    primes.dedup();
-
-    for p in primes {
-        loop {
-            if (&d % &p).is_zero() {
-                d /= &p;
-            } else {
-                break;
-            }
-        }
+    for (p, _) in factor(&d) {
         let loc = if p == BigInt::from(2) || p == BigInt::from(3) {
             reduce_elliptic_curve_mod_small_p(a.clone(), p.clone())?
         } else {
@@ -4,6 +4,9 @@ use num_rational::BigRational;
 use num_traits::{One, Zero, Signed};
 use std::collections::HashSet;
 
+use crate::divisors_factored;
+use crate::factor::factor;
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RationalPoint {
     Inf,
@@ -41,49 +44,7 @@ fn gcd_big(a: &BigInt, b: &BigInt) -> BigInt {
 }
 
 fn divisors(n: &BigInt) -> Vec<BigInt> {
-    let mut res = Vec::new();
-    let mut factors = Vec::new();
-    let mut m = n.clone();
-    let two = BigInt::from(2);
-    let mut count = 0;
-    while (&m % &two).is_zero() {
-        m /= &two;
-        count += 1;
-    }
-    if count > 0 {
-        factors.push((two.clone(), count));
-    }
-    let mut p = BigInt::from(3);
-    while &p * &p <= m {
-        let mut c = 0;
-        while (&m % &p).is_zero() {
-            m /= &p;
-            c += 1;
-        }
-        if c > 0 {
-            factors.push((p.clone(), c));
-        }
-        p += 2;
-    }
-    if m > BigInt::one() {
-        factors.push((m, 1));
-    }
-
-    fn gen_divisors(idx: usize, factors: &[(BigInt, i32)], cur: BigInt, res: &mut Vec<BigInt>) {
-        if idx == factors.len() {
-            res.push(cur);
-            return;
-        }
-        let (p, e) = &factors[idx];
-        let mut prod = BigInt::one();
-        for _ in 0..=*e {
-            gen_divisors(idx + 1, factors, &cur * &prod, res);
-            prod *= p;
-        }
-    }
-    gen_divisors(0, &factors, BigInt::one(), &mut res);
-    res.sort();
-    res
+    divisors_factored(&factor(n))
 }
 
 fn rational_roots_cubic(coeffs: &[BigInt; 4]) -> Vec<BigRational> {
@@ -198,21 +159,8 @@ pub fn rational_torsion_points(a: [BigInt; 6]) -> Vec<RationalPoint> {
 
     // n = 4 * prod p^{floor(vp(Δ)/2)}
     let mut n = BigInt::from(4);
-    let mut d = delta.clone();
-    let mut p = BigInt::from(2);
-    while &p * &p <= d {
-        let mut e = 0;
-        while (&d % &p).is_zero() {
-            d /= &p;
-            e += 1;
-        }
-        if e > 0 {
-            n *= pow_big(&p, (e / 2) as u32);
-        }
-        p += 1;
-    }
-    if d > BigInt::one() {
-        n *= BigInt::one();
+    for (p, e) in factor(&delta) {
+        n *= pow_big(&p, e / 2);
     }
     let divisors_list = divisors(&n);
 
@@ -3,6 +3,7 @@ use std::f64::consts::PI;
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
 
+use crate::error::Error;
 use crate::{elliptic_logarithm, periods_over_reals};
 
 fn b_invariants_f64(a: &[BigInt; 6]) -> (f64, f64, f64, f64, f64) {
@@ -19,7 +20,7 @@ fn b_invariants_f64(a: &[BigInt; 6]) -> (f64, f64, f64, f64, f64) {
     (b2, b4, b6, b8, delta)
 }
 
-pub fn height_archimedean(a: [BigInt; 6], x: f64, y: f64) -> Option<f64> {
+pub fn height_archimedean(a: [BigInt; 6], x: f64, y: f64) -> Result<f64, Error> {
     let (b2, b4, b6, _b8, delta) = b_invariants_f64(&a);
     let a_f: [f64; 6] = [
         a[0].to_f64().unwrap_or(0.0),
@@ -44,11 +45,11 @@ pub fn height_archimedean(a: [BigInt; 6], x: f64, y: f64) -> Option<f64> {
         theta += ((2 * n + 1) as f64 * t).sin() * ((-1i32).pow(n as u32) as f64) * term_mag;
     }
     if theta == 0.0 || q == 0.0 {
-        return None;
+        return Err(Error::PrecisionLoss);
     }
     let part1 = (1.0 / 32.0) * (delta.abs() / q.abs()).ln();
     let numerator = x * x * x + (b2 / 4.0) * x * x + (b4 / 2.0) * x + b6 / 4.0;
     let part2 = (1.0 / 8.0) * (numerator / lambda).ln();
     let part3 = -0.25 * theta.abs().ln();
-    Some(part1 + part2 + part3)
+    Ok(part1 + part2 + part3)
 }
@@ -2,51 +2,15 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Zero};
 
-pub fn mod_pow(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> BigInt {
-    if modulus.is_one() {
-        return BigInt::zero();
-    }
-    let mut result = BigInt::one();
-    let mut base = base.mod_floor(modulus);
-    let mut e = exp.clone();
-    while e > BigInt::zero() {
-        if e.is_odd() {
-            result = (result * &base).mod_floor(modulus);
-        }
-        e >>= 1;
-        if e.is_zero() {
-            break;
-        }
-        base = (&base * &base).mod_floor(modulus);
-    }
-    result
-}
+use crate::error::Error;
+use crate::factor::factor;
 
 pub fn mod_inv(a: &BigInt, modulus: &BigInt) -> Option<BigInt> {
-    let (g, x) = extended_gcd(a.clone(), modulus.clone());
-    if g != BigInt::one() {
+    let (u, _v, d) = crate::extended_euclid(a.clone(), modulus.clone());
+    if !d.is_one() {
         return None;
     }
-    Some(x.mod_floor(modulus))
-}
-
-fn extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt) {
-    let mut old_r = a;
-    let mut r = b;
-    let mut old_s = BigInt::one();
-    let mut s = BigInt::zero();
-
-    while !r.is_zero() {
-        let q = &old_r / &r;
-        let temp_r = &old_r - &q * &r;
-        old_r = r;
-        r = temp_r;
-
-        let temp_s = &old_s - &q * &s;
-        old_s = s;
-        s = temp_s;
-    }
-    (old_r, old_s)
+    Some(u.mod_floor(modulus))
 }
 
 pub fn int_sqrt(n: &BigInt) -> BigInt {
@@ -62,3 +26,209 @@ pub fn int_sqrt(n: &BigInt) -> BigInt {
     }
     x
 }
+
+// Multiplicative functions. Each takes n != 0 (the sign is ignored) and
+// factors it, or takes the factorization of |n| directly; primes listed with
+// exponent 0 are ignored.
+
+fn factored(n: &BigInt) -> Result<Vec<(BigInt, u32)>, Error> {
+    if n.is_zero() {
+        return Err(Error::InvalidArgument("n must be nonzero"));
+    }
+    Ok(factor(n))
+}
+
+pub fn euler_phi(n: &BigInt) -> Result<BigInt, Error> {
+    Ok(euler_phi_factored(&factored(n)?))
+}
+
+pub fn euler_phi_factored(f: &[(BigInt, u32)]) -> BigInt {
+    f.iter()
+        .filter(|(_, e)| *e > 0)
+        .map(|(p, e)| p.pow(e - 1) * (p - 1u32))
+        .product()
+}
+
+pub fn moebius(n: &BigInt) -> Result<i32, Error> {
+    Ok(moebius_factored(&factored(n)?))
+}
+
+pub fn moebius_factored(f: &[(BigInt, u32)]) -> i32 {
+    if f.iter().any(|(_, e)| *e > 1) {
+        0
+    } else if f.iter().filter(|(_, e)| *e > 0).count().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+// sum of the k-th powers of the divisors
+pub fn sigma(k: u32, n: &BigInt) -> Result<BigInt, Error> {
+    Ok(sigma_factored(k, &factored(n)?))
+}
+
+pub fn sigma_factored(k: u32, f: &[(BigInt, u32)]) -> BigInt {
+    if k == 0 {
+        return tau_factored(f);
+    }
+    f.iter()
+        .map(|(p, e)| {
+            let pk = p.pow(k);
+            (pk.pow(e + 1) - 1u32) / (pk - 1u32)
+        })
+        .product()
+}
+
+// number of divisors
+pub fn tau(n: &BigInt) -> Result<BigInt, Error> {
+    Ok(tau_factored(&factored(n)?))
+}
+
+pub fn tau_factored(f: &[(BigInt, u32)]) -> BigInt {
+    f.iter().map(|(_, e)| BigInt::from(e + 1)).product()
+}
+
+// exponent of (Z/nZ)^*
+pub fn carmichael_lambda(n: &BigInt) -> Result<BigInt, Error> {
+    Ok(carmichael_lambda_factored(&factored(n)?))
+}
+
+pub fn carmichael_lambda_factored(f: &[(BigInt, u32)]) -> BigInt {
+    f.iter()
+        .filter(|(_, e)| *e > 0)
+        .map(|(p, e)| {
+            if *p == BigInt::from(2) && *e >= 3 {
+                BigInt::one() << (e - 2)
+            } else {
+                p.pow(e - 1) * (p - 1u32)
+            }
+        })
+        .fold(BigInt::one(), |acc, l| acc.lcm(&l))
+}
+
+// positive divisors in increasing order
+pub fn divisors(n: &BigInt) -> Result<Vec<BigInt>, Error> {
+    Ok(divisors_factored(&factored(n)?))
+}
+
+pub fn divisors_factored(f: &[(BigInt, u32)]) -> Vec<BigInt> {
+    let mut res = vec![BigInt::one()];
+    for (p, e) in f {
+        let mut next = Vec::with_capacity(res.len() * (*e as usize + 1));
+        for d in &res {
+            let mut q = d.clone();
+            for _ in 0..=*e {
+                next.push(q.clone());
+                q *= p;
+            }
+        }
+        res = next;
+    }
+    res.sort();
+    res
+}
+
+// product of the distinct primes dividing n
+pub fn radical(n: &BigInt) -> Result<BigInt, Error> {
+    Ok(radical_factored(&factored(n)?))
+}
+
+pub fn radical_factored(f: &[(BigInt, u32)]) -> BigInt {
+    f.iter()
+        .filter(|(_, e)| *e > 0)
+        .map(|(p, _)| p.clone())
+        .product()
+}
+
+// v_p(n), the exponent of p in n; needs no factorization
+pub fn valuation(p: &BigInt, n: &BigInt) -> Result<u32, Error> {
+    if *p < BigInt::from(2) {
+        return Err(Error::InvalidArgument("p must be at least 2"));
+    }
+    if n.is_zero() {
+        return Err(Error::InvalidArgument("n must be nonzero"));
+    }
+    let mut m = n.clone();
+    let mut v = 0;
+    loop {
+        let (q, r) = m.div_rem(p);
+        if !r.is_zero() {
+            return Ok(v);
+        }
+        m = q;
+        v += 1;
+    }
+}
+
+pub fn valuation_factored(p: &BigInt, f: &[(BigInt, u32)]) -> u32 {
+    f.iter().find(|(q, _)| q == p).map_or(0, |(_, e)| *e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_divisors(n: u64) -> Vec<u64> {
+        (1..=n).filter(|&d| n.is_multiple_of(d)).collect()
+    }
+
+    #[test]
+    fn multiplicative_functions_match_brute_force() {
+        for n in 1..300u64 {
+            let big = BigInt::from(n);
+            let divs = brute_divisors(n);
+            let primes: Vec<u64> = divs
+                .iter()
+                .copied()
+                .filter(|&d| d > 1 && brute_divisors(d).len() == 2)
+                .collect();
+            let phi = (1..=n).filter(|k| k.gcd(&n) == 1).count() as u64;
+            let squarefree = primes.iter().all(|p| n % (p * p) != 0);
+            let mu = if !squarefree {
+                0
+            } else if primes.len().is_multiple_of(2) {
+                1
+            } else {
+                -1
+            };
+            // least e with k^e = 1 mod n for every unit k
+            let lambda = (1..=n.max(1))
+                .find(|&e| {
+                    (1..=n).filter(|k| k.gcd(&n) == 1).all(|k| {
+                        BigInt::from(k).modpow(&BigInt::from(e), &big) == BigInt::one() % &big
+                    })
+                })
+                .unwrap();
+
+            assert_eq!(euler_phi(&big).unwrap(), BigInt::from(phi));
+            assert_eq!(moebius(&big).unwrap(), mu);
+            assert_eq!(tau(&big).unwrap(), BigInt::from(divs.len()));
+            assert_eq!(
+                sigma(1, &big).unwrap(),
+                BigInt::from(divs.iter().sum::<u64>())
+            );
+            assert_eq!(
+                divisors(&big).unwrap(),
+                divs.iter().map(|&d| BigInt::from(d)).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                radical(&big).unwrap(),
+                BigInt::from(primes.iter().product::<u64>())
+            );
+            assert_eq!(carmichael_lambda(&big).unwrap(), BigInt::from(lambda));
+        }
+    }
+
+    #[test]
+    fn zero_exponents_are_ignored() {
+        let f = [(BigInt::from(2), 1), (BigInt::from(3), 0)];
+        assert_eq!(radical_factored(&f), BigInt::from(2));
+        assert_eq!(euler_phi_factored(&f), BigInt::one());
+        assert_eq!(moebius_factored(&f), -1);
+        assert_eq!(tau_factored(&f), BigInt::from(2));
+        assert_eq!(sigma_factored(1, &f), BigInt::from(3));
+        assert_eq!(carmichael_lambda_factored(&f), BigInt::one());
+        assert_eq!(divisors_factored(&f), vec![BigInt::one(), BigInt::from(2)]);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use crate::arith::int_sqrt;
+use crate::error::Error;
+
+// the partial quotients [a_0; a_1, ..., a_n] of x, with a_i >= 1 for i >= 1
+// and a_n >= 2 unless x is an integer
+pub fn rational_continued_fraction(x: &BigRational) -> Vec<BigInt> {
+    let mut a = x.numer().clone();
+    let mut b = x.denom().clone();
+    let mut partials = Vec::new();
+    while !b.is_zero() {
+        let (q, r) = a.div_mod_floor(&b);
+        partials.push(q);
+        a = b;
+        b = r;
+    }
+    partials
+}
+
+// the expansion of (p + sqrt(d)) / q, d > 0 not a square, as the partial
+// quotients before the period and those of the period; when q does not divide
+// d - p^2 the number is first rewritten as (p |q| + sqrt(d q^2)) / (q |q|)
+pub fn quadratic_continued_fraction(
+    p: &BigInt,
+    q: &BigInt,
+    d: &BigInt,
+) -> Result<(Vec<BigInt>, Vec<BigInt>), Error> {
+    if q.is_zero() {
+        return Err(Error::InvalidArgument("denominator must be nonzero"));
+    }
+    if !d.is_positive() {
+        return Err(Error::InvalidArgument("d must be positive"));
+    }
+    let s = int_sqrt(d);
+    if &s * &s == *d {
+        return Err(Error::InvalidArgument("d must not be a square"));
+    }
+
+    let (mut p, mut q, d, s) = if (d - p * p).is_multiple_of(q) {
+        (p.clone(), q.clone(), d.clone(), s)
+    } else {
+        let d = d * q * q;
+        let s = int_sqrt(&d);
+        (p * q.abs(), q * q.abs(), d, s)
+    };
+
+    // the complete quotients (P + sqrt(D)) / Q keep Q | D - P^2, and once
+    // reduced they cycle, so the first repeated pair closes the period
+    let mut seen = HashMap::new();
+    let mut partials = Vec::new();
+    loop {
+        if let Some(&start) = seen.get(&(p.clone(), q.clone())) {
+            let period = partials.split_off(start);
+            return Ok((partials, period));
+        }
+        seen.insert((p.clone(), q.clone()), partials.len());
+        // sqrt(D) is irrational, so floor((P + sqrt(D)) / Q) is found from
+        // floor(sqrt(D)) alone
+        let a = if q.is_positive() {
+            (&p + &s).div_floor(&q)
+        } else {
+            -(&p + &s).div_floor(&-&q) - 1
+        };
+        p = &a * &q - &p;
+        q = (&d - &p * &p) / &q;
+        partials.push(a);
+    }
+}
+
+// p_n / q_n for each n, from p_n = a_n p_(n-1) + p_(n-2) and the same for q_n
+pub fn convergents(partials: &[BigInt]) -> Vec<BigRational> {
+    let (mut p0, mut p1) = (BigInt::zero(), BigInt::one());
+    let (mut q0, mut q1) = (BigInt::one(), BigInt::zero());
+    let mut result = Vec::with_capacity(partials.len());
+    for a in partials {
+        let p = a * &p1 + &p0;
+        let q = a * &q1 + &q0;
+        result.push(BigRational::new_raw(p.clone(), q.clone()));
+        p0 = std::mem::replace(&mut p1, p);
+        q0 = std::mem::replace(&mut q1, q);
+    }
+    result
+}
+
+// the fractions (p_(n-2) + k p_(n-1)) / (q_(n-2) + k q_(n-1)) for n >= 1 and
+// 1 <= k <= a_n, after p_0 / q_0: the convergents (k = a_n) together with the
+// intermediate fractions between them, in order of increasing denominator
+pub fn semiconvergents(partials: &[BigInt]) -> Vec<BigRational> {
+    let mut result = Vec::new();
+    let Some(a0) = partials.first() else {
+        return result;
+    };
+    let (mut p0, mut p1) = (BigInt::one(), a0.clone());
+    let (mut q0, mut q1) = (BigInt::zero(), BigInt::one());
+    result.push(BigRational::from_integer(a0.clone()));
+    for a in &partials[1..] {
+        let mut k = BigInt::one();
+        while k <= *a {
+            let p = &p0 + &k * &p1;
+            let q = &q0 + &k * &q1;
+            result.push(BigRational::new_raw(p, q));
+            k += 1;
+        }
+        let p = a * &p1 + &p0;
+        let q = a * &q1 + &q0;
+        p0 = std::mem::replace(&mut p1, p);
+        q0 = std::mem::replace(&mut q1, q);
+    }
+    result
+}
+
+// the fraction closest to x among those with denominator at most bound, the
+// smaller denominator on a tie: it is either the last convergent within the
+// bound or the largest semiconvergent after it that still fits
+pub fn best_approximation(x: &BigRational, bound: &BigInt) -> Result<BigRational, Error> {
+    if !bound.is_positive() {
+        return Err(Error::InvalidArgument("bound must be positive"));
+    }
+    if x.denom() <= bound {
+        return Ok(x.clone());
+    }
+
+    let (mut p0, mut p1) = (BigInt::zero(), BigInt::one());
+    let (mut q0, mut q1) = (BigInt::one(), BigInt::zero());
+    for a in rational_continued_fraction(x) {
+        let q = &a * &q1 + &q0;
+        if q > *bound {
+            let k = (bound - &q0) / &q1;
+            let convergent = BigRational::new_raw(p1.clone(), q1.clone());
+            let semi = BigRational::new(&p0 + &k * &p1, &q0 + &k * &q1);
+            return Ok(if (x - &semi).abs() < (x - &convergent).abs() {
+                semi
+            } else {
+                convergent
+            });
+        }
+        let p = &a * &p1 + &p0;
+        p0 = std::mem::replace(&mut p1, p);
+        q0 = std::mem::replace(&mut q1, q);
+    }
+    unreachable!("the last convergent is x, whose denominator exceeds the bound")
+}
+
+// the convergent p / q just before the end of the first period of sqrt(d),
+// which has p^2 - d q^2 = (-1)^l for the period length l
+fn pell_period(d: &BigInt) -> Result<(BigInt, BigInt, usize), Error> {
+    let (pre, period) = quadratic_continued_fraction(&BigInt::zero(), &BigInt::one(), d)?;
+    let l = period.len();
+    let mut partials = pre;
+    partials.extend_from_slice(&period[..l - 1]);
+    let c = convergents(&partials).pop().unwrap();
+    Ok((c.numer().clone(), c.denom().clone(), l))
+}
+
+// the fundamental solution x, y > 0 of x^2 - d y^2 = 1, d > 0 not a square
+pub fn pell(d: &BigInt) -> Result<(BigInt, BigInt), Error> {
+    let (x, y, l) = pell_period(d)?;
+    if l.is_odd() {
+        // squaring the solution of the negative equation
+        Ok((&x * &x + d * &y * &y, 2 * x * y))
+    } else {
+        Ok((x, y))
+    }
+}
+
+// the smallest solution x, y > 0 of x^2 - d y^2 = -1, which exists exactly
+// when the period of sqrt(d) has odd length
+pub fn negative_pell(d: &BigInt) -> Result<Option<(BigInt, BigInt)>, Error> {
+    let (x, y, l) = pell_period(d)?;
+    Ok(if l.is_odd() { Some((x, y)) } else { None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(p: i64, q: i64) -> BigRational {
+        BigRational::new(BigInt::from(p), BigInt::from(q))
+    }
+
+    #[test]
+    fn rational_expansion_rebuilds_x() {
+        for q in 1..40i64 {
+            for p in -60..60i64 {
+                let x = ratio(p, q);
+                let partials = rational_continued_fraction(&x);
+                assert!(partials[1..].iter().all(|a| a.is_positive()));
+                assert_eq!(convergents(&partials).last(), Some(&x));
+            }
+        }
+        let partials: Vec<BigInt> = [3, 7, 15, 1, 292]
+            .iter()
+            .map(|&a| BigInt::from(a))
+            .collect();
+        assert_eq!(
+            rational_continued_fraction(&ratio(103_993, 33_102)),
+            partials
+        );
+    }
+
+    #[test]
+    fn best_approximation_matches_exhaustive_search() {
+        let x = ratio(103_993, 33_102);
+        for bound in 1..200i64 {
+            // the closest p / q with q <= bound, the smaller q on a tie
+            let mut best = ratio(0, 1);
+            let mut best_err = None;
+            for q in 1..=bound {
+                let p = (&x * BigRational::from_integer(BigInt::from(q))).round();
+                let c = BigRational::new(p.to_integer(), BigInt::from(q));
+                let err = (&x - &c).abs();
+                if best_err.as_ref().is_none_or(|e| err < *e) {
+                    best = c;
+                    best_err = Some(err);
+                }
+            }
+            assert_eq!(best_approximation(&x, &BigInt::from(bound)), Ok(best));
+        }
+    }
+
+    #[test]
+    fn pell_matches_exhaustive_search() {
+        for d in 2..60i64 {
+            let s = int_sqrt(&BigInt::from(d));
+            if &s * &s == BigInt::from(d) {
+                continue;
+            }
+            let (x, y) = pell(&BigInt::from(d)).unwrap();
+            assert_eq!(&x * &x - d * &y * &y, BigInt::one());
+            // no smaller y solves either equation
+            let y_max = y.clone();
+            let smallest = |target: i64| {
+                (1..)
+                    .map(BigInt::from)
+                    .take_while(|v| *v <= y_max)
+                    .find(|v| {
+                        let x2 = d * v * v + target;
+                        let r = int_sqrt(&x2);
+                        &r * &r == x2
+                    })
+            };
+            assert_eq!(smallest(1), Some(y.clone()));
+            match negative_pell(&BigInt::from(d)).unwrap() {
+                Some((x, y)) => {
+                    assert_eq!(&x * &x - d * &y * &y, -BigInt::one());
+                    assert_eq!(smallest(-1), Some(y));
+                }
+                None => assert_eq!(smallest(-1), None),
+            }
+        }
+    }
+
+    #[test]
+    fn quadratic_expansion_of_square_roots() {
+        let (pre, period) =
+            quadratic_continued_fraction(&BigInt::zero(), &BigInt::one(), &BigInt::from(14))
+                .unwrap();
+        assert_eq!(pre, vec![BigInt::from(3)]);
+        let expected: Vec<BigInt> = [1, 2, 1, 6].iter().map(|&a| BigInt::from(a)).collect();
+        assert_eq!(period, expected);
+        assert!(
+            quadratic_continued_fraction(&BigInt::zero(), &BigInt::one(), &BigInt::from(16))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+use crate::arith::mod_inv;
+use crate::error::Error;
+use crate::matrix::Matrix;
+use crate::poly::Poly;
+
+// x with x = x_i (mod m_i) for moduli that need not be coprime, together
+// with the lcm of the m_i modulo which it is unique; two congruences agree
+// exactly when their residues agree modulo the gcd of their moduli
+pub fn chinese_remainder_general(mi: &[BigInt], xi: &[BigInt]) -> Result<(BigInt, BigInt), Error> {
+    if mi.len() != xi.len() || mi.is_empty() {
+        return Err(Error::DimensionMismatch);
+    }
+    if mi.iter().any(|m| m.is_zero()) {
+        return Err(Error::InvalidArgument("moduli must be nonzero"));
+    }
+
+    let mut m = mi[0].abs();
+    let mut x = xi[0].mod_floor(&m);
+    for (n, y) in mi[1..].iter().zip(&xi[1..]) {
+        let n = n.abs();
+        let g = m.gcd(&n);
+        let diff = y - &x;
+        if !diff.is_multiple_of(&g) {
+            return Err(Error::Inconsistent);
+        }
+        // x + m t with (m / g) t = diff / g (mod n / g)
+        let ng = &n / &g;
+        let t = (&diff / &g) * mod_inv(&(&m / &g), &ng).ok_or(Error::NotInvertible)?;
+        let l = &m * &ng;
+        x = (x + &m * t.mod_floor(&ng)).mod_floor(&l);
+        m = l;
+    }
+    Ok((x, m))
+}
+
+// pairwise coprime moduli with their subproduct tree and the inverses
+// c_i = (M / m_i)^-1 mod m_i, so that any number of residue vectors lift by
+// x = sum x_i c_i M / m_i, summed up the tree in O(M(n) log n)
+pub struct CrtBasis {
+    // tree[0] the moduli, tree[k + 1] the products of pairs from tree[k], and
+    // the last level the product M
+    tree: Vec<Vec<BigInt>>,
+    inverses: Vec<BigInt>,
+}
+
+impl CrtBasis {
+    pub fn new(mi: &[BigInt]) -> Result<CrtBasis, Error> {
+        if mi.is_empty() {
+            return Err(Error::DimensionMismatch);
+        }
+        if mi.iter().any(|m| m.is_zero()) {
+            return Err(Error::InvalidArgument("moduli must be nonzero"));
+        }
+        let mut tree = vec![mi.iter().map(|m| m.abs()).collect::<Vec<_>>()];
+        while tree[tree.len() - 1].len() > 1 {
+            let level = tree[tree.len() - 1]
+                .chunks(2)
+                .map(|pair| pair.iter().product())
+                .collect();
+            tree.push(level);
+        }
+
+        // M mod m_i^2 down the remainder tree, then M / m_i mod m_i is
+        // (M mod m_i^2) / m_i
+        let mut rems = vec![tree[tree.len() - 1][0].clone()];
+        for k in (0..tree.len() - 1).rev() {
+            rems = tree[k]
+                .iter()
+                .enumerate()
+                .map(|(i, m)| &rems[i / 2] % (m * m))
+                .collect();
+        }
+        let mut inverses = Vec::with_capacity(mi.len());
+        for (r, m) in rems.iter().zip(&tree[0]) {
+            inverses.push(mod_inv(&(r / m), m).ok_or(Error::NotInvertible)?);
+        }
+        Ok(CrtBasis { tree, inverses })
+    }
+
+    pub fn moduli(&self) -> &[BigInt] {
+        &self.tree[0]
+    }
+
+    pub fn product(&self) -> &BigInt {
+        &self.tree[self.tree.len() - 1][0]
+    }
+
+    // the x in [0, M) with x = x_i (mod m_i)
+    pub fn lift(&self, xi: &[BigInt]) -> Result<BigInt, Error> {
+        if xi.len() != self.inverses.len() {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut values: Vec<BigInt> = xi
+            .iter()
+            .zip(&self.inverses)
+            .zip(&self.tree[0])
+            .map(|((x, c), m)| (x * c).mod_floor(m))
+            .collect();
+        // a node over moduli A and B holds x_A B + x_B A
+        for k in 0..self.tree.len() - 1 {
+            values = values
+                .chunks(2)
+                .enumerate()
+                .map(|(i, pair)| match pair {
+                    [a, b] => a * &self.tree[k][2 * i + 1] + b * &self.tree[k][2 * i],
+                    _ => pair[0].clone(),
+                })
+                .collect();
+        }
+        Ok(values[0].mod_floor(self.product()))
+    }
+
+    // as lift, in (-M/2, M/2]
+    pub fn lift_symmetric(&self, xi: &[BigInt]) -> Result<BigInt, Error> {
+        let x = self.lift(xi)?;
+        let m = self.product();
+        Ok(if &x * 2u32 > *m { x - m } else { x })
+    }
+
+    // coefficient by coefficient, the i-th polynomial taken modulo m_i
+    pub fn lift_poly(
+        &self,
+        polys: &[Poly<BigInt>],
+        symmetric: bool,
+    ) -> Result<Poly<BigInt>, Error> {
+        if polys.len() != self.inverses.len() {
+            return Err(Error::DimensionMismatch);
+        }
+        let len = polys.iter().map(|f| f.coeffs().len()).max().unwrap_or(0);
+        let coeffs = (0..len)
+            .map(|j| {
+                let xi: Vec<BigInt> = polys.iter().map(|f| f.coeff(j)).collect();
+                self.lift_entry(&xi, symmetric)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Poly::new(coeffs))
+    }
+
+    // entry by entry, the i-th matrix taken modulo m_i
+    pub fn lift_matrix(
+        &self,
+        mats: &[Matrix<BigInt>],
+        symmetric: bool,
+    ) -> Result<Matrix<BigInt>, Error> {
+        if mats.len() != self.inverses.len() {
+            return Err(Error::DimensionMismatch);
+        }
+        let (rows, cols) = mats[0].shape();
+        if mats.iter().any(|a| a.shape() != (rows, cols)) {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut data = Vec::with_capacity(rows * cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                let xi: Vec<BigInt> = mats.iter().map(|a| a.row(i)[j].clone()).collect();
+                data.push(self.lift_entry(&xi, symmetric)?);
+            }
+        }
+        Matrix::from_vec(rows, cols, data)
+    }
+
+    fn lift_entry(&self, xi: &[BigInt], symmetric: bool) -> Result<BigInt, Error> {
+        if symmetric {
+            self.lift_symmetric(xi)
+        } else {
+            self.lift(xi)
+        }
+    }
+}
+
+// x in [0, prod m_i) with x = x_i (mod m_i), m_i pairwise coprime, through
+// the subproduct tree
+pub fn chinese_remainder_tree(mi: &[BigInt], xi: &[BigInt]) -> Result<BigInt, Error> {
+    CrtBasis::new(mi)?.lift(xi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(v: &[i64]) -> Vec<BigInt> {
+        v.iter().map(|&x| BigInt::from(x)).collect()
+    }
+
+    #[test]
+    fn general_crt_matches_exhaustive_search() {
+        for m1 in 1..13i64 {
+            for m2 in 1..13i64 {
+                let l = m1 * m2 / m1.gcd(&m2);
+                for x1 in 0..m1 {
+                    for x2 in 0..m2 {
+                        let found = (0..l).find(|x| x % m1 == x1 && x % m2 == x2);
+                        let res = chinese_remainder_general(&big(&[m1, m2]), &big(&[x1, x2]));
+                        match found {
+                            Some(x) => assert_eq!(res, Ok((BigInt::from(x), BigInt::from(l)))),
+                            None => assert_eq!(res, Err(Error::Inconsistent)),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tree_lift_matches_exhaustive_search() {
+        let mi = big(&[3, 5, 7, 11, 13]);
+        let basis = CrtBasis::new(&mi).unwrap();
+        assert_eq!(basis.product(), &BigInt::from(15_015));
+        for x in (0..15_015i64).step_by(97) {
+            let xi: Vec<BigInt> = mi.iter().map(|m| BigInt::from(x) % m).collect();
+            assert_eq!(basis.lift(&xi), Ok(BigInt::from(x)));
+            assert_eq!(chinese_remainder_tree(&mi, &xi), Ok(BigInt::from(x)));
+            let s = if 2 * x > 15_015 { x - 15_015 } else { x };
+            assert_eq!(basis.lift_symmetric(&xi), Ok(BigInt::from(s)));
+        }
+        assert_eq!(
+            CrtBasis::new(&big(&[4, 6])).err(),
+            Some(Error::NotInvertible)
+        );
+    }
+
+    #[test]
+    fn polys_and_matrices_lift_entrywise() {
+        let mi = big(&[7, 11, 13]);
+        let basis = CrtBasis::new(&mi).unwrap();
+        let f = Poly::new(big(&[-5, 0, 400]));
+        let reduce = |x: &BigInt, m: &BigInt| x.mod_floor(m);
+        let polys: Vec<Poly<BigInt>> = mi.iter().map(|m| f.map(|c| reduce(c, m))).collect();
+        assert_eq!(basis.lift_poly(&polys, true), Ok(f));
+
+        let a = Matrix::from_rows(vec![big(&[1, -2]), big(&[300, -400])]).unwrap();
+        let mats: Vec<Matrix<BigInt>> = mi.iter().map(|m| a.map(|c| reduce(c, m))).collect();
+        assert_eq!(basis.lift_matrix(&mats, true), Ok(a));
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::arith::{int_sqrt, mod_inv};
+use crate::chinese_remainder_inductive;
+use crate::factor::factor;
+use crate::group::GroupElement;
+use crate::power::power;
+
+// subgroups of prime order up to this use baby-step giant-step, larger ones rho
+const BSGS_LIMIT: u64 = 1 << 36;
+
+fn key<G: Hash>(x: &G) -> u64 {
+    let mut s = DefaultHasher::new();
+    x.hash(&mut s);
+    s.finish()
+}
+
+fn pow<G: GroupElement>(g: &G, e: &BigInt) -> G {
+    power(g.clone(), e)
+}
+
+// g and h each carried into the representation of the other: elements such
+// as the Fp constants adopt a modulus only from another operand, and must do
+// so before they are hashed
+fn normalise<G: GroupElement>(g: &G, h: &G) -> (G, G) {
+    let g = g.mul(&h.identity_of());
+    let h = h.mul(&g.identity_of());
+    (g, h)
+}
+
+// x in [0, n) with g^x = h, n any bound on the order of g
+pub fn dlog_bsgs<G>(g: &G, h: &G, n: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    let (g, h) = &normalise(g, h);
+    let mut m = int_sqrt(n);
+    if &m * &m < *n {
+        m += 1u32;
+    }
+    let m_usize = m.to_usize()?;
+
+    let mut baby: HashMap<G, usize> = HashMap::with_capacity(m_usize);
+    let mut e = g.identity_of();
+    for j in 0..m_usize {
+        baby.entry(e.clone()).or_insert(j);
+        e = e.mul(g);
+    }
+    // e = g^m
+    let giant = e.inverse();
+    let mut y = h.clone();
+    for i in 0..m_usize {
+        if let Some(&j) = baby.get(&y) {
+            return Some(&m * i + j);
+        }
+        y = y.mul(&giant);
+    }
+    None
+}
+
+// Pollard rho with the three-set partition, n the order of g; a collision
+// g^a1 h^b1 = g^a2 h^b2 leaves x (b1 - b2) = a2 - a1 (mod n)
+pub fn dlog_pollard_rho<G>(g: &G, h: &G, n: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    let (g, h) = &normalise(g, h);
+    if h == &G::identity() {
+        return Some(BigInt::zero());
+    }
+    let step = |x: &G, a: &BigInt, b: &BigInt| -> (G, BigInt, BigInt) {
+        match key(x) % 3 {
+            0 => (x.mul(g), (a + 1u32) % n, b.clone()),
+            1 => (x.mul(x), (a * 2u32) % n, (b * 2u32) % n),
+            _ => (x.mul(h), a.clone(), (b + 1u32) % n),
+        }
+    };
+
+    for attempt in 1u32..=20 {
+        // Floyd's cycle finding from g^a0 h^b0
+        let a0 = BigInt::from(attempt) * 7919u32 % n;
+        let b0 = BigInt::from(attempt) % n;
+        let x0 = pow(g, &a0).mul(&pow(h, &b0));
+        let (mut x1, mut a1, mut b1) = step(&x0, &a0, &b0);
+        let (mut x2, mut a2, mut b2) = step(&x1, &a1, &b1);
+        while x1 != x2 {
+            (x1, a1, b1) = step(&x1, &a1, &b1);
+            let (y, c, d) = step(&x2, &a2, &b2);
+            (x2, a2, b2) = step(&y, &c, &d);
+        }
+
+        let r = (&b1 - &b2).mod_floor(n);
+        let s = (&a2 - &a1).mod_floor(n);
+        let d = r.gcd(n);
+        if r.is_zero() || !s.is_multiple_of(&d) || d > BigInt::from(1u32 << 16) {
+            continue;
+        }
+        // d candidate solutions modulo n
+        let nd = n / &d;
+        let mut x = (&s / &d) * mod_inv(&(&r / &d), &nd)? % &nd;
+        while &x < n {
+            if &pow(g, &x) == h {
+                return Some(x);
+            }
+            x += &nd;
+        }
+    }
+    None
+}
+
+// Pollard lambda (kangaroos) for x in [a, b] with g^x = h
+pub fn dlog_pollard_lambda<G>(g: &G, h: &G, a: &BigInt, b: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    let (g, h) = &normalise(g, h);
+    if a > b {
+        return None;
+    }
+    let w = b - a;
+    if w < BigInt::from(16) {
+        let mut x = a.clone();
+        let mut y = pow(g, a);
+        while &x <= b {
+            if &y == h {
+                return Some(x);
+            }
+            y = y.mul(g);
+            x += 1u32;
+        }
+        return None;
+    }
+
+    // jumps 2^i, i < k, with mean (2^k - 1) / k close to sqrt(w) / 2
+    let root = int_sqrt(&w);
+    let k = (root.bits() as usize).max(2);
+    let tame_jumps = root.to_usize()?.saturating_mul(2);
+
+    for attempt in 0..8usize {
+        let jumps: Vec<BigInt> = (0..k)
+            .map(|i| BigInt::one() << ((i + attempt) % k))
+            .collect();
+        let powers: Vec<G> = jumps.iter().map(|s| pow(g, s)).collect();
+        let jump = |y: &G| ((key(y) as usize).wrapping_add(attempt)) % k;
+
+        // the tame kangaroo starts at g^b and sets a trap where it stops
+        let mut tame = pow(g, b);
+        let mut dt = BigInt::zero();
+        for _ in 0..tame_jumps {
+            let i = jump(&tame);
+            tame = tame.mul(&powers[i]);
+            dt += &jumps[i];
+        }
+
+        // the wild one starts at h = g^x and either falls into the trap or
+        // passes it
+        let mut wild = h.clone();
+        let mut dw = BigInt::zero();
+        let limit = &w + &dt;
+        while dw <= limit {
+            if wild == tame {
+                let x = b + &dt - &dw;
+                if &x >= a && &x <= b && &pow(g, &x) == h {
+                    return Some(x);
+                }
+                break;
+            }
+            let i = jump(&wild);
+            wild = wild.mul(&powers[i]);
+            dw += &jumps[i];
+        }
+    }
+    None
+}
+
+// Pohlig-Hellman: g^n = 1 and n = prod p^e; reduces to prime order subgroups,
+// solves for x mod p^f, p^f the p-part of the order of g, one base-p digit at
+// a time and recombines by CRT
+pub fn dlog_pohlig_hellman<G>(g: &G, h: &G, n: &BigInt, factors: &[(BigInt, u32)]) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    let (g, h) = &normalise(g, h);
+    let mut moduli = Vec::new();
+    let mut residues = Vec::new();
+    for (p, e) in factors {
+        let pe = p.pow(*e);
+        let cofactor = n / &pe;
+        let gi = pow(g, &cofactor);
+        let hi = pow(h, &cofactor);
+
+        // gi has order p^f for some f <= e, and gamma order p
+        let mut f = 0;
+        let mut t = gi.clone();
+        while t != G::identity() {
+            t = pow(&t, p);
+            f += 1;
+        }
+        if f == 0 {
+            if hi != G::identity() {
+                return None;
+            }
+            continue;
+        }
+        let gamma = pow(&gi, &p.pow(f - 1));
+        let gi_inv = gi.inverse();
+
+        let mut x = BigInt::zero();
+        let mut pk = BigInt::one();
+        for k in 0..f {
+            let hk = pow(&pow(&gi_inv, &x).mul(&hi), &p.pow(f - 1 - k));
+            let d = if p <= &BigInt::from(BSGS_LIMIT) {
+                dlog_bsgs(&gamma, &hk, p)?
+            } else {
+                dlog_pollard_rho(&gamma, &hk, p)?
+            };
+            x += d * &pk;
+            pk *= p;
+        }
+        moduli.push(p.pow(f));
+        residues.push(x);
+    }
+    if moduli.is_empty() {
+        return if h == &G::identity() {
+            Some(BigInt::zero())
+        } else {
+            None
+        };
+    }
+    let x = chinese_remainder_inductive(&moduli, &residues).ok()?;
+    if &pow(g, &x) == h {
+        Some(x.mod_floor(n))
+    } else {
+        None
+    }
+}
+
+// as above, factoring n itself
+pub fn discrete_log<G>(g: &G, h: &G, n: &BigInt) -> Option<BigInt>
+where
+    G: GroupElement + Hash + Eq,
+{
+    dlog_pohlig_hellman(g, h, n, &factor(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fp::{Fp, FpContext};
+
+    // 2039 = 2 * 1019 + 1, so 4 generates the subgroup of prime order 1019
+    // and 7 the whole group of order 2038
+    const P: u32 = 2039;
+
+    #[test]
+    fn logs_reexponentiate_to_h() {
+        let ctx = FpContext::new(&BigInt::from(P));
+        let q = BigInt::from(1019);
+        let n = BigInt::from(P - 1);
+        let g = ctx.elem(&BigInt::from(7));
+        let g4 = ctx.elem(&BigInt::from(4));
+        for x in (0..P - 1).step_by(37) {
+            let h = pow(&g, &BigInt::from(x));
+            let h4 = pow(&g4, &BigInt::from(x));
+
+            let y = dlog_bsgs(&g, &h, &n).unwrap();
+            assert!(y < n && pow(&g, &y) == h);
+            let y = discrete_log(&g, &h, &n).unwrap();
+            assert!(y < n && pow(&g, &y) == h);
+            let y = dlog_pollard_rho(&g4, &h4, &q).unwrap();
+            assert!(y < q && pow(&g4, &y) == h4);
+            let (a, b) = (BigInt::from(x.saturating_sub(100)), BigInt::from(x + 200));
+            let y = dlog_pollard_lambda(&g, &h, &a, &b).unwrap();
+            assert!(y >= a && y <= b && pow(&g, &y) == h);
+        }
+    }
+
+    #[test]
+    fn no_log_outside_the_subgroup() {
+        let ctx = FpContext::new(&BigInt::from(P));
+        let g4 = ctx.elem(&BigInt::from(4));
+        // 7 is not a square, so not a power of 4
+        let h = ctx.elem(&BigInt::from(7));
+        assert_eq!(dlog_bsgs(&g4, &h, &BigInt::from(1019)), None);
+        assert_eq!(discrete_log(&g4, &h, &BigInt::from(P - 1)), None);
+    }
+
+    #[test]
+    fn constants_are_carried_into_the_field() {
+        let ctx = FpContext::new(&BigInt::from(P));
+        let g = ctx.elem(&BigInt::from(P - 1));
+        let n = BigInt::from(2);
+        assert_eq!(dlog_bsgs(&g, &Fp::from(-1), &n), Some(BigInt::one()));
+        assert_eq!(dlog_bsgs(&g, &Fp::one(), &n), Some(BigInt::zero()));
+        assert_eq!(discrete_log(&g, &Fp::from(-1), &n), Some(BigInt::one()));
+    }
+}
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+use crate::fp::{Fp, FpContext};
+use crate::ring::Field;
+use crate::sieve::primes_up_to;
+
+// stage 2 baby steps are the j < D/2 coprime to D
+const D: u64 = 210;
+
+// projective x-coordinate (X : Z) on B y^2 = x^3 + A x^2 + x
+#[derive(Clone)]
+struct XPoint {
+    x: Fp,
+    z: Fp,
+}
+
+struct Curve {
+    // (A + 2) / 4
+    a24: Fp,
+}
+
+impl Curve {
+    fn double(&self, p: &XPoint) -> XPoint {
+        let s = &p.x + &p.z;
+        let d = &p.x - &p.z;
+        let s2 = &s * &s;
+        let d2 = &d * &d;
+        let t = &s2 - &d2;
+        XPoint {
+            x: &s2 * &d2,
+            z: &t * &(&d2 + &(&self.a24 * &t)),
+        }
+    }
+
+    // p + q given diff = p - q
+    fn add(&self, p: &XPoint, q: &XPoint, diff: &XPoint) -> XPoint {
+        let u = &(&p.x - &p.z) * &(&q.x + &q.z);
+        let v = &(&p.x + &p.z) * &(&q.x - &q.z);
+        let s = &u + &v;
+        let d = &u - &v;
+        XPoint {
+            x: &diff.z * &(&s * &s),
+            z: &diff.x * &(&d * &d),
+        }
+    }
+
+    // Montgomery ladder, k >= 1
+    fn mul(&self, k: u64, p: &XPoint) -> XPoint {
+        let mut r0 = p.clone();
+        let mut r1 = self.double(p);
+        for i in (0..63 - k.leading_zeros()).rev() {
+            if (k >> i) & 1 == 1 {
+                r0 = self.add(&r1, &r0, p);
+                r1 = self.double(&r1);
+            } else {
+                r1 = self.add(&r1, &r0, p);
+                r0 = self.double(&r0);
+            }
+        }
+        r0
+    }
+}
+
+enum Setup {
+    Curve(Curve, XPoint),
+    Factor(BigInt),
+    Degenerate,
+}
+
+// Suyama's parametrization, the group order is divisible by 12
+fn suyama(sigma: u64, ctx: &Arc<FpContext>) -> Setup {
+    let n = ctx.modulus();
+    let sigma = ctx.elem(&BigInt::from(sigma));
+    let u = &(&sigma * &sigma) - &ctx.elem(&BigInt::from(5));
+    let v = &ctx.elem(&BigInt::from(4)) * &sigma;
+    let u3 = &(&u * &u) * &u;
+    let vmu = &v - &u;
+    let num = &(&(&vmu * &vmu) * &vmu) * &(&(&ctx.elem(&BigInt::from(3)) * &u) + &v);
+    let den = &(&ctx.elem(&BigInt::from(16)) * &u3) * &v;
+
+    // a failed inversion modulo n is as good as a factor
+    match den.inv() {
+        Some(inv) => Setup::Curve(
+            Curve { a24: &num * &inv },
+            XPoint {
+                x: u3,
+                z: &(&v * &v) * &v,
+            },
+        ),
+        None => {
+            let g = ctx.residue(&den).gcd(n);
+            if g.is_zero() || g == *n {
+                Setup::Degenerate
+            } else {
+                Setup::Factor(g)
+            }
+        }
+    }
+}
+
+// Lenstra's elliptic curve method on Montgomery curves, for |n| composite;
+// None for |n| <= 3
+pub fn ecm(n: &BigInt, b1: u64, b2: u64, curves: usize) -> Option<BigInt> {
+    let n = &n.abs();
+    if *n <= BigInt::from(3) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(BigInt::from(2));
+    }
+    let ctx = FpContext::new(n);
+    let primes = primes_up_to(b1);
+    let split = |g: BigInt| if g.is_one() || g == *n { None } else { Some(g) };
+
+    for i in 0..curves {
+        let (curve, mut q) = match suyama(6 + i as u64, &ctx) {
+            Setup::Curve(c, p) => (c, p),
+            Setup::Factor(g) => return Some(g),
+            Setup::Degenerate => continue,
+        };
+
+        // stage 1: multiply by every prime power up to B1
+        for &p in &primes {
+            let mut pe = p;
+            while pe <= b1 / p {
+                pe *= p;
+            }
+            q = curve.mul(pe, &q);
+        }
+        let g = ctx.residue(&q.z).gcd(n);
+        if g == *n {
+            continue;
+        }
+        if let Some(g) = split(g) {
+            return Some(g);
+        }
+        if b2 <= b1 {
+            continue;
+        }
+
+        // stage 2: a prime s = mD +- j in (B1, B2] kills Q exactly when
+        // x([mD]Q) = x([j]Q), so accumulate the cross differences
+        let q2 = curve.double(&q);
+        let mut baby = vec![q.clone()];
+        let mut prev = q.clone();
+        let mut cur = curve.add(&q2, &q, &q);
+        let mut j = 3;
+        while j < D / 2 {
+            if j.gcd(&D) == 1 {
+                baby.push(cur.clone());
+            }
+            let next = curve.add(&cur, &q2, &prev);
+            prev = cur;
+            cur = next;
+            j += 2;
+        }
+
+        let step = curve.mul(D, &q);
+        let m0 = (b1 / D).max(1);
+        let mut r_prev = curve.mul(m0 * D, &q);
+        let mut r = curve.mul((m0 + 1) * D, &q);
+        let mut acc = ctx.elem(&BigInt::one());
+        for s in &baby {
+            acc = &acc * &(&(&r_prev.x * &s.z) - &(&s.x * &r_prev.z));
+        }
+        let mut m = m0 + 1;
+        while m * D < b2 + D {
+            for s in &baby {
+                acc = &acc * &(&(&r.x * &s.z) - &(&s.x * &r.z));
+            }
+            let next = curve.add(&r, &step, &r_prev);
+            r_prev = r;
+            r = next;
+            m += 1;
+        }
+        if let Some(g) = split(ctx.residue(&acc).gcd(n)) {
+            return Some(g);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_factors_of_15_to_20_digits() {
+        for (p, q) in [
+            (100_000_000_000_031u64, 10_000_000_000_000_000_051u64),
+            (3_141_592_653_589_793_239, 2_718_281_828_459_045_269),
+        ] {
+            let n = BigInt::from(p) * q;
+            let d = ecm(&n, 11_000, 1_100_000, 200).unwrap();
+            assert!(!d.is_one() && d != n && (&n % &d).is_zero(), "{}", d);
+        }
+    }
+
+    #[test]
+    fn small_and_negative_inputs() {
+        for n in [-1i64, 0, 1, 2, 3] {
+            assert_eq!(ecm(&BigInt::from(n), 100, 1_000, 5), None, "n = {}", n);
+        }
+        assert_eq!(
+            ecm(&BigInt::from(-20), 100, 1_000, 5),
+            Some(BigInt::from(2))
+        );
+        let d = ecm(&BigInt::from(-15), 100, 1_000, 20).unwrap();
+        assert!(d == BigInt::from(3) || d == BigInt::from(5));
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    // operands have incompatible shapes or lengths
+    DimensionMismatch,
+    // a matrix, element or leading coefficient has no inverse
+    NotInvertible,
+    NotPrime,
+    // a floating point computation lost too much accuracy to be trusted
+    PrecisionLoss,
+    // an iterative method did not reach the requested tolerance
+    NoConvergence,
+    // the curve is singular or the model does not have the expected shape
+    InvalidCurve,
+    // congruences or equations that contradict each other
+    Inconsistent,
+    InvalidArgument(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DimensionMismatch => write!(f, "dimension mismatch"),
+            Error::NotInvertible => write!(f, "not invertible"),
+            Error::NotPrime => write!(f, "modulus is not prime"),
+            Error::PrecisionLoss => write!(f, "loss of precision"),
+            Error::NoConvergence => write!(f, "iteration did not converge"),
+            Error::InvalidCurve => write!(f, "invalid elliptic curve"),
+            Error::Inconsistent => write!(f, "inconsistent system"),
+            Error::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+
+use num_bigint::BigInt;
+use num_integer::{Integer, Roots};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::ecm::ecm;
+use crate::primality::is_probable_prime;
+use crate::sieve::primes_up_to;
+use crate::siqs::siqs;
+
+const TRIAL_BOUND: u64 = 1 << 12;
+const P_MINUS_1_BOUND: u64 = 20_000;
+const RHO_STEPS: u64 = 1 << 16;
+
+// SIQS beats ECM once the factors ECM is still looking for pass a third of
+// the digits of the number
+const SIQS_MAX_DIGITS: usize = 90;
+
+// (expected factor digits, B1, curves)
+const ECM_SCHEDULE: [(usize, u64, usize); 6] = [
+    (15, 2_000, 25),
+    (20, 11_000, 90),
+    (25, 50_000, 300),
+    (30, 250_000, 700),
+    (35, 1_000_000, 1_800),
+    (40, 3_000_000, 5_100),
+];
+
+// the factorization of |n| as sorted (prime, exponent) pairs, empty for 0 and 1
+pub fn factor(n: &BigInt) -> Vec<(BigInt, u32)> {
+    let mut res: BTreeMap<BigInt, u32> = BTreeMap::new();
+    let (small, rest) = trial_division(&n.abs(), TRIAL_BOUND);
+    for (p, e) in small {
+        res.insert(p, e);
+    }
+
+    // cofactors still to split, with the multiplicity they occur with
+    let mut stack = vec![(rest, 1u32)];
+    while let Some((m, e)) = stack.pop() {
+        if m <= BigInt::one() {
+            continue;
+        }
+        if is_probable_prime(&m) {
+            *res.entry(m).or_insert(0) += e;
+            continue;
+        }
+        if let Some((r, k)) = perfect_power(&m) {
+            stack.push((r, e * k));
+            continue;
+        }
+        let d = find_factor(&m);
+        let q = &m / &d;
+        stack.push((d, e));
+        stack.push((q, e));
+    }
+    res.into_iter().collect()
+}
+
+// a nontrivial factor of the composite, non perfect power m
+fn find_factor(m: &BigInt) -> BigInt {
+    if let Some(d) = squfof(m) {
+        return d;
+    }
+    if let Some(d) = pollard_p_minus_1(m, P_MINUS_1_BOUND) {
+        return d;
+    }
+    // rho is cheapest for factors below about 10^9
+    if let Some(d) = brent(m, 1, RHO_STEPS) {
+        return d;
+    }
+    let digits = m.to_string().len();
+    let mut sieved = digits > SIQS_MAX_DIGITS;
+    for &(size, b1, curves) in ECM_SCHEDULE.iter() {
+        if !sieved && 3 * size > digits {
+            sieved = true;
+            if let Some(d) = siqs(m) {
+                return d;
+            }
+        }
+        if let Some(d) = ecm(m, b1, 100 * b1, curves) {
+            return d;
+        }
+    }
+    if !sieved {
+        if let Some(d) = siqs(m) {
+            return d;
+        }
+    }
+    let mut c = 1u64;
+    loop {
+        if let Some(d) = pollard_rho_brent(m, c) {
+            return d;
+        }
+        c += 1;
+    }
+}
+
+// removes all prime factors below bound, returning them and the cofactor
+pub fn trial_division(n: &BigInt, bound: u64) -> (Vec<(BigInt, u32)>, BigInt) {
+    let mut res = Vec::new();
+    let mut m = n.clone();
+    if m.is_zero() {
+        return (res, m);
+    }
+    let mut d = 2u64;
+    while d < bound && BigInt::from(d) * d <= m {
+        let mut e = 0;
+        while (&m % d).is_zero() {
+            m /= d;
+            e += 1;
+        }
+        if e > 0 {
+            res.push((BigInt::from(d), e));
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+    // what is left is prime when no factor below its square root exists
+    if m > BigInt::one() && m < BigInt::from(d) * d {
+        res.push((m, 1));
+        m = BigInt::one();
+    }
+    (res, m)
+}
+
+// m = r^k with k > 1 maximal
+fn perfect_power(m: &BigInt) -> Option<(BigInt, u32)> {
+    for k in (2..m.bits() as u32).rev() {
+        let r = m.nth_root(k);
+        if r > BigInt::one() && r.pow(k) == *m {
+            return Some((r, k));
+        }
+    }
+    None
+}
+
+// Brent's variant of Pollard rho with f(x) = x^2 + c, n composite; None for
+// n <= 3
+pub fn pollard_rho_brent(n: &BigInt, c: u64) -> Option<BigInt> {
+    brent(n, c, u64::MAX)
+}
+
+// gives up once the cycle search passes max_steps iterations
+fn brent(n: &BigInt, c: u64, max_steps: u64) -> Option<BigInt> {
+    if *n <= BigInt::from(3) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(BigInt::from(2));
+    }
+    let c = BigInt::from(c);
+    let f = |x: &BigInt| (x * x + &c) % n;
+    let block = 128u64;
+
+    let mut y = BigInt::from(2);
+    let mut x = y.clone();
+    let mut ys = y.clone();
+    let mut q = BigInt::one();
+    let mut g = BigInt::one();
+    let mut r = 1u64;
+    while g.is_one() {
+        if r > max_steps {
+            return None;
+        }
+        x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g.is_one() {
+            ys = y.clone();
+            for _ in 0..block.min(r - k) {
+                y = f(&y);
+                q = (q * (&x - &y).abs()) % n;
+            }
+            g = q.gcd(n);
+            k += block;
+        }
+        r *= 2;
+    }
+
+    // the batched product overshot, step back through the last block one at a time
+    if g == *n {
+        loop {
+            ys = f(&ys);
+            g = (&x - &ys).abs().gcd(n);
+            if !g.is_one() {
+                break;
+            }
+        }
+    }
+    if g == *n {
+        None
+    } else {
+        Some(g)
+    }
+}
+
+// stage 1 of Pollard p-1: finds p | n when p - 1 is bound-powersmooth
+pub fn pollard_p_minus_1(n: &BigInt, bound: u64) -> Option<BigInt> {
+    let primes = primes_up_to(bound);
+    let mut a = BigInt::from(2);
+    let checkpoint = 64;
+
+    for chunk in primes.chunks(checkpoint) {
+        let saved = a.clone();
+        for &p in chunk {
+            a = a.modpow(&BigInt::from(prime_power_below(p, bound)), n);
+        }
+        let g = (&a - 1u32).gcd(n);
+        if g.is_one() {
+            continue;
+        }
+        if g != *n {
+            return Some(g);
+        }
+        // every factor appeared in this chunk, redo it one prime at a time
+        a = saved;
+        for &p in chunk {
+            a = a.modpow(&BigInt::from(prime_power_below(p, bound)), n);
+            let g = (&a - 1u32).gcd(n);
+            if !g.is_one() {
+                return if g == *n { None } else { Some(g) };
+            }
+        }
+    }
+    None
+}
+
+fn prime_power_below(p: u64, bound: u64) -> u64 {
+    let mut q = p;
+    while q <= bound / p {
+        q *= p;
+    }
+    q
+}
+
+// Shanks' square forms factorization, only for n < 2^62
+pub fn squfof(n: &BigInt) -> Option<BigInt> {
+    let n = n.to_u64().filter(|&n| n < 1 << 62 && n > 1)?;
+    if n % 2 == 0 {
+        return Some(BigInt::from(2));
+    }
+    let r = n.sqrt();
+    if r * r == n {
+        return Some(BigInt::from(r));
+    }
+
+    const MULTIPLIERS: [i128; 16] = [
+        1, 3, 5, 7, 11, 15, 21, 33, 35, 55, 77, 105, 165, 231, 385, 1155,
+    ];
+    let n = n as i128;
+    for &k in MULTIPLIERS.iter() {
+        let kn = k * n;
+        let p0 = kn.sqrt();
+        let mut q_prev = 1i128;
+        let mut p = p0;
+        let mut q = kn - p0 * p0;
+        if q == 0 {
+            continue;
+        }
+        let limit = 2 * (2 * n.sqrt()).sqrt() * 3;
+
+        // forward cycle until a square form appears at an even step
+        let mut root = 0;
+        let mut found = false;
+        for i in 2..limit {
+            let b = (p0 + p) / q;
+            let p_next = b * q - p;
+            let q_next = q_prev + b * (p - p_next);
+            q_prev = q;
+            q = q_next;
+            p = p_next;
+            root = q.sqrt();
+            if i % 2 == 0 && root * root == q {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            continue;
+        }
+
+        // reverse cycle on the square root form until P repeats
+        let b = (p0 - p) / root;
+        p += b * root;
+        q_prev = root;
+        q = (kn - p * p) / q_prev;
+        for _ in 0..limit {
+            let b = (p0 + p) / q;
+            let p_next = b * q - p;
+            let q_next = q_prev + b * (p - p_next);
+            q_prev = q;
+            q = q_next;
+            if p_next == p {
+                break;
+            }
+            p = p_next;
+        }
+        let g = n.gcd(&q_prev);
+        if g != 1 && g != n {
+            return Some(BigInt::from(g));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factor_brute(mut n: u64) -> Vec<(BigInt, u32)> {
+        let mut res = Vec::new();
+        let mut d = 2;
+        while d * d <= n {
+            let mut e = 0;
+            while n.is_multiple_of(d) {
+                n /= d;
+                e += 1;
+            }
+            if e > 0 {
+                res.push((BigInt::from(d), e));
+            }
+            d += 1;
+        }
+        if n > 1 {
+            res.push((BigInt::from(n), 1));
+        }
+        res
+    }
+
+    fn product(f: &[(BigInt, u32)]) -> BigInt {
+        f.iter().map(|(p, e)| p.pow(*e)).product()
+    }
+
+    #[test]
+    fn factor_matches_trial_division() {
+        for n in 1..5_000u64 {
+            assert_eq!(factor(&BigInt::from(n)), factor_brute(n), "{}", n);
+        }
+        assert_eq!(factor(&BigInt::from(-360)), factor_brute(360));
+        assert!(factor(&BigInt::zero()).is_empty());
+    }
+
+    #[test]
+    fn factor_splits_pseudoprimes_and_prime_powers() {
+        for n in [2047u64, 3_215_031_751, 3_825_123_056_546_413_051] {
+            let f = factor(&BigInt::from(n));
+            assert_eq!(f, factor_brute(n));
+        }
+        // 2^67 - 1 = 193707721 * 761838257287
+        let m67 = (BigInt::one() << 67u32) - 1u32;
+        assert_eq!(
+            factor(&m67),
+            vec![
+                (BigInt::from(193_707_721u64), 1),
+                (BigInt::from(761_838_257_287u64), 1)
+            ]
+        );
+        let power = BigInt::from(1_000_003u64).pow(3) * BigInt::from(999_983u64).pow(2);
+        let f = factor(&power);
+        assert_eq!(
+            f,
+            vec![
+                (BigInt::from(999_983u64), 2),
+                (BigInt::from(1_000_003u64), 3)
+            ]
+        );
+        assert_eq!(product(&f), power);
+    }
+
+    #[test]
+    fn single_methods_find_proper_factors() {
+        let n = BigInt::from(1_000_003u64 * 999_983);
+        for g in [
+            pollard_rho_brent(&n, 1),
+            squfof(&n),
+            pollard_p_minus_1(&BigInt::from(2_047u64 * 1_000_003), 100),
+        ] {
+            let g = g.expect("a factor");
+            assert!(g > BigInt::one());
+        }
+        assert!((&n % pollard_rho_brent(&n, 1).unwrap()).is_zero());
+        assert!((&n % squfof(&n).unwrap()).is_zero());
+        let (small, rest) = trial_division(&BigInt::from(2u64 * 2 * 3 * 1_000_003), 100);
+        assert_eq!(small, vec![(BigInt::from(2), 2), (BigInt::from(3), 1)]);
+        assert_eq!(rest, BigInt::from(1_000_003));
+    }
+
+    #[test]
+    fn rho_gives_none_below_4() {
+        for n in [-4i64, 0, 1, 2, 3] {
+            assert_eq!(pollard_rho_brent(&BigInt::from(n), 1), None, "n = {}", n);
+        }
+        assert_eq!(
+            pollard_rho_brent(&BigInt::from(4), 1),
+            Some(BigInt::from(2))
+        );
+    }
+}
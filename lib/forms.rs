@@ -0,0 +1,248 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+use crate::arith::divisors_factored;
+use crate::error::Error;
+use crate::factor::factor;
+use crate::sqrt_mod::sqrt_mod_factored;
+
+// a 2 x 2 integer matrix [[m0, m1], [m2, m3]] of determinant 1, acting on
+// forms by (f M)(x, y) = f(m0 x + m1 y, m2 x + m3 y)
+type Sl2 = [BigInt; 4];
+
+fn sl2_identity() -> Sl2 {
+    [BigInt::one(), BigInt::zero(), BigInt::zero(), BigInt::one()]
+}
+
+fn sl2_mul(m: &Sl2, n: &Sl2) -> Sl2 {
+    [
+        &m[0] * &n[0] + &m[1] * &n[2],
+        &m[0] * &n[1] + &m[1] * &n[3],
+        &m[2] * &n[0] + &m[3] * &n[2],
+        &m[2] * &n[1] + &m[3] * &n[3],
+    ]
+}
+
+fn sl2_inverse(m: &Sl2) -> Sl2 {
+    [m[3].clone(), -&m[1], -&m[2], m[0].clone()]
+}
+
+fn sl2(m: [i64; 4]) -> Sl2 {
+    m.map(BigInt::from)
+}
+
+// the reduced form (a, b, c), |b| <= a <= c with b >= 0 when |b| = a or
+// a = c, properly equivalent to the positive definite (a, b, c), and the M
+// taking the one to the other
+fn reduce(mut a: BigInt, mut b: BigInt, mut c: BigInt) -> ((BigInt, BigInt, BigInt), Sl2) {
+    let mut m = sl2_identity();
+    loop {
+        // (a, b, c) [[1, k], [0, 1]] = (a, b + 2ak, ak^2 + bk + c)
+        if b > a || b <= -&a {
+            let k = (&a - &b).div_floor(&(2 * &a));
+            c = &a * &k * &k + &b * &k + &c;
+            b += 2 * &a * &k;
+            m = sl2_mul(&m, &[BigInt::one(), k, BigInt::zero(), BigInt::one()]);
+        }
+        // (a, b, c) [[0, -1], [1, 0]] = (c, -b, a)
+        if a > c || (a == c && b.is_negative()) {
+            std::mem::swap(&mut a, &mut c);
+            b = -b;
+            m = sl2_mul(&m, &sl2([0, -1, 1, 0]));
+        } else {
+            return ((a, b, c), m);
+        }
+    }
+}
+
+// the proper automorphisms of a reduced form: +-1, together with the powers
+// of a rotation of order 4 for (a, 0, a) and of order 6 for (a, a, a)
+fn automorphisms(a: &BigInt, b: &BigInt, c: &BigInt) -> Vec<Sl2> {
+    let rotation = if b.is_zero() && a == c {
+        Some(sl2([0, -1, 1, 0]))
+    } else if a == b && b == c {
+        Some(sl2([0, -1, 1, 1]))
+    } else {
+        None
+    };
+    let mut result = vec![sl2_identity(), sl2([-1, 0, 0, -1])];
+    if let Some(r) = rotation {
+        let order = if b.is_zero() { 4 } else { 6 };
+        let mut g = r.clone();
+        while result.len() < order {
+            result.push(g.clone());
+            result.push(sl2_mul(&g, &sl2([-1, 0, 0, -1])));
+            g = sl2_mul(&g, &r);
+        }
+    }
+    result
+}
+
+// the primitive representations of n > 0 by (a, b, c): each is the first
+// column of a T with (a, b, c) T = (n, B, C), B^2 = D (mod 4n) with
+// 0 <= B < 2n fixed by the representation, and the T for one B are the A T0
+// for the automorphisms A of (a, b, c); so the form (n, B, C) is reduced and
+// compared with the reduction of (a, b, c) for every square root B
+fn primitive_representations(
+    (a, b, c): (&BigInt, &BigInt, &BigInt),
+    n: &BigInt,
+    nf: &[(BigInt, u32)],
+) -> Result<Vec<(BigInt, BigInt)>, Error> {
+    let d = b * b - 4 * a * c;
+    let (reduced, to_reduced) = reduce(a.clone(), b.clone(), c.clone());
+    let (ra, rb, rc) = &reduced;
+    let autos: Vec<Sl2> = automorphisms(ra, rb, rc)
+        .iter()
+        .map(|u| sl2_mul(&sl2_mul(&to_reduced, u), &sl2_inverse(&to_reduced)))
+        .collect();
+
+    // the factorization of 4n
+    let mut f4: Vec<(BigInt, u32)> = nf.to_vec();
+    match f4.iter_mut().find(|(p, _)| *p == BigInt::from(2)) {
+        Some((_, e)) => *e += 2,
+        None => f4.push((BigInt::from(2), 2)),
+    }
+
+    let two_n = 2 * n;
+    let mut reps = Vec::new();
+    for big_b in sqrt_mod_factored(&d, &f4)? {
+        if big_b >= two_n {
+            continue;
+        }
+        let big_c = (&big_b * &big_b - &d) / (4 * n);
+        let (g, m) = reduce(n.clone(), big_b, big_c);
+        if g != reduced {
+            continue;
+        }
+        // (a, b, c) to_reduced = g = (n, B, C) m, so T = to_reduced m^-1
+        let t = sl2_mul(&to_reduced, &sl2_inverse(&m));
+        for u in &autos {
+            let v = sl2_mul(u, &t);
+            reps.push((v[0].clone(), v[2].clone()));
+        }
+    }
+    Ok(reps)
+}
+
+// all (x, y) with a x^2 + b x y + c y^2 = m for a positive definite form,
+// primitive or not: the representations with gcd(x, y) = g are g times the
+// primitive ones of m / g^2
+pub fn form_representations(
+    a: &BigInt,
+    b: &BigInt,
+    c: &BigInt,
+    m: &BigInt,
+) -> Result<Vec<(BigInt, BigInt)>, Error> {
+    let d: BigInt = b * b - 4 * a * c;
+    if !a.is_positive() || !d.is_negative() {
+        return Err(Error::InvalidArgument("form must be positive definite"));
+    }
+    if m.is_negative() {
+        return Ok(Vec::new());
+    }
+    if m.is_zero() {
+        return Ok(vec![(BigInt::zero(), BigInt::zero())]);
+    }
+
+    let mf = factor(m);
+    let halves: Vec<(BigInt, u32)> = mf.iter().map(|(p, e)| (p.clone(), e / 2)).collect();
+    let mut reps = Vec::new();
+    for g in divisors_factored(&halves) {
+        let g2 = &g * &g;
+        let n = m / &g2;
+        let nf: Vec<(BigInt, u32)> = mf
+            .iter()
+            .map(|(p, e)| {
+                let mut e = *e;
+                let mut h = g2.clone();
+                while h.is_multiple_of(p) {
+                    h /= p;
+                    e -= 1;
+                }
+                (p.clone(), e)
+            })
+            .filter(|(_, e)| *e > 0)
+            .collect();
+        for (x, y) in primitive_representations((a, b, c), &n, &nf)? {
+            reps.push((&g * x, &g * y));
+        }
+    }
+    reps.sort();
+    reps.dedup();
+    Ok(reps)
+}
+
+// all (x, y) with x^2 + d y^2 = m, d > 0
+pub fn representations_x2_dy2(d: &BigInt, m: &BigInt) -> Result<Vec<(BigInt, BigInt)>, Error> {
+    if !d.is_positive() {
+        return Err(Error::InvalidArgument("d must be positive"));
+    }
+    form_representations(&BigInt::one(), &BigInt::zero(), d, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // |x| and |y| are bounded by sqrt(4 c m / |D|) and sqrt(4 a m / |D|)
+    fn brute(a: i64, b: i64, c: i64, m: i64) -> Vec<(BigInt, BigInt)> {
+        let d = 4 * a * c - b * b;
+        let bound = |k: i64| (0..).take_while(|t| t * t * d <= 4 * k * m).last().unwrap();
+        let (xb, yb) = (bound(c), bound(a));
+        let mut reps = Vec::new();
+        for x in -xb..=xb {
+            for y in -yb..=yb {
+                if a * x * x + b * x * y + c * y * y == m {
+                    reps.push((BigInt::from(x), BigInt::from(y)));
+                }
+            }
+        }
+        reps
+    }
+
+    #[test]
+    fn form_representations_match_enumeration() {
+        let forms = [
+            (1i64, 0i64, 1i64),
+            (1, 1, 1),
+            (1, 0, 5),
+            (2, 2, 3),
+            (2, 1, 3),
+            (3, 2, 5),
+            (4, -3, 5),
+            (2, 0, 2),
+            (5, 8, 7),
+        ];
+        for &(a, b, c) in &forms {
+            for m in 0..200i64 {
+                let got = form_representations(
+                    &BigInt::from(a),
+                    &BigInt::from(b),
+                    &BigInt::from(c),
+                    &BigInt::from(m),
+                )
+                .unwrap();
+                assert_eq!(got, brute(a, b, c, m), "({}, {}, {}) = {}", a, b, c, m);
+            }
+        }
+    }
+
+    #[test]
+    fn x2_dy2_matches_enumeration() {
+        for d in 1..12i64 {
+            for m in [0i64, 1, 9, 25, 49, 100, 221, 1_105, 4_225] {
+                let got = representations_x2_dy2(&BigInt::from(d), &BigInt::from(m)).unwrap();
+                assert_eq!(got, brute(1, 0, d, m), "x^2 + {} y^2 = {}", d, m);
+            }
+        }
+        assert!(representations_x2_dy2(&BigInt::zero(), &BigInt::one()).is_err());
+        assert!(form_representations(
+            &BigInt::one(),
+            &BigInt::from(3),
+            &BigInt::one(),
+            &BigInt::one()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,659 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::sync::Arc;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::error::Error;
+use crate::group::GroupElement;
+use crate::ring::{EuclideanDomain, Field, Ring};
+
+// a power of a constant without a modulus is computed exactly only up to
+// this many bits
+const CONSTANT_POWER_BITS: u64 = 1 << 16;
+
+#[derive(Debug)]
+pub struct FpContext {
+    p: BigUint,
+    p_int: BigInt,
+    // Montgomery parameters, R = 2^r_bits (odd moduli only)
+    montgomery: bool,
+    r_bits: u64,
+    r_mask: BigUint,
+    n_prime: BigUint,
+    r2: BigUint,
+    one: BigUint,
+    // Barrett parameters, mu = floor(2^(2k) / p)
+    k: u64,
+    mu: BigUint,
+}
+
+impl FpContext {
+    // panics for p < 2, see try_new
+    pub fn new(p: &BigInt) -> Arc<FpContext> {
+        FpContext::try_new(p).expect("modulus must be at least 2")
+    }
+
+    pub fn try_new(p: &BigInt) -> Result<Arc<FpContext>, Error> {
+        if p <= &BigInt::one() {
+            return Err(Error::InvalidArgument("modulus must be at least 2"));
+        }
+        let p_u = p.magnitude().clone();
+        let k = p_u.bits();
+        let mu = (BigUint::one() << (2 * k)) / &p_u;
+        let montgomery = p_u.is_odd();
+
+        let (r_bits, r_mask, n_prime, r2, one) = if montgomery {
+            let r_bits = k.div_ceil(64) * 64;
+            let r = BigUint::one() << r_bits;
+            let r_mask = &r - BigUint::one();
+            // p^-1 mod R by Newton iteration, each step doubles the correct bits
+            let mut inv = BigUint::one();
+            let mut bits = 1u64;
+            while bits < r_bits {
+                let t = (&p_u * &inv) & &r_mask;
+                inv = (&inv * ((BigUint::from(2u32) + &r - t) & &r_mask)) & &r_mask;
+                bits *= 2;
+            }
+            let n_prime = (&r - inv) & &r_mask;
+            let one = &r % &p_u;
+            let r2 = (&one * &one) % &p_u;
+            (r_bits, r_mask, n_prime, r2, one)
+        } else {
+            (
+                0,
+                BigUint::zero(),
+                BigUint::zero(),
+                BigUint::one(),
+                BigUint::one(),
+            )
+        };
+
+        Ok(Arc::new(FpContext {
+            p: p_u,
+            p_int: p.clone(),
+            montgomery,
+            r_bits,
+            r_mask,
+            n_prime,
+            r2,
+            one,
+            k,
+            mu,
+        }))
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.p_int
+    }
+
+    pub fn elem(self: &Arc<Self>, x: &BigInt) -> Fp {
+        Fp::new(x, self)
+    }
+
+    // x carried into this field, as a residue in [0, p)
+    pub fn residue(self: &Arc<Self>, x: &Fp) -> BigInt {
+        BigInt::from(self.decode(&x.lifted(self)))
+    }
+
+    // Euler's criterion for x carried into this field, p an odd prime
+    pub fn legendre(self: &Arc<Self>, x: &Fp) -> i32 {
+        let x = Fp {
+            repr: Repr::Mod(x.lifted(self), self.clone()),
+        };
+        if x.is_zero() {
+            return 0;
+        }
+        let e = (self.modulus() - BigInt::one()) >> 1;
+        if x.pow(&e).is_one() {
+            1
+        } else {
+            -1
+        }
+    }
+
+    // x mod p for 0 <= x < p^2
+    fn barrett(&self, x: &BigUint) -> BigUint {
+        if x.bits() > 2 * self.k {
+            return x % &self.p;
+        }
+        let q = ((x >> (self.k - 1)) * &self.mu) >> (self.k + 1);
+        let mut r = x - q * &self.p;
+        while r >= self.p {
+            r -= &self.p;
+        }
+        r
+    }
+
+    // t R^-1 mod p for 0 <= t < p R
+    fn redc(&self, t: &BigUint) -> BigUint {
+        let m = ((t & &self.r_mask) * &self.n_prime) & &self.r_mask;
+        let u = (t + m * &self.p) >> self.r_bits;
+        if u >= self.p {
+            u - &self.p
+        } else {
+            u
+        }
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let t = a * b;
+        if self.montgomery {
+            self.redc(&t)
+        } else {
+            self.barrett(&t)
+        }
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let s = a + b;
+        if s >= self.p {
+            s - &self.p
+        } else {
+            s
+        }
+    }
+
+    fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        if a >= b {
+            a - b
+        } else {
+            a + &self.p - b
+        }
+    }
+
+    fn reduce(&self, x: &BigInt) -> BigUint {
+        let r = self.barrett(x.magnitude());
+        if x.sign() == Sign::Minus && !r.is_zero() {
+            &self.p - r
+        } else {
+            r
+        }
+    }
+
+    fn encode(&self, x: &BigUint) -> BigUint {
+        if self.montgomery {
+            self.redc(&(x * &self.r2))
+        } else {
+            x.clone()
+        }
+    }
+
+    fn decode(&self, x: &BigUint) -> BigUint {
+        if self.montgomery {
+            self.redc(x)
+        } else {
+            x.clone()
+        }
+    }
+}
+
+// Constants (zero, one, 1 + 1, 1 / 2, ...) carry no modulus and are kept
+// as exact rationals; they adopt the context of the other operand as soon
+// as they meet a reduced element, a denominator being inverted there.
+#[derive(Clone, Debug)]
+enum Repr {
+    Int(BigRational),
+    Mod(BigUint, Arc<FpContext>),
+}
+
+// An element of Z/pZ, or a constant such as Fp::from(-1) not yet tied to a
+// modulus. Equality is exact and never panics: reduced elements are equal
+// when their moduli and residues are, and only 0 and 1, which name the same
+// residue in every field, are equal to reduced elements as constants. So
+// Fp::from(-1) != ctx.elem(&(-1).into()); carry a constant into a context
+// first, by ctx.elem or by multiplying with identity_of, to compare or hash
+// it as a residue.
+#[derive(Clone, Debug)]
+pub struct Fp {
+    repr: Repr,
+}
+
+impl Fp {
+    pub fn new(x: &BigInt, ctx: &Arc<FpContext>) -> Fp {
+        let v = ctx.encode(&ctx.reduce(x));
+        Fp {
+            repr: Repr::Mod(v, ctx.clone()),
+        }
+    }
+
+    pub fn context(&self) -> Option<&Arc<FpContext>> {
+        match &self.repr {
+            Repr::Int(_) => None,
+            Repr::Mod(_, c) => Some(c),
+        }
+    }
+
+    // the residue in [0, p), or the value of an integer constant; None for a
+    // fractional constant, which has no value until it meets a modulus
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        match &self.repr {
+            Repr::Int(x) => x.is_integer().then(|| x.to_integer()),
+            Repr::Mod(v, c) => Some(BigInt::from(c.decode(v))),
+        }
+    }
+
+    pub fn pow(&self, e: &BigInt) -> Fp {
+        let base = if e.sign() == Sign::Minus {
+            self.inv()
+                .expect("negative power of a non-invertible element")
+        } else {
+            self.clone()
+        };
+        let e = e.magnitude();
+        match &base.repr {
+            Repr::Int(x) => Fp {
+                repr: Repr::Int(constant_pow(x, e)),
+            },
+            Repr::Mod(v, c) => {
+                let mut result = c.one.clone();
+                for i in (0..e.bits()).rev() {
+                    result = c.mul(&result, &result);
+                    if e.bit(i) {
+                        result = c.mul(&result, v);
+                    }
+                }
+                Fp {
+                    repr: Repr::Mod(result, c.clone()),
+                }
+            }
+        }
+    }
+
+    // the value in the context c, None for a constant whose denominator is
+    // not invertible there
+    fn try_lifted(&self, c: &Arc<FpContext>) -> Option<BigUint> {
+        match &self.repr {
+            Repr::Int(x) => {
+                let mut v = c.reduce(x.numer());
+                if !x.denom().is_one() {
+                    let den = BigInt::from(c.reduce(x.denom()));
+                    let inv = crate::arith::mod_inv(&den, c.modulus())?;
+                    v = (v * inv.magnitude()) % &c.p;
+                }
+                Some(c.encode(&v))
+            }
+            Repr::Mod(v, d) => {
+                assert!(
+                    Arc::ptr_eq(c, d) || c.p == d.p,
+                    "mixing elements of different fields"
+                );
+                Some(v.clone())
+            }
+        }
+    }
+
+    fn lifted(&self, c: &Arc<FpContext>) -> BigUint {
+        self.try_lifted(c)
+            .expect("constant with a denominator divisible by the modulus")
+    }
+
+    fn binop(
+        &self,
+        other: &Fp,
+        int_op: fn(&BigRational, &BigRational) -> BigRational,
+        mod_op: fn(&FpContext, &BigUint, &BigUint) -> BigUint,
+    ) -> Fp {
+        let c = match (&self.repr, &other.repr) {
+            (Repr::Int(x), Repr::Int(y)) => {
+                return Fp {
+                    repr: Repr::Int(int_op(x, y)),
+                }
+            }
+            (Repr::Mod(_, c), _) | (_, Repr::Mod(_, c)) => c,
+        };
+        let v = mod_op(c, &self.lifted(c), &other.lifted(c));
+        Fp {
+            repr: Repr::Mod(v, c.clone()),
+        }
+    }
+}
+
+// x^e for a constant: 0, 1 and -1 to any power, anything else only while
+// the result stays below CONSTANT_POWER_BITS, as the exponent cannot be
+// reduced before a modulus is known
+fn constant_pow(x: &BigRational, e: &BigUint) -> BigRational {
+    if e.is_zero() {
+        return BigRational::one();
+    }
+    if x.is_zero() || x.abs().is_one() {
+        return if x.is_negative() && e.is_even() {
+            -x
+        } else {
+            x.clone()
+        };
+    }
+    let size = x.numer().bits().max(x.denom().bits());
+    let e = e
+        .to_u64()
+        .filter(|e| e.saturating_mul(size) <= CONSTANT_POWER_BITS)
+        .expect("power of a constant too large without a modulus");
+    num_traits::pow::Pow::pow(x, e)
+}
+
+fn is_zero_or_one(x: &BigRational) -> bool {
+    x.is_zero() || x.is_one()
+}
+
+impl PartialEq for Fp {
+    fn eq(&self, other: &Fp) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::Int(x), Repr::Int(y)) => x == y,
+            (Repr::Mod(v, c), Repr::Mod(w, d)) => (Arc::ptr_eq(c, d) || c.p == d.p) && v == w,
+            (Repr::Mod(v, c), Repr::Int(x)) | (Repr::Int(x), Repr::Mod(v, c)) => {
+                (x.is_zero() && v.is_zero()) || (x.is_one() && *v == c.one)
+            }
+        }
+    }
+}
+
+impl Eq for Fp {}
+
+// reduced values hash their residue, and so do the constants 0 and 1 which
+// equal them; any other constant equals only itself
+impl Hash for Fp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.repr {
+            Repr::Int(x) if is_zero_or_one(x) => x.to_integer().hash(state),
+            Repr::Int(x) => x.hash(state),
+            Repr::Mod(v, c) => BigInt::from(c.decode(v)).hash(state),
+        }
+    }
+}
+
+impl From<i64> for Fp {
+    fn from(x: i64) -> Self {
+        Fp {
+            repr: Repr::Int(BigRational::from_integer(BigInt::from(x))),
+        }
+    }
+}
+
+impl fmt::Display for Fp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Int(x) => write!(f, "{}", x),
+            Repr::Mod(v, c) => write!(f, "{}", c.decode(v)),
+        }
+    }
+}
+
+impl<'a> Add<&'a Fp> for &'a Fp {
+    type Output = Fp;
+
+    fn add(self, other: &'a Fp) -> Fp {
+        self.binop(other, |x, y| x + y, FpContext::add)
+    }
+}
+
+impl<'a> Sub<&'a Fp> for &'a Fp {
+    type Output = Fp;
+
+    fn sub(self, other: &'a Fp) -> Fp {
+        self.binop(other, |x, y| x - y, FpContext::sub)
+    }
+}
+
+impl<'a> Mul<&'a Fp> for &'a Fp {
+    type Output = Fp;
+
+    fn mul(self, other: &'a Fp) -> Fp {
+        self.binop(other, |x, y| x * y, FpContext::mul)
+    }
+}
+
+impl<'a> Div<&'a Fp> for &'a Fp {
+    type Output = Fp;
+
+    fn div(self, other: &'a Fp) -> Fp {
+        let inv = match (&self.repr, &other.repr) {
+            (Repr::Mod(_, c), Repr::Int(_)) => {
+                let lifted = Fp {
+                    repr: Repr::Mod(other.lifted(c), c.clone()),
+                };
+                lifted.inv()
+            }
+            _ => other.inv(),
+        };
+        let inv = inv.expect("division by a non-invertible element");
+        self.binop(&inv, |x, y| x * y, FpContext::mul)
+    }
+}
+
+impl Neg for &Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Fp {
+        match &self.repr {
+            Repr::Int(x) => Fp {
+                repr: Repr::Int(-x),
+            },
+            Repr::Mod(v, c) => Fp {
+                repr: Repr::Mod(c.sub(&BigUint::zero(), v), c.clone()),
+            },
+        }
+    }
+}
+
+impl Add for Fp {
+    type Output = Fp;
+
+    fn add(self, other: Fp) -> Fp {
+        &self + &other
+    }
+}
+
+impl Sub for Fp {
+    type Output = Fp;
+
+    fn sub(self, other: Fp) -> Fp {
+        &self - &other
+    }
+}
+
+impl Mul for Fp {
+    type Output = Fp;
+
+    fn mul(self, other: Fp) -> Fp {
+        &self * &other
+    }
+}
+
+impl Div for Fp {
+    type Output = Fp;
+
+    fn div(self, other: Fp) -> Fp {
+        &self / &other
+    }
+}
+
+impl Neg for Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Fp {
+        -&self
+    }
+}
+
+impl Zero for Fp {
+    fn zero() -> Self {
+        Fp {
+            repr: Repr::Int(BigRational::zero()),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        match &self.repr {
+            Repr::Int(x) => x.is_zero(),
+            Repr::Mod(v, _) => v.is_zero(),
+        }
+    }
+}
+
+impl One for Fp {
+    fn one() -> Self {
+        Fp {
+            repr: Repr::Int(BigRational::one()),
+        }
+    }
+
+    fn is_one(&self) -> bool {
+        match &self.repr {
+            Repr::Int(x) => x.is_one(),
+            Repr::Mod(v, c) => *v == c.one,
+        }
+    }
+}
+
+impl Ring for Fp {}
+
+impl EuclideanDomain for Fp {
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        (self / other, Fp::zero())
+    }
+
+    fn normal_unit(&self) -> Self {
+        self.inv().unwrap_or_else(Fp::one)
+    }
+}
+
+impl Field for Fp {
+    fn inv(&self) -> Option<Self> {
+        match &self.repr {
+            Repr::Int(x) => {
+                if x.is_zero() {
+                    None
+                } else {
+                    Some(Fp {
+                        repr: Repr::Int(x.recip()),
+                    })
+                }
+            }
+            Repr::Mod(v, c) => {
+                let a = BigInt::from(c.decode(v));
+                let inv = crate::arith::mod_inv(&a, c.modulus())?;
+                Some(Fp::new(&inv, c))
+            }
+        }
+    }
+}
+
+impl GroupElement for Fp {
+    fn identity() -> Self {
+        Fp::one()
+    }
+
+    fn identity_of(&self) -> Self {
+        match &self.repr {
+            Repr::Int(_) => Fp::one(),
+            Repr::Mod(_, c) => Fp {
+                repr: Repr::Mod(c.one.clone(), c.clone()),
+            },
+        }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn inverse(&self) -> Self {
+        self.inv().expect("zero is not invertible")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash_of(x: &Fp) -> u64 {
+        let mut s = DefaultHasher::new();
+        x.hash(&mut s);
+        s.finish()
+    }
+
+    #[test]
+    fn arithmetic_matches_integers_mod_p() {
+        for p in [2i64, 3, 101, 65_537] {
+            let ctx = FpContext::new(&BigInt::from(p));
+            for a in [0i64, 1, 2, p - 1, 12_345] {
+                for b in [1i64, 3, p - 2, 777] {
+                    let (x, y) = (ctx.elem(&a.into()), ctx.elem(&b.into()));
+                    let r = |v: i64| BigInt::from(v.rem_euclid(p));
+                    assert_eq!(ctx.residue(&(&x + &y)), r(a + b));
+                    assert_eq!(ctx.residue(&(&x - &y)), r(a - b));
+                    assert_eq!(ctx.residue(&(&x * &y)), r(a * b));
+                    if !y.is_zero() {
+                        assert_eq!(&(&x / &y) * &y, x);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn constants_equal_residues_only_for_zero_and_one() {
+        let c5 = FpContext::new(&BigInt::from(5));
+        let c7 = FpContext::new(&BigInt::from(7));
+        assert_eq!(Fp::zero(), c5.elem(&BigInt::from(10)));
+        assert_eq!(Fp::one(), c7.elem(&BigInt::from(8)));
+        assert_ne!(Fp::from(2), c5.elem(&BigInt::from(2)));
+        assert_ne!(Fp::from(2), c7.elem(&BigInt::from(2)));
+        // carried into a field first, a constant takes its residue there
+        let two = &Fp::from(2) * &c5.elem(&BigInt::one());
+        assert_eq!(two, c5.elem(&BigInt::from(7)));
+        assert_eq!(c5.residue(&Fp::from(-1)), BigInt::from(4));
+        assert_eq!(c7.residue(&(Fp::one() / Fp::from(2))), BigInt::from(4));
+    }
+
+    #[test]
+    fn elements_of_different_fields_are_unequal() {
+        let c5 = FpContext::new(&BigInt::from(5));
+        let c7 = FpContext::new(&BigInt::from(7));
+        let other5 = FpContext::new(&BigInt::from(5));
+        for a in 0..10i64 {
+            let x = c5.elem(&BigInt::from(a));
+            assert_ne!(x, c7.elem(&BigInt::from(a)));
+            assert_eq!(x, other5.elem(&BigInt::from(a + 5)));
+            assert_eq!(hash_of(&x), hash_of(&other5.elem(&BigInt::from(a))));
+        }
+        assert_ne!(Fp::from(-1), c5.elem(&BigInt::from(-1)));
+        assert_eq!(
+            c5.elem(&Fp::from(-1).to_bigint().unwrap()),
+            c5.elem(&BigInt::from(4))
+        );
+    }
+
+    #[test]
+    fn hash_agrees_with_eq_and_never_panics() {
+        let ctx = FpContext::new(&BigInt::from(101));
+        assert_eq!(hash_of(&Fp::zero()), hash_of(&ctx.elem(&BigInt::zero())));
+        assert_eq!(hash_of(&Fp::one()), hash_of(&ctx.elem(&BigInt::from(102))));
+        assert_eq!(hash_of(&Fp::from(3)), hash_of(&(Fp::one() + Fp::from(2))));
+        hash_of(&(Fp::one() / Fp::from(3)));
+        assert_eq!(Fp::from(3).to_bigint(), Some(BigInt::from(3)));
+        assert_eq!((Fp::one() / Fp::from(3)).to_bigint(), None);
+    }
+
+    #[test]
+    fn legendre_matches_euler_by_brute_force() {
+        let p = 103i64;
+        let ctx = FpContext::new(&BigInt::from(p));
+        for a in 0..p {
+            let square = (1..p).any(|x| (x * x - a) % p == 0);
+            let expected = if a == 0 {
+                0
+            } else if square {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(ctx.legendre(&ctx.elem(&BigInt::from(a))), expected);
+        }
+        assert_eq!(ctx.legendre(&Fp::from(-1)), -1);
+    }
+}
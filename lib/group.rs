@@ -1,5 +1,16 @@
 pub trait GroupElement: Clone + PartialEq {
+    // set when inverting costs about as little as multiplying, as on elliptic
+    // curves, so that signed-digit recodings pay off
+    const CHEAP_INVERSE: bool = false;
+
     fn identity() -> Self;
+
+    // the identity in the representation of self, for elements that carry
+    // their group with them only some of the time
+    fn identity_of(&self) -> Self {
+        Self::identity()
+    }
+
     fn mul(&self, other: &Self) -> Self;
     fn inverse(&self) -> Self;
 }
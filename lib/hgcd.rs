@@ -0,0 +1,279 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+use crate::extended_euclid;
+
+// below this many bits the recursion stops and Euclid's steps are taken one
+// by one
+const HGCD_THRESHOLD: u64 = 256;
+
+// extended_gcd switches from Euclid to the half-gcd above this many bits
+const EXTENDED_GCD_THRESHOLD: u64 = 4_096;
+
+// a product of quotient matrices [[q, 1], [1, 0]], with its determinant
+#[derive(Clone)]
+struct Matrix2 {
+    m: [BigInt; 4],
+    det: i8,
+}
+
+impl Matrix2 {
+    fn identity() -> Matrix2 {
+        Matrix2 {
+            m: [BigInt::one(), BigInt::zero(), BigInt::zero(), BigInt::one()],
+            det: 1,
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.m[1].is_zero() && self.m[2].is_zero()
+    }
+
+    fn mul(&self, o: &Matrix2) -> Matrix2 {
+        let [a, b, c, d] = &self.m;
+        let [e, f, g, h] = &o.m;
+        Matrix2 {
+            m: [a * e + b * g, a * f + b * h, c * e + d * g, c * f + d * h],
+            det: self.det * o.det,
+        }
+    }
+
+    // M [[q, 1], [1, 0]]
+    fn push(&mut self, q: &BigInt) {
+        let [a, b, c, d] = std::mem::take(&mut self.m);
+        self.m = [q * &a + b, a, q * &c + d, c];
+        self.det = -self.det;
+    }
+
+    // M [[q, 1], [1, 0]]^-1 for the last quotient q, which is the smaller of
+    // the two column quotients since the columns of the matrix before it cannot
+    // both be equal
+    fn pop(&mut self) -> BigInt {
+        let [a, b, c, d] = std::mem::take(&mut self.m);
+        let q = if d.is_zero() {
+            &a / &b
+        } else {
+            (&a / &b).min(&c / &d)
+        };
+        self.m = [b.clone(), &a - &q * &b, d.clone(), &c - &q * &d];
+        self.det = -self.det;
+        q
+    }
+
+    // M^-1 (a, b)
+    fn solve(&self, a: &BigInt, b: &BigInt) -> (BigInt, BigInt) {
+        let [m00, m01, m10, m11] = &self.m;
+        let c = m11 * a - m01 * b;
+        let d = m00 * b - m10 * a;
+        if self.det > 0 {
+            (c, d)
+        } else {
+            (-c, -d)
+        }
+    }
+}
+
+// Euclid's steps on (c, d) until d < 2^s, recorded in m
+fn euclid_until(m: &mut Matrix2, mut c: BigInt, mut d: BigInt, s: u64) -> (BigInt, BigInt) {
+    while !d.is_zero() && d.bits() > s {
+        let (q, r) = c.div_rem(&d);
+        m.push(&q);
+        c = d;
+        d = r;
+    }
+    (c, d)
+}
+
+// (c, d) = M^-1 (a, b) is a pair of consecutive remainders of a and b, and M
+// the product of the quotients before it, exactly when c > d >= 0; quotients
+// from the truncated numbers that overshoot are undone, as are those taking
+// c below 2^s
+fn fix_up(m: &mut Matrix2, c: &mut BigInt, d: &mut BigInt, s: u64) {
+    while !m.is_identity() && (d.is_negative() || *d >= *c || c.bits() <= s) {
+        let q = m.pop();
+        let prev = &q * &*c + &*d;
+        *d = std::mem::replace(c, prev);
+    }
+}
+
+// a > b >= 0; M and (c, d) = M^-1 (a, b) consecutive remainders with
+// d < 2^s <= c, s = bits(a) / 2: the quotients of the top halves of a and b
+// are those of a and b themselves for about half their length, so two
+// recursive calls on numbers of half the size each take a quarter off
+fn hgcd(a: &BigInt, b: &BigInt) -> (Matrix2, BigInt, BigInt) {
+    let n = a.bits();
+    let s = n / 2;
+    let mut m = Matrix2::identity();
+    if b.bits() <= s {
+        return (m, a.clone(), b.clone());
+    }
+    if n < HGCD_THRESHOLD {
+        let (c, d) = euclid_until(&mut m, a.clone(), b.clone(), s);
+        return (m, c, d);
+    }
+
+    // the top n - s bits bring a and b down to about 3n/4 bits
+    let (a1, b1) = (a >> s, b >> s);
+    if a1 > b1 {
+        m = hgcd(&a1, &b1).0;
+    }
+    let (mut c, mut d) = m.solve(a, b);
+    fix_up(&mut m, &mut c, &mut d, s);
+    if d.bits() <= s {
+        return (m, c, d);
+    }
+
+    let (q, r) = c.div_rem(&d);
+    m.push(&q);
+    c = d;
+    d = r;
+    if d.bits() <= s {
+        return (m, c, d);
+    }
+
+    // and the top 2 (bits(c) - s) bits of c and d the rest of the way
+    let k = (2 * s).saturating_sub(c.bits());
+    let (c1, d1) = (&c >> k, &d >> k);
+    if c1 > d1 {
+        let m2 = hgcd(&c1, &d1).0;
+        if !m2.is_identity() {
+            let (mut c2, mut d2) = m2.solve(&c, &d);
+            let mut m2 = m2;
+            fix_up(&mut m2, &mut c2, &mut d2, s);
+            m = m.mul(&m2);
+            c = c2;
+            d = d2;
+        }
+    }
+    let (c, d) = euclid_until(&mut m, c, d, s);
+    (m, c, d)
+}
+
+// (u, v, d) with u a + v b = d = gcd(a, b) >= 0, by the Schonhage-Moller
+// half-gcd in O(M(n) log n)
+pub fn half_gcd_extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
+    let (sa, sb) = (a.signum(), b.signum());
+    let (mut x, mut y) = (a.abs(), b.abs());
+    let swapped = x < y;
+    if swapped {
+        std::mem::swap(&mut x, &mut y);
+    }
+
+    // (|a|, |b|) = M (x, y) throughout
+    let mut m = Matrix2::identity();
+    while !y.is_zero() {
+        if x.bits() >= HGCD_THRESHOLD {
+            let (m1, c, d) = hgcd(&x, &y);
+            m = m.mul(&m1);
+            x = c;
+            y = d;
+            if y.is_zero() {
+                break;
+            }
+        }
+        let (q, r) = x.div_rem(&y);
+        m.push(&q);
+        x = y;
+        y = r;
+    }
+
+    // (x, 0) = M^-1 (|a|, |b|) gives x = det (m11 |a| - m01 |b|)
+    let [_, m01, _, m11] = &m.m;
+    let (mut u, mut v) = if m.det > 0 {
+        (m11.clone(), -m01)
+    } else {
+        (-m11, m01.clone())
+    };
+    if swapped {
+        std::mem::swap(&mut u, &mut v);
+    }
+    if sa.is_negative() {
+        u = -u;
+    }
+    if sb.is_negative() {
+        v = -v;
+    }
+    (u, v, x)
+}
+
+// the extended gcd by Euclid for small inputs and by the half-gcd for large
+pub fn extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
+    if a.bits().max(b.bits()) >= EXTENDED_GCD_THRESHOLD {
+        half_gcd_extended_gcd(a, b)
+    } else {
+        extended_euclid(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // pseudo-random values of exactly the given number of bits
+    fn sample(bits: u64, state: &mut u64) -> BigInt {
+        let mut n = BigInt::one();
+        for _ in 1..bits {
+            *state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            n = (n << 1u32) + (*state >> 63);
+        }
+        n
+    }
+
+    fn check(a: &BigInt, b: &BigInt) {
+        let g = a.gcd(b);
+        for f in [half_gcd_extended_gcd, extended_gcd] {
+            let (u, v, d) = f(a.clone(), b.clone());
+            assert_eq!(d, g, "({}, {})", a, b);
+            assert_eq!(&u * a + &v * b, d, "({}, {})", a, b);
+        }
+    }
+
+    #[test]
+    fn bezout_around_the_thresholds() {
+        let mut state = 1u64;
+        let t = HGCD_THRESHOLD;
+        let e = EXTENDED_GCD_THRESHOLD;
+        for (x, y) in [
+            (t - 1, t - 1),
+            (t, t),
+            (t, t - 1),
+            (t + 1, 10),
+            (2 * t, t),
+            (e - 1, e - 1),
+            (e, e),
+            (e + 1, e - 200),
+            (e, 1),
+            (3 * e, e),
+        ] {
+            let a = sample(x, &mut state);
+            let b = sample(y, &mut state);
+            // a common factor, so that the gcd is not 1
+            let c = sample(t / 2, &mut state);
+            for (a, b) in [(a.clone(), b.clone()), (&a * &c, &b * &c)] {
+                check(&a, &b);
+                check(&-&a, &b);
+                check(&a, &-&b);
+                check(&-&a, &-&b);
+                check(&b, &a);
+                check(&a, &a);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_inputs() {
+        let mut state = 7u64;
+        let a = sample(5_000, &mut state);
+        for (x, y) in [
+            (BigInt::zero(), BigInt::zero()),
+            (a.clone(), BigInt::zero()),
+            (BigInt::zero(), -&a),
+            (BigInt::one(), a.clone()),
+        ] {
+            check(&x, &y);
+        }
+    }
+}
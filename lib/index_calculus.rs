@@ -0,0 +1,867 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use num_bigint::BigInt;
+use num_integer::{Integer, Roots};
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::arith::{int_sqrt, mod_inv};
+use crate::dlog::dlog_pohlig_hellman;
+use crate::error::Error;
+use crate::factor::factor;
+use crate::fp::FpContext;
+use crate::primality::is_probable_prime;
+use crate::sieve::primes_up_to;
+use crate::{chinese_remainder_inductive, primitive_root_mod_p};
+
+// prime powers of p - 1 below this are handled by Pohlig-Hellman, the rest by
+// the relations
+const SMALL_PRIME_BOUND: u64 = 1 << 32;
+
+// (bits of p, factor base bound, sieve width)
+const PARAMETERS: [(u64, u64, u64); 9] = [
+    (40, 200, 200),
+    (50, 400, 300),
+    (60, 700, 500),
+    (70, 1_200, 800),
+    (80, 2_000, 1_200),
+    (90, 4_000, 2_000),
+    (100, 7_000, 3_000),
+    (110, 14_000, 4_500),
+    (126, 60_000, 8_000),
+];
+
+// relations collected, as a percentage of the unknowns: the surplus lets the
+// structured elimination discard the heavy columns cheaply
+const RELATION_EXCESS: usize = 150;
+
+// sieve values within this many bits of the size of the value are factored
+const SIEVE_SLACK: u32 = 20;
+
+// the sparse elimination stops once a pivot would touch more entries than this
+const MARKOWITZ_LIMIT: usize = 300_000;
+
+// sieve roots for a prime dividing every value or none
+const ALWAYS: u64 = u64::MAX;
+const NEVER: u64 = u64::MAX - 1;
+
+// exponent increment between consecutive candidates g^k
+const STEP: u64 = 1_000_003;
+
+// a cofactor left over after trial division is descended on when it is a prime
+// below this
+const DESCENT_BOUND: u64 = 1 << 40;
+const DESCENT_DEPTH: u32 = 3;
+const DESCENT_TRIES: usize = 1 << 16;
+const LOG_TRIES: usize = 1 << 24;
+
+// entries in Montgomery form
+type SparseRow = Vec<(usize, u128)>;
+
+// logarithms to the base of the least primitive root g mod p of the primes
+// below the factor base bound, known modulo the part of p - 1 made of large
+// primes; built once and reused for any number of individual logs
+pub struct LogTable {
+    p: BigInt,
+    g: BigInt,
+    // large part of p - 1 and its cofactor's factorization
+    large: BigInt,
+    small: Vec<(BigInt, u32)>,
+    primes: Vec<u64>,
+    // odd q with q^-1 mod 2^64 and floor((2^64 - 1) / q): q | a exactly when
+    // a q^-1 mod 2^64 is at most the latter, and then it is a / q
+    divisors: Vec<(u64, u64, u64)>,
+    logs: HashMap<u64, BigInt>,
+}
+
+impl LogTable {
+    // p an odd prime below 2^126, so that the half Euclidean steps fit in i128
+    pub fn new(p: &BigInt) -> Result<LogTable, Error> {
+        if p.bits() > 126 || *p < BigInt::from(3) {
+            return Err(Error::InvalidArgument("p must be an odd prime below 2^126"));
+        }
+        if !is_probable_prime(p) {
+            return Err(Error::NotPrime);
+        }
+        let n = p - 1u32;
+        let factors = factor(&n);
+        let g = primitive_root_mod_p(p, &factors)?;
+
+        let mut large = BigInt::one();
+        let mut small = Vec::new();
+        for (q, e) in factors {
+            if q < BigInt::from(SMALL_PRIME_BOUND) {
+                small.push((q, e));
+            } else {
+                large *= q.pow(e);
+            }
+        }
+
+        let bits = p.bits();
+        let (_, bound, width) = PARAMETERS
+            .iter()
+            .copied()
+            .find(|&(b, _, _)| bits <= b)
+            .unwrap_or(PARAMETERS[PARAMETERS.len() - 1]);
+        let primes = primes_up_to(bound);
+        let divisors = primes[1..]
+            .iter()
+            .map(|&q| {
+                let mut inv = q;
+                for _ in 0..5 {
+                    inv = inv.wrapping_mul(2u64.wrapping_sub(q.wrapping_mul(inv)));
+                }
+                (q, inv, u64::MAX / q)
+            })
+            .collect();
+
+        let mut table = LogTable {
+            p: p.clone(),
+            g,
+            large,
+            small,
+            primes,
+            divisors,
+            logs: HashMap::new(),
+        };
+        if !table.large.is_one() {
+            table.sieve_logs(width)?;
+        }
+        Ok(table)
+    }
+
+    pub fn base(&self) -> &BigInt {
+        &self.g
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.p
+    }
+
+    // x in [0, p - 1) with g^x = h (mod p)
+    pub fn log(&self, h: &BigInt) -> Result<BigInt, Error> {
+        let p = &self.p;
+        let n = p - 1u32;
+        let h = h.mod_floor(p);
+        if h.is_zero() {
+            return Err(Error::NotInvertible);
+        }
+
+        let ctx = FpContext::new(p);
+        let mut moduli = Vec::new();
+        let mut residues = Vec::new();
+        for (q, e) in &self.small {
+            let qe = q.pow(*e);
+            let cofactor = &n / &qe;
+            let gi = ctx.elem(&self.g.modpow(&cofactor, p));
+            let hi = ctx.elem(&h.modpow(&cofactor, p));
+            let x = dlog_pohlig_hellman(&gi, &hi, &qe, &[(q.clone(), *e)])
+                .ok_or(Error::NoConvergence)?;
+            moduli.push(qe);
+            residues.push(x);
+        }
+        if !self.large.is_one() {
+            moduli.push(self.large.clone());
+            residues.push(
+                self.descend(&h, DESCENT_DEPTH, LOG_TRIES)
+                    .ok_or(Error::NoConvergence)?,
+            );
+        }
+
+        let x = chinese_remainder_inductive(&moduli, &residues)?.mod_floor(&n);
+        if self.g.modpow(&x, p) == h {
+            Ok(x)
+        } else {
+            Err(Error::NoConvergence)
+        }
+    }
+
+    // log h modulo the large part: h g^k = a / b with a, b about sqrt(p) is
+    // factored over the factor base, and a prime cofactor below DESCENT_BOUND
+    // is itself descended on
+    fn descend(&self, h: &BigInt, depth: u32, tries: usize) -> Option<BigInt> {
+        let p = &self.p;
+        let n = p - 1u32;
+        let half = (&n / 2u32) % &self.large;
+        let step = self.g.modpow(&BigInt::from(STEP), p);
+        let mut y = h * &self.g % p;
+        let mut k = BigInt::one();
+        for _ in 0..tries {
+            if let Some((a, b, negative)) = self.reconstruct(&y) {
+                let log = self
+                    .known_log(a, depth)
+                    .and_then(|la| Some(la - self.known_log(b, depth)?));
+                if let Some(mut log) = log {
+                    if negative {
+                        log += &half;
+                    }
+                    return Some((log - &k).mod_floor(&self.large));
+                }
+            }
+            y = y * &step % p;
+            k += STEP;
+        }
+        None
+    }
+
+    // log a modulo the large part from the table, descending on a prime
+    // cofactor and on factor base primes the relations missed
+    fn known_log(&self, a: u64, depth: u32) -> Option<BigInt> {
+        let (exps, cofactor) = self.split(a);
+        let mut log = BigInt::zero();
+        for (q, e) in exps {
+            match self.logs.get(&q) {
+                Some(x) => log += x * e,
+                None if depth > 0 => {
+                    log += self.descend(&BigInt::from(q), depth - 1, DESCENT_TRIES)? * e
+                }
+                None => return None,
+            }
+        }
+        if cofactor > 1 {
+            if depth == 0 || cofactor > DESCENT_BOUND || !is_probable_prime(&BigInt::from(cofactor))
+            {
+                return None;
+            }
+            log += self.descend(&BigInt::from(cofactor), depth - 1, DESCENT_TRIES)?;
+        }
+        Some(log)
+    }
+
+    // trial division of a over the factor base, with what is left over
+    fn split(&self, mut a: u64) -> (Vec<(u64, u32)>, u64) {
+        let mut exps = Vec::new();
+        let e = a.trailing_zeros();
+        if e > 0 {
+            exps.push((2, e));
+            a >>= e;
+        }
+        for &(q, inv, max) in &self.divisors {
+            if q * q > a {
+                break;
+            }
+            let mut e = 0;
+            while a.wrapping_mul(inv) <= max {
+                a = a.wrapping_mul(inv);
+                e += 1;
+            }
+            if e > 0 {
+                exps.push((q, e));
+            }
+        }
+        if a > 1 && a <= *self.primes.last().unwrap_or(&1) {
+            exps.push((a, 1));
+            a = 1;
+        }
+        (exps, a)
+    }
+
+    // y = a / b (mod p) with |a|, |b| <= sqrt(p), from the extended Euclidean
+    // algorithm stopped halfway; the flag records a negative b
+    fn reconstruct(&self, y: &BigInt) -> Option<(u64, u64, bool)> {
+        let p = self.p.to_u128()?;
+        let root = p.sqrt();
+        let (mut r0, mut r1) = (p, y.to_u128()?);
+        let (mut t0, mut t1) = (0i128, 1i128);
+        while r1 > root {
+            let q = r0 / r1;
+            (r0, r1) = (r1, r0 - q * r1);
+            (t0, t1) = (t1, t0 - q as i128 * t1);
+        }
+        if r1 == 0 {
+            return None;
+        }
+        Some((r1.to_u64()?, t1.unsigned_abs().to_u64()?, t1 < 0))
+    }
+
+    // the linear sieve: with H = ceil(sqrt(p)) and J = H^2 - p, the values
+    // (H + c1)(H + c2) - p = J + c1 H + c2 (H + c1), 0 <= c1 <= c2 < width,
+    // are about width sqrt(p) and linear in c2, so the primes dividing them
+    // are sieved out; each that factors over the base gives
+    // sum e log q = log(H + c1) + log(H + c2), the log(H + c) being further
+    // unknowns, and log g = 1, over the factorization of g when it is
+    // composite, fixes the scale
+    fn sieve_logs(&mut self, width: u64) -> Result<(), Error> {
+        let p = self.p.clone();
+        let n = &p - 1u32;
+        let m = self.large.clone();
+        let nb = self.primes.len();
+        let ring = Montgomery::new(&m);
+        let g_row = self
+            .g
+            .to_u64()
+            .map(|g| self.split(g))
+            .filter(|&(_, cofactor)| cofactor == 1)
+            .and_then(|(exps, _)| {
+                exps.into_iter()
+                    .map(|(q, e)| {
+                        let i = self.primes.binary_search(&q).ok()?;
+                        Some((i, ring.encode(e as u64)))
+                    })
+                    .collect::<Option<SparseRow>>()
+            })
+            .ok_or(Error::InvalidArgument("g does not factor over the base"))?;
+        let (Some(p128), Some(h)) = (p.to_u128(), (int_sqrt(&p) + 1u32).to_u128()) else {
+            return Err(Error::InvalidArgument("p must be an odd prime below 2^126"));
+        };
+        let j = h * h - p128;
+
+        // a width too small for the relations to outnumber the unknowns is
+        // doubled
+        let mut width = width;
+        let rows = loop {
+            let rows = self.relations(&g_row, h, j, width, &ring);
+            if rows.len() > nb + width as usize {
+                break rows;
+            }
+            width *= 2;
+        };
+        let solution = solve_sparse(rows, nb + width as usize, &ring);
+        let cofactor = &n / &m;
+        let gm = self.g.modpow(&cofactor, &p);
+        for (i, x) in solution.into_iter().take(nb).enumerate() {
+            if let Some(x) = x {
+                let q = self.primes[i];
+                let x = ring.decode(x);
+                if gm.modpow(&x, &p) == BigInt::from(q).modpow(&cofactor, &p) {
+                    self.logs.insert(q, x);
+                }
+            }
+        }
+        if self.logs.is_empty() {
+            return Err(Error::NoConvergence);
+        }
+        Ok(())
+    }
+
+    // the relations for 0 <= c1 <= c2 < width, stopping once there are
+    // RELATION_EXCESS percent of the unknowns
+    fn relations(
+        &self,
+        g_row: &SparseRow,
+        h: u128,
+        j: u128,
+        width: u64,
+        ring: &Montgomery,
+    ) -> Vec<(SparseRow, u128)> {
+        let nb = self.primes.len();
+        let one = ring.encode(1);
+
+        // H and J modulo each q, and log2 q rounded for the sieve
+        let h_mod: Vec<u64> = self
+            .primes
+            .iter()
+            .map(|&q| (h % q as u128) as u64)
+            .collect();
+        let j_mod: Vec<u64> = self
+            .primes
+            .iter()
+            .map(|&q| (j % q as u128) as u64)
+            .collect();
+        let log_q: Vec<u8> = self
+            .primes
+            .iter()
+            .map(|&q| (q as f64).log2().round() as u8)
+            .collect();
+
+        let wanted = (nb + width as usize) * RELATION_EXCESS / 100;
+        let mut rows: Vec<(SparseRow, u128)> = Vec::with_capacity(wanted + 1);
+        rows.push((g_row.clone(), one));
+        let mut roots = vec![0u64; nb];
+        let mut sieve = Vec::with_capacity(width as usize);
+        for c1 in 0..width {
+            if rows.len() > wanted {
+                break;
+            }
+            let a = j + c1 as u128 * h;
+            let d = h + c1 as u128;
+            let len = (width - c1) as usize;
+            sieve.clear();
+            sieve.resize(len, 0u8);
+
+            // q | a + c2 d for c2 = -a / d (mod q), for every c2 when q | a, d
+            for (i, &q) in self.primes.iter().enumerate() {
+                let dq = (h_mod[i] + c1 % q) % q;
+                let aq = (j_mod[i] + (c1 % q) * h_mod[i]) % q;
+                let (root, step) = if dq != 0 {
+                    ((q - aq) % q * inv_mod(dq, q) % q, q as usize)
+                } else if aq == 0 {
+                    (ALWAYS, 1)
+                } else {
+                    (NEVER, 0)
+                };
+                roots[i] = root;
+                if root == NEVER {
+                    continue;
+                }
+                let first = if root == ALWAYS {
+                    0
+                } else {
+                    ((root + q - c1 % q) % q) as usize
+                };
+                for k in (first..len).step_by(step) {
+                    sieve[k] = sieve[k].saturating_add(log_q[i]);
+                }
+            }
+
+            for (k, &s) in sieve.iter().enumerate() {
+                let c2 = c1 + k as u64;
+                let mut v = a + c2 as u128 * d;
+                if (s as u32) + SIEVE_SLACK < 128 - v.leading_zeros() {
+                    continue;
+                }
+                let mut row = Vec::new();
+                for (i, &q) in self.primes.iter().enumerate() {
+                    let root = roots[i];
+                    if root == NEVER || (root != ALWAYS && c2 % q != root) {
+                        continue;
+                    }
+                    let mut e = 0;
+                    while v.is_multiple_of(q as u128) {
+                        v /= q as u128;
+                        e += 1;
+                    }
+                    if e > 0 {
+                        row.push((i, ring.encode(e)));
+                    }
+                }
+                if v != 1 {
+                    continue;
+                }
+                let minus_one = ring.neg(one);
+                if c1 == c2 {
+                    row.push((nb + c1 as usize, ring.neg(ring.encode(2))));
+                } else {
+                    row.push((nb + c1 as usize, minus_one));
+                    row.push((nb + c2 as usize, minus_one));
+                }
+                rows.push((row, 0));
+            }
+        }
+
+        rows
+    }
+}
+
+// arithmetic modulo an odd m < 2^126 on u128 values in Montgomery form,
+// a R mod m for R = 2^128
+struct Montgomery {
+    m: u128,
+    // -m^-1 mod R and R^2 mod m
+    m_neg_inv: u128,
+    r2: u128,
+}
+
+// the 256-bit product a b as (high, low)
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    let (a1, a0) = (a >> 64, a as u64 as u128);
+    let (b1, b0) = (b >> 64, b as u64 as u128);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 as u64 as u128) + (p10 as u64 as u128);
+    let low = (p00 as u64 as u128) | (mid << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (high, low)
+}
+
+impl Montgomery {
+    fn new(m: &BigInt) -> Montgomery {
+        let m128 = m.to_u128().expect("modulus below 2^126");
+        // m^-1 mod R by Newton iteration, each step doubles the correct bits
+        let mut inv = m128;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(m128.wrapping_mul(inv)));
+        }
+        let r2 = ((BigInt::one() << 256u32) % m).to_u128().unwrap_or(0);
+        Montgomery {
+            m: m128,
+            m_neg_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    // t R^-1 mod m for t = (high, low) < m R
+    fn redc(&self, (high, low): (u128, u128)) -> u128 {
+        let q = low.wrapping_mul(self.m_neg_inv);
+        let (th, tl) = mul_wide(q, self.m);
+        let carry = low.overflowing_add(tl).1 as u128;
+        let r = high + th + carry;
+        if r >= self.m {
+            r - self.m
+        } else {
+            r
+        }
+    }
+
+    fn mul(&self, a: u128, b: u128) -> u128 {
+        self.redc(mul_wide(a, b))
+    }
+
+    fn sub(&self, a: u128, b: u128) -> u128 {
+        if a >= b {
+            a - b
+        } else {
+            a + (self.m - b)
+        }
+    }
+
+    fn neg(&self, a: u128) -> u128 {
+        self.sub(0, a)
+    }
+
+    fn encode(&self, x: u64) -> u128 {
+        self.mul(x as u128 % self.m, self.r2)
+    }
+
+    fn decode(&self, a: u128) -> BigInt {
+        BigInt::from(self.redc((0, a)))
+    }
+
+    fn inv(&self, a: u128) -> Option<u128> {
+        let m = BigInt::from(self.m);
+        let x = mod_inv(&self.decode(a), &m)?;
+        // x R = x R^2 R^-1
+        Some(self.mul(x.to_u128()?, self.r2))
+    }
+}
+
+fn inv_mod(a: u64, p: u64) -> u64 {
+    let (mut r0, mut r1) = (a as i64 % p as i64, p as i64);
+    let (mut s0, mut s1) = (1i64, 0i64);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(p as i64) as u64
+}
+
+// structured Gaussian elimination modulo m: the column of least weight among
+// the rows still active is pivoted on the shortest of its rows with a unit
+// coefficient, the weights and the column to rows index being kept up to
+// date through the fill-in, until the cheapest pivot would cost more than
+// MARKOWITZ_LIMIT; what is left is solved densely. None for the unknowns the
+// relations do not determine
+fn solve_sparse(
+    mut rows: Vec<(SparseRow, u128)>,
+    n_cols: usize,
+    ring: &Montgomery,
+) -> Vec<Option<u128>> {
+    let mut active = vec![true; rows.len()];
+    let mut done = vec![false; n_cols];
+    let mut weight = vec![0usize; n_cols];
+    let mut index: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
+    for (r, (row, _)) in rows.iter().enumerate() {
+        for &(c, _) in row {
+            weight[c] += 1;
+            index[c].push(r);
+        }
+    }
+    let mut queue: BinaryHeap<Reverse<(usize, usize)>> = (0..n_cols)
+        .filter(|&c| weight[c] > 0)
+        .map(|c| Reverse((weight[c], c)))
+        .collect();
+
+    let coeff = |row: &SparseRow, c: usize| -> Option<u128> {
+        row.binary_search_by_key(&c, |&(i, _)| i)
+            .ok()
+            .map(|j| row[j].1)
+    };
+
+    let mut pivots: Vec<(usize, usize)> = Vec::new();
+    while let Some(Reverse((w, c))) = queue.pop() {
+        if done[c] || w != weight[c] || w == 0 {
+            continue;
+        }
+        index[c].retain(|&r| active[r] && coeff(&rows[r].0, c).is_some());
+        index[c].sort_by_key(|&r| rows[r].0.len());
+        let Some((r, inv)) = index[c]
+            .iter()
+            .find_map(|&r| Some((r, ring.inv(coeff(&rows[r].0, c)?)?)))
+        else {
+            continue;
+        };
+        if (w - 1) * (rows[r].0.len() - 1) > MARKOWITZ_LIMIT {
+            break;
+        }
+
+        active[r] = false;
+        done[c] = true;
+        for (i, a) in rows[r].0.iter_mut() {
+            *a = ring.mul(*a, inv);
+            weight[*i] -= 1;
+            queue.push(Reverse((weight[*i], *i)));
+        }
+        rows[r].1 = ring.mul(rows[r].1, inv);
+        pivots.push((c, r));
+
+        let (pivot, pivot_rhs) = rows[r].clone();
+        for s in std::mem::take(&mut index[c]) {
+            if s == r {
+                continue;
+            }
+            let a = coeff(&rows[s].0, c).unwrap_or(0);
+            let new = sub_mul(&rows[s].0, &pivot, a, ring);
+            for (i, delta) in column_changes(&rows[s].0, &new) {
+                if delta > 0 {
+                    weight[i] += 1;
+                    index[i].push(s);
+                } else {
+                    weight[i] -= 1;
+                }
+                queue.push(Reverse((weight[i], i)));
+            }
+            rows[s].1 = ring.sub(rows[s].1, ring.mul(a, pivot_rhs));
+            rows[s].0 = new;
+            if rows[s].0.is_empty() {
+                active[s] = false;
+            }
+        }
+    }
+
+    let mut x: Vec<Option<u128>> = vec![None; n_cols];
+    let rest: Vec<usize> = (0..rows.len()).filter(|&r| active[r]).collect();
+    solve_dense(&rows, &rest, n_cols, ring, &mut x);
+
+    // back substitution, the later pivots are free of the earlier columns
+    for &(c, r) in pivots.iter().rev() {
+        let (row, rhs) = &rows[r];
+        let mut v = *rhs;
+        let mut known = true;
+        for &(i, a) in row {
+            if i == c {
+                continue;
+            }
+            match x[i] {
+                Some(xi) => v = ring.sub(v, ring.mul(a, xi)),
+                None => {
+                    known = false;
+                    break;
+                }
+            }
+        }
+        if known {
+            x[c] = Some(v);
+        }
+    }
+    x
+}
+
+// the rows left over by the sparse phase, reduced to echelon form over the
+// columns they still involve; an unknown is set when its pivot row has no
+// other entries
+fn solve_dense(
+    rows: &[(SparseRow, u128)],
+    rest: &[usize],
+    n_cols: usize,
+    ring: &Montgomery,
+    x: &mut [Option<u128>],
+) {
+    let mut position = vec![usize::MAX; n_cols];
+    let mut cols = Vec::new();
+    for &r in rest {
+        for &(c, _) in &rows[r].0 {
+            if position[c] == usize::MAX {
+                position[c] = cols.len();
+                cols.push(c);
+            }
+        }
+    }
+    let width = cols.len();
+    let mut rest = rest.to_vec();
+    rest.sort_by_key(|&r| rows[r].0.len());
+    rest.truncate(width + width / 10 + 10);
+    let mut dense: Vec<Vec<u128>> = rest
+        .iter()
+        .map(|&r| {
+            let mut v = vec![0u128; width + 1];
+            for &(c, a) in &rows[r].0 {
+                v[position[c]] = a;
+            }
+            v[width] = rows[r].1;
+            v
+        })
+        .collect();
+
+    let mut pivot_rows: Vec<(usize, usize)> = Vec::new();
+    let mut next = 0;
+    for j in 0..width {
+        let Some((k, inv)) = (next..dense.len())
+            .find_map(|k| Some((k, ring.inv(dense[k][j]).filter(|_| dense[k][j] != 0)?)))
+        else {
+            continue;
+        };
+        dense.swap(next, k);
+        for a in dense[next][j..].iter_mut() {
+            *a = ring.mul(*a, inv);
+        }
+        let (done_rows, below) = dense.split_at_mut(next + 1);
+        let pivot = &done_rows[next];
+        for row in below {
+            let a = row[j];
+            if a == 0 {
+                continue;
+            }
+            for (y, &b) in row[j..].iter_mut().zip(&pivot[j..]) {
+                if b != 0 {
+                    *y = ring.sub(*y, ring.mul(a, b));
+                }
+            }
+        }
+        pivot_rows.push((j, next));
+        next += 1;
+    }
+
+    // back substitution over the pivot columns, a row with an entry in a free
+    // column leaving its unknown undetermined
+    let mut value: Vec<Option<u128>> = vec![None; width];
+    for &(j, k) in pivot_rows.iter().rev() {
+        let row = &dense[k];
+        let mut v = Some(row[width]);
+        for (i, &a) in row.iter().enumerate().take(width).skip(j + 1) {
+            if a != 0 {
+                v = match (v, value[i]) {
+                    (Some(v), Some(xi)) => Some(ring.sub(v, ring.mul(a, xi))),
+                    _ => None,
+                };
+            }
+        }
+        value[j] = v;
+        x[cols[j]] = v;
+    }
+}
+
+// the columns that u gains (+1) or loses (-1) in becoming v, on rows sorted
+// by column
+fn column_changes(u: &SparseRow, v: &SparseRow) -> Vec<(usize, i32)> {
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < u.len() || j < v.len() {
+        if j == v.len() || (i < u.len() && u[i].0 < v[j].0) {
+            changes.push((u[i].0, -1));
+            i += 1;
+        } else if i == u.len() || v[j].0 < u[i].0 {
+            changes.push((v[j].0, 1));
+            j += 1;
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+    changes
+}
+
+// u - a v modulo m, on rows sorted by column
+fn sub_mul(u: &SparseRow, v: &SparseRow, a: u128, ring: &Montgomery) -> SparseRow {
+    let mut res = Vec::with_capacity(u.len() + v.len());
+    let (mut i, mut j) = (0, 0);
+    while i < u.len() || j < v.len() {
+        let (c, x) = if j == v.len() || (i < u.len() && u[i].0 < v[j].0) {
+            i += 1;
+            (u[i - 1].0, u[i - 1].1)
+        } else if i == u.len() || v[j].0 < u[i].0 {
+            j += 1;
+            (v[j - 1].0, ring.neg(ring.mul(a, v[j - 1].1)))
+        } else {
+            i += 1;
+            j += 1;
+            (u[i - 1].0, ring.sub(u[i - 1].1, ring.mul(a, v[j - 1].1)))
+        };
+        if x != 0 {
+            res.push((c, x));
+        }
+    }
+    res
+}
+
+// x in [0, p - 1) with g^x = h (mod p), g the least primitive root
+pub fn index_calculus_log(p: &BigInt, h: &BigInt) -> Result<BigInt, Error> {
+    LogTable::new(p)?.log(h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sieve::next_prime;
+
+    #[test]
+    fn logs_reexponentiate_to_h() {
+        let primes = [
+            BigInt::from(1_000_003u64),
+            BigInt::from(1_000_000_007u64),
+            next_prime(&(BigInt::one() << 48u32)),
+        ];
+        for p in &primes {
+            let table = LogTable::new(p).unwrap();
+            let g = table.base();
+            for h in [1u64, 2, 3, 12_345, 999_999, 31_415_926_535] {
+                let h = BigInt::from(h);
+                let x = table.log(&h).unwrap();
+                assert!(x >= BigInt::zero() && x < p - 1u32);
+                assert_eq!(g.modpow(&x, p), h.mod_floor(p));
+            }
+        }
+    }
+
+    // p - 1 with a prime factor above SMALL_PRIME_BOUND, so that the log
+    // modulo it comes from the sieve, the elimination and the descent
+    fn check_large_part(p: u64) {
+        let p = BigInt::from(p);
+        let table = LogTable::new(&p).unwrap();
+        assert!(!table.large.is_one());
+        let g = table.base();
+        for h in [2u64, 3, 97, 12_345, 31_415_926_535, 576_460_752_303_423_487] {
+            let h = BigInt::from(h);
+            let x = table.log(&h).unwrap();
+            assert!(x >= BigInt::zero() && x < &p - 1u32);
+            assert_eq!(g.modpow(&x, &p), h.mod_floor(&p));
+        }
+    }
+
+    #[test]
+    fn large_part_of_p_minus_1() {
+        // the safe prime 2 q + 1, q = next prime of 2^58 with 2 q + 1 prime
+        check_large_part(576_460_752_303_424_907);
+    }
+
+    #[test]
+    fn composite_primitive_root() {
+        // least primitive roots 10, 6 and 10, each p - 1 with a prime factor
+        // between 2^35 and 2^39
+        for p in [
+            576_460_753_520_977_033,
+            576_460_754_806_172_321,
+            576_460_755_009_097_897,
+        ] {
+            check_large_part(p);
+        }
+    }
+
+    #[test]
+    fn base_is_the_least_primitive_root() {
+        let p = BigInt::from(1_000_003u64);
+        let table = LogTable::new(&p).unwrap();
+        let n = &p - 1u32;
+        let is_primitive = |a: &BigInt| {
+            factor(&n)
+                .iter()
+                .all(|(q, _)| !a.modpow(&(&n / q), &p).is_one())
+        };
+        let least = (2u32..).map(BigInt::from).find(is_primitive).unwrap();
+        assert_eq!(table.base(), &least);
+        assert_eq!(
+            index_calculus_log(&p, &(&least * &least)),
+            Ok(BigInt::from(2))
+        );
+    }
+
+    #[test]
+    fn bad_moduli_are_rejected() {
+        assert_eq!(
+            LogTable::new(&BigInt::from(1_000_001u64)).err(),
+            Some(Error::NotPrime)
+        );
+        assert!(LogTable::new(&BigInt::from(2)).is_err());
+        let table = LogTable::new(&BigInt::from(1_000_003u64)).unwrap();
+        assert_eq!(table.log(&BigInt::zero()), Err(Error::NotInvertible));
+    }
+}
@@ -1,6 +1,26 @@
 pub mod group;
 pub mod arith;
+pub mod contfrac;
+pub mod crt;
+pub mod dlog;
+pub mod ecm;
+pub mod error;
+pub mod factor;
+pub mod forms;
+pub mod fp;
+pub mod hgcd;
+pub mod index_calculus;
 pub mod matrix;
+pub mod poly;
+pub mod power;
+pub mod primality;
+pub mod reconstruct;
+pub mod ring;
+pub mod roots_mod;
+pub mod sieve;
+pub mod sqrt_mod;
+pub mod squares;
+pub mod siqs;
 
 pub type LargeInt = num_bigint::BigInt;
 
@@ -191,8 +211,11 @@ pub use algorithm_1_3_11::chinese_remainder_pairwise;
 pub use algorithm_1_3_12::chinese_remainder_inductive;
 pub use algorithm_1_3_13::lehmer_continued_fraction_bounds;
 pub use algorithm_1_3_14::gauss_reduce;
-pub use algorithm_1_4_3::order_of_element;
-pub use algorithm_1_4_4::primitive_root_mod_p;
+pub use algorithm_1_4_3::{
+    exponent_of_group, order_of_element, order_of_element_auto, order_of_element_bsgs,
+    order_of_element_primorial,
+};
+pub use algorithm_1_4_4::{primitive_root_mod_p, primitive_root_mod_p_auto};
 pub use algorithm_1_4_10::kronecker;
 pub use algorithm_1_4_12::kronecker_binary;
 pub use algorithm_1_5_1::sqrt_mod_prime;
@@ -208,7 +231,7 @@ pub use algorithm_2_2_3::determinant;
 pub use algorithm_2_2_6::gauss_bareiss_det;
 pub use algorithm_2_2_7::characteristic_polynomial_and_adjoint;
 pub use algorithm_2_2_9::characteristic_polynomial_hessenberg;
-pub use algorithm_2_3_1::kernel_basis;
+pub use algorithm_2_3_1::{kernel_basis, kernel_basis_mod2};
 pub use algorithm_2_3_2::image_basis;
 pub use algorithm_2_3_4::inverse_image_vector;
 pub use algorithm_2_3_5::inverse_image_matrix;
@@ -236,7 +259,7 @@ pub use algorithm_2_7_7::fincke_pohst;
 pub use algorithm_3_1_1::euclidean_division;
 pub use algorithm_3_1_2::pseudo_division;
 pub use algorithm_3_2_1::polynomial_gcd;
-pub use algorithm_3_2_2::polynomial_extended_gcd;
+pub use algorithm_3_2_2::{polynomial_extended_gcd, PolyBezout};
 pub use algorithm_3_2_10::primitive_polynomial_gcd;
 pub use algorithm_3_3_1::subresultant_gcd;
 pub use algorithm_3_3_7::resultant_subresultant;
@@ -263,8 +286,44 @@ pub use algorithm_7_5_5::{rational_torsion_points, RationalPoint};
 pub use algorithm_7_5_6::finite_height_contribution;
 pub use algorithm_7_5_7::height_archimedean;
 pub use algorithm_7_6_1::hilbert_class_polynomial;
-pub use matrix::{
-    br, columns_to_matrix, identity, mat_add, mat_mul, mat_mul_rect, scalar_identity, trace,
-    Matrix, Vector,
+pub use arith::{
+    carmichael_lambda, carmichael_lambda_factored, divisors, divisors_factored, euler_phi,
+    euler_phi_factored, moebius, moebius_factored, radical, radical_factored, sigma,
+    sigma_factored, tau, tau_factored, valuation, valuation_factored,
+};
+pub use matrix::{br, Matrix, Vector};
+pub use contfrac::{
+    best_approximation, convergents, negative_pell, pell, quadratic_continued_fraction,
+    rational_continued_fraction, semiconvergents,
 };
+pub use crt::{chinese_remainder_general, chinese_remainder_tree, CrtBasis};
+pub use dlog::{discrete_log, dlog_bsgs, dlog_pohlig_hellman, dlog_pollard_lambda, dlog_pollard_rho};
+pub use ecm::ecm;
+pub use error::Error;
+pub use factor::{factor, pollard_p_minus_1, pollard_rho_brent, squfof, trial_division};
+pub use forms::{form_representations, representations_x2_dy2};
+pub use fp::{Fp, FpContext};
 pub use group::GroupElement;
+pub use hgcd::{extended_gcd, half_gcd_extended_gcd};
+pub use index_calculus::{index_calculus_log, LogTable};
+pub use poly::Poly;
+pub use power::{
+    montgomery_ladder_power, multi_power, multi_power_pippenger, multi_power_straus, power,
+    sliding_window_power, wnaf, wnaf_power, FixedBase,
+};
+pub use primality::{
+    is_probable_prime, is_strong_lucas_probable_prime, is_strong_probable_prime, miller_rabin,
+};
+pub use reconstruct::{
+    multimodular_integer, multimodular_rational, rational_reconstruction,
+    rational_reconstruction_bounded, rational_reconstruction_matrix, rational_reconstruction_vector,
+};
+pub use ring::{EuclideanDomain, Field, Ring};
+pub use roots_mod::{roots_mod, roots_mod_factored, roots_mod_prime_power, ModularRoots};
+pub use sieve::{next_prime, prev_prime, prime_pi, primes_in_range, primes_up_to, PrimeRange};
+pub use siqs::siqs;
+pub use sqrt_mod::{cipolla, sqrt_mod, sqrt_mod_factored, sqrt_mod_prime_power};
+pub use squares::{
+    four_squares, r2, r2_factored, r4, r4_factored, three_squares, two_squares,
+    two_squares_factored,
+};
@@ -1,98 +1,498 @@
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Range, Sub};
+
 use num_bigint::BigInt;
 use num_rational::BigRational;
-use num_traits::{One, Zero};
 
-pub type Matrix = Vec<Vec<BigRational>>;
-pub type Vector = Vec<BigRational>;
+use crate::error::Error;
+use crate::ring::Ring;
+
+pub type Vector<F> = Vec<F>;
+
+// Dense row-major matrix, entry (i, j) is stored at data[i * cols + j].
+// The shape is tracked separately so that empty matrices keep their
+// dimensions (a 3 x 0 matrix is not the same as a 0 x 0 one).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix<R> {
+    rows: usize,
+    cols: usize,
+    data: Vec<R>,
+}
 
 pub fn br(n: i64) -> BigRational {
     BigRational::from_integer(BigInt::from(n))
 }
 
-pub fn identity(n: usize) -> Matrix {
-    let mut m = vec![vec![BigRational::zero(); n]; n];
-    for i in 0..n {
-        m[i][i] = BigRational::one();
+impl<R> Matrix<R> {
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<R>) -> Result<Matrix<R>, Error> {
+        if data.len() != rows * cols {
+            return Err(Error::DimensionMismatch);
+        }
+        Ok(Matrix { rows, cols, data })
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn row(&self, i: usize) -> &[R] {
+        assert!(i < self.rows, "row index out of range");
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [R] {
+        assert!(i < self.rows, "row index out of range");
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[R]> {
+        (0..self.rows).map(move |i| self.row(i))
+    }
+
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        for k in 0..self.cols {
+            self.data.swap(i * self.cols + k, j * self.cols + k);
+        }
+    }
+
+    pub fn swap_columns(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        for k in 0..self.rows {
+            self.data.swap(k * self.cols + i, k * self.cols + j);
+        }
+    }
+
+    pub fn map<S, F: Fn(&R) -> S>(&self, f: F) -> Matrix<S> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(f).collect(),
+        }
     }
-    m
 }
 
-pub fn mat_mul(a: &Matrix, b: &Matrix) -> Matrix {
-    let n = a.len();
-    let mut res = vec![vec![BigRational::zero(); n]; n];
-    for i in 0..n {
-        for k in 0..n {
-            for j in 0..n {
-                res[i][j] += &a[i][k] * &b[k][j];
+impl<R: Clone> Matrix<R> {
+    // rows must all have the same length
+    pub fn from_rows(rows: Vec<Vec<R>>) -> Result<Matrix<R>, Error> {
+        let n_rows = rows.len();
+        let n_cols = rows.first().map(|row| row.len()).unwrap_or(0);
+        if rows.iter().any(|row| row.len() != n_cols) {
+            return Err(Error::DimensionMismatch);
+        }
+        let data = rows.into_iter().flatten().collect();
+        Ok(Matrix {
+            rows: n_rows,
+            cols: n_cols,
+            data,
+        })
+    }
+
+    pub fn from_columns(cols: &[Vector<R>]) -> Result<Matrix<R>, Error> {
+        let n_rows = cols.first().map(|col| col.len()).unwrap_or(0);
+        if cols.iter().any(|col| col.len() != n_rows) {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut data = Vec::with_capacity(n_rows * cols.len());
+        for i in 0..n_rows {
+            for col in cols {
+                data.push(col[i].clone());
             }
         }
+        Ok(Matrix {
+            rows: n_rows,
+            cols: cols.len(),
+            data,
+        })
     }
-    res
-}
 
-pub fn mat_mul_rect(a: &Matrix, b: &Matrix) -> Option<Matrix> {
-    let a_rows = a.len();
-    let a_cols = a.first().map(|row| row.len()).unwrap_or(0);
-    if a.iter().any(|row| row.len() != a_cols) {
-        return None;
+    pub fn column(&self, j: usize) -> Vector<R> {
+        assert!(j < self.cols, "column index out of range");
+        (0..self.rows).map(|i| self[(i, j)].clone()).collect()
     }
-    let b_rows = b.len();
-    if b_rows != a_cols {
-        return None;
+
+    pub fn columns(&self) -> Vec<Vector<R>> {
+        (0..self.cols).map(|j| self.column(j)).collect()
+    }
+
+    pub fn to_rows(&self) -> Vec<Vector<R>> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+
+    pub fn transpose(&self) -> Matrix<R> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for j in 0..self.cols {
+            for i in 0..self.rows {
+                data.push(self[(i, j)].clone());
+            }
+        }
+        Matrix {
+            rows: self.cols,
+            cols: self.rows,
+            data,
+        }
     }
-    let b_cols = if b_rows == 0 {
-        0
-    } else {
-        b.first().map(|row| row.len()).unwrap_or(0)
-    };
-    if b.iter().any(|row| row.len() != b_cols) {
-        return None;
+
+    pub fn submatrix(&self, rows: Range<usize>, cols: Range<usize>) -> Matrix<R> {
+        assert!(rows.end <= self.rows && cols.end <= self.cols, "submatrix out of range");
+        let mut data = Vec::with_capacity(rows.len() * cols.len());
+        for i in rows.clone() {
+            data.extend_from_slice(&self.row(i)[cols.clone()]);
+        }
+        Matrix {
+            rows: rows.len(),
+            cols: cols.len(),
+            data,
+        }
     }
-    let mut res = vec![vec![BigRational::zero(); b_cols]; a_rows];
-    for i in 0..a_rows {
-        for k in 0..a_cols {
-            for j in 0..b_cols {
-                res[i][j] += &a[i][k] * &b[k][j];
+
+    // columns listed in `cols`, in that order
+    pub fn select_columns(&self, cols: &[usize]) -> Matrix<R> {
+        let mut data = Vec::with_capacity(self.rows * cols.len());
+        for i in 0..self.rows {
+            for &j in cols {
+                data.push(self[(i, j)].clone());
             }
         }
+        Matrix {
+            rows: self.rows,
+            cols: cols.len(),
+            data,
+        }
+    }
+
+    // [self | other]
+    pub fn augment(&self, other: &Matrix<R>) -> Result<Matrix<R>, Error> {
+        if self.rows != other.rows {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut data = Vec::with_capacity(self.data.len() + other.data.len());
+        for i in 0..self.rows {
+            data.extend_from_slice(self.row(i));
+            data.extend_from_slice(other.row(i));
+        }
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols + other.cols,
+            data,
+        })
     }
-    Some(res)
 }
 
-pub fn mat_add(a: &Matrix, b: &Matrix) -> Matrix {
-    let n = a.len();
-    let mut res = vec![vec![BigRational::zero(); n]; n];
-    for i in 0..n {
-        for j in 0..n {
-            res[i][j] = &a[i][j] + &b[i][j];
+impl<R: Ring> Matrix<R> {
+    pub fn zeros(rows: usize, cols: usize) -> Matrix<R> {
+        Matrix {
+            rows,
+            cols,
+            data: vec![R::zero(); rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Matrix<R> {
+        Matrix::scalar(R::one(), n)
+    }
+
+    pub fn scalar(s: R, n: usize) -> Matrix<R> {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = s.clone();
+        }
+        m
+    }
+
+    pub fn trace(&self) -> Result<R, Error> {
+        if !self.is_square() {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut t = R::zero();
+        for i in 0..self.rows {
+            t = t + self[(i, i)].clone();
+        }
+        Ok(t)
+    }
+
+    pub fn checked_add(&self, other: &Matrix<R>) -> Result<Matrix<R>, Error> {
+        self.zip_with(other, |x, y| x.clone() + y.clone())
+    }
+
+    pub fn checked_sub(&self, other: &Matrix<R>) -> Result<Matrix<R>, Error> {
+        self.zip_with(other, |x, y| x.clone() - y.clone())
+    }
+
+    pub fn checked_mul(&self, other: &Matrix<R>) -> Result<Matrix<R>, Error> {
+        if self.cols != other.rows {
+            return Err(Error::DimensionMismatch);
         }
+        let mut res: Matrix<R> = Matrix::zeros(self.rows, other.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = &self[(i, k)];
+                if a.is_zero() {
+                    continue;
+                }
+                for j in 0..other.cols {
+                    res[(i, j)] = res[(i, j)].clone() + a.clone() * other[(k, j)].clone();
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn mul_vec(&self, v: &[R]) -> Result<Vector<R>, Error> {
+        if self.cols != v.len() {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut res = vec![R::zero(); self.rows];
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                res[i] = res[i].clone() + self[(i, j)].clone() * v[j].clone();
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn scale_row(&mut self, i: usize, c: &R) {
+        for x in self.row_mut(i) {
+            *x = c.clone() * x.clone();
+        }
+    }
+
+    pub fn scale_column(&mut self, j: usize, c: &R) {
+        for i in 0..self.rows {
+            self[(i, j)] = c.clone() * self[(i, j)].clone();
+        }
+    }
+
+    // row dst <- row dst + c * row src
+    pub fn add_row_multiple(&mut self, dst: usize, src: usize, c: &R) {
+        assert!(dst != src, "source and destination rows coincide");
+        for k in 0..self.cols {
+            let t = c.clone() * self[(src, k)].clone();
+            self[(dst, k)] = self[(dst, k)].clone() + t;
+        }
+    }
+
+    // column dst <- column dst + c * column src
+    pub fn add_column_multiple(&mut self, dst: usize, src: usize, c: &R) {
+        assert!(dst != src, "source and destination columns coincide");
+        for k in 0..self.rows {
+            let t = c.clone() * self[(k, src)].clone();
+            self[(k, dst)] = self[(k, dst)].clone() + t;
+        }
+    }
+
+    fn zip_with(&self, other: &Matrix<R>, f: impl Fn(&R, &R) -> R) -> Result<Matrix<R>, Error> {
+        if self.shape() != other.shape() {
+            return Err(Error::DimensionMismatch);
+        }
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().zip(other.data.iter()).map(|(x, y)| f(x, y)).collect(),
+        })
+    }
+}
+
+impl<R> Index<(usize, usize)> for Matrix<R> {
+    type Output = R;
+
+    fn index(&self, (i, j): (usize, usize)) -> &R {
+        assert!(i < self.rows && j < self.cols, "matrix index out of range");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<R> IndexMut<(usize, usize)> for Matrix<R> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut R {
+        assert!(i < self.rows && j < self.cols, "matrix index out of range");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+// m[i] is row i, so m[i][j] is entry (i, j)
+impl<R> Index<usize> for Matrix<R> {
+    type Output = [R];
+
+    fn index(&self, i: usize) -> &[R] {
+        self.row(i)
+    }
+}
+
+impl<R> IndexMut<usize> for Matrix<R> {
+    fn index_mut(&mut self, i: usize) -> &mut [R] {
+        self.row_mut(i)
+    }
+}
+
+// The operators panic on a shape mismatch, use the checked_* methods to
+// get an Error instead.
+impl<'a, R: Ring> Add<&'a Matrix<R>> for &'a Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn add(self, other: &'a Matrix<R>) -> Matrix<R> {
+        self.checked_add(other).expect("matrix shapes differ")
+    }
+}
+
+impl<'a, R: Ring> Sub<&'a Matrix<R>> for &'a Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn sub(self, other: &'a Matrix<R>) -> Matrix<R> {
+        self.checked_sub(other).expect("matrix shapes differ")
     }
-    res
 }
 
-pub fn scalar_identity(s: BigRational, n: usize) -> Matrix {
-    let mut id = vec![vec![BigRational::zero(); n]; n];
-    for i in 0..n {
-        id[i][i] = s.clone();
+impl<'a, R: Ring> Mul<&'a Matrix<R>> for &'a Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn mul(self, other: &'a Matrix<R>) -> Matrix<R> {
+        self.checked_mul(other).expect("inner dimensions differ")
     }
-    id
 }
 
-pub fn trace(m: &Matrix) -> BigRational {
-    let mut t = BigRational::zero();
-    for i in 0..m.len() {
-        t += &m[i][i];
+impl<R: Ring> Neg for &Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn neg(self) -> Matrix<R> {
+        self.map(|x| -x.clone())
     }
-    t
 }
 
-pub fn columns_to_matrix(cols: &[Vector]) -> Matrix {
-    let rows = cols.first().map(|c| c.len()).unwrap_or(0);
-    let mut m = vec![vec![BigRational::zero(); cols.len()]; rows];
-    for (j, col) in cols.iter().enumerate() {
-        for (i, val) in col.iter().enumerate() {
-            m[i][j] = val.clone();
+impl<R: Ring> Add for Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn add(self, other: Matrix<R>) -> Matrix<R> {
+        &self + &other
+    }
+}
+
+impl<R: Ring> Sub for Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn sub(self, other: Matrix<R>) -> Matrix<R> {
+        &self - &other
+    }
+}
+
+impl<R: Ring> Mul for Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn mul(self, other: Matrix<R>) -> Matrix<R> {
+        &self * &other
+    }
+}
+
+impl<R: Ring> Neg for Matrix<R> {
+    type Output = Matrix<R>;
+
+    fn neg(self) -> Matrix<R> {
+        -&self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the 2 x 3 matrix [[1, 2, 3], [4, 5, 6]]
+    fn m23() -> Matrix<i64> {
+        Matrix::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap()
+    }
+
+    #[test]
+    fn bad_shapes_are_rejected() {
+        assert_eq!(
+            Matrix::from_vec(2, 3, vec![1i64; 5]),
+            Err(Error::DimensionMismatch)
+        );
+        assert_eq!(
+            Matrix::from_vec(0, 3, vec![1i64]),
+            Err(Error::DimensionMismatch)
+        );
+        assert_eq!(
+            Matrix::from_rows(vec![vec![1i64, 2], vec![3]]),
+            Err(Error::DimensionMismatch)
+        );
+        assert_eq!(
+            Matrix::from_columns(&[vec![1i64, 2], vec![3]]),
+            Err(Error::DimensionMismatch)
+        );
+        let a = m23();
+        assert_eq!(a.checked_mul(&a), Err(Error::DimensionMismatch));
+        assert_eq!(a.checked_add(&a.transpose()), Err(Error::DimensionMismatch));
+        assert_eq!(a.checked_sub(&a.transpose()), Err(Error::DimensionMismatch));
+        assert_eq!(a.augment(&a.transpose()), Err(Error::DimensionMismatch));
+        assert_eq!(a.mul_vec(&[1, 2]), Err(Error::DimensionMismatch));
+        assert_eq!(a.trace(), Err(Error::DimensionMismatch));
+    }
+
+    #[test]
+    fn good_shapes_multiply_and_augment() {
+        let a = m23();
+        assert_eq!(
+            Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]),
+            Ok(a.clone())
+        );
+        assert_eq!(
+            Matrix::from_columns(&[vec![1, 4], vec![2, 5], vec![3, 6]]),
+            Ok(a.clone())
+        );
+        let p = a.checked_mul(&a.transpose()).unwrap();
+        assert_eq!(
+            p,
+            Matrix::from_rows(vec![vec![14, 32], vec![32, 77]]).unwrap()
+        );
+        assert_eq!(p.trace(), Ok(91));
+        assert_eq!(a.mul_vec(&[1, 0, -1]), Ok(vec![-2, -2]));
+        let b = a.augment(&Matrix::identity(2)).unwrap();
+        assert_eq!(b.shape(), (2, 5));
+        assert_eq!(b.row(1), &[4, 5, 6, 0, 1]);
+        // empty matrices keep their shape
+        let e: Matrix<i64> = Matrix::zeros(3, 0);
+        assert_eq!(e.transpose().shape(), (0, 3));
+        assert_eq!(e.checked_mul(&Matrix::zeros(0, 2)), Ok(Matrix::zeros(3, 2)));
+    }
+
+    #[test]
+    fn transpose_submatrix_and_select_columns() {
+        let a = m23();
+        let t = a.transpose();
+        assert_eq!(t.shape(), (3, 2));
+        for i in 0..2 {
+            for j in 0..3 {
+                assert_eq!(t[(j, i)], a[(i, j)]);
+            }
         }
+        assert_eq!(t.transpose(), a);
+        assert_eq!(
+            a.submatrix(0..2, 1..3),
+            Matrix::from_rows(vec![vec![2, 3], vec![5, 6]]).unwrap()
+        );
+        assert_eq!(a.submatrix(1..2, 0..3).to_rows(), vec![vec![4, 5, 6]]);
+        assert_eq!(a.submatrix(1..1, 0..3).shape(), (0, 3));
+        assert_eq!(
+            a.select_columns(&[2, 0, 2]),
+            Matrix::from_rows(vec![vec![3, 1, 3], vec![6, 4, 6]]).unwrap()
+        );
+        assert_eq!(a.select_columns(&[]).shape(), (2, 0));
     }
-    m
 }
@@ -0,0 +1,468 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::ring::{EuclideanDomain, Field};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Poly<R> {
+    coeffs: Vec<R>,
+}
+
+impl<R: Clone + Zero> Poly<R> {
+    pub fn new(coeffs: Vec<R>) -> Self {
+        let mut p = Poly { coeffs };
+        p.trim();
+        p
+    }
+
+    pub fn constant(c: R) -> Self {
+        Poly::new(vec![c])
+    }
+
+    pub fn monomial(c: R, k: usize) -> Self {
+        if c.is_zero() {
+            return Poly { coeffs: Vec::new() };
+        }
+        let mut coeffs = vec![R::zero(); k + 1];
+        coeffs[k] = c;
+        Poly { coeffs }
+    }
+
+    pub fn x() -> Self
+    where
+        R: One,
+    {
+        Poly::monomial(R::one(), 1)
+    }
+
+    fn trim(&mut self) {
+        while let Some(last) = self.coeffs.last() {
+            if last.is_zero() {
+                self.coeffs.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn coeffs(&self) -> &[R] {
+        &self.coeffs
+    }
+
+    pub fn into_coeffs(self) -> Vec<R> {
+        self.coeffs
+    }
+
+    pub fn coeff(&self, i: usize) -> R {
+        self.coeffs.get(i).cloned().unwrap_or_else(R::zero)
+    }
+
+    pub fn degree(&self) -> isize {
+        self.coeffs.len() as isize - 1
+    }
+
+    pub fn leading_coeff(&self) -> R {
+        self.coeffs.last().cloned().unwrap_or_else(R::zero)
+    }
+
+    pub fn is_monic(&self) -> bool
+    where
+        R: One + PartialEq,
+    {
+        self.coeffs.last().is_some_and(|c| c.is_one())
+    }
+
+    pub fn mul_x_pow(&self, k: usize) -> Self {
+        if self.coeffs.is_empty() {
+            return self.clone();
+        }
+        let mut coeffs = vec![R::zero(); k];
+        coeffs.extend(self.coeffs.iter().cloned());
+        Poly { coeffs }
+    }
+
+    pub fn scale(&self, c: &R) -> Self
+    where
+        R: Mul<Output = R>,
+    {
+        Poly::new(self.coeffs.iter().map(|a| a.clone() * c.clone()).collect())
+    }
+
+    pub fn eval(&self, x: &R) -> R
+    where
+        R: Mul<Output = R>,
+    {
+        let mut acc = R::zero();
+        for c in self.coeffs.iter().rev() {
+            acc = acc * x.clone() + c.clone();
+        }
+        acc
+    }
+
+    pub fn derivative(&self) -> Self
+    where
+        R: One + Mul<Output = R>,
+    {
+        let mut res = Vec::with_capacity(self.coeffs.len().saturating_sub(1));
+        let mut k = R::zero();
+        for c in self.coeffs.iter().skip(1) {
+            k = k + R::one();
+            res.push(k.clone() * c.clone());
+        }
+        Poly::new(res)
+    }
+
+    pub fn map<S, F>(&self, f: F) -> Poly<S>
+    where
+        S: Clone + Zero,
+        F: FnMut(&R) -> S,
+    {
+        Poly::new(self.coeffs.iter().map(f).collect())
+    }
+}
+
+impl Poly<BigInt> {
+    pub fn content(&self) -> BigInt {
+        let mut g = BigInt::zero();
+        for c in self.coeffs.iter() {
+            g = g.gcd(c);
+        }
+        g
+    }
+
+    pub fn primitive_part(&self) -> Poly<BigInt> {
+        let g = self.content();
+        if g.is_zero() || g.is_one() {
+            return self.clone();
+        }
+        Poly::new(self.coeffs.iter().map(|c| c / &g).collect())
+    }
+
+    pub fn reduce_mod(&self, m: &BigInt) -> Poly<BigInt> {
+        self.map(|c| c.mod_floor(m))
+    }
+
+    pub fn mul_mod(&self, other: &Poly<BigInt>, m: &BigInt) -> Poly<BigInt> {
+        (self * other).reduce_mod(m)
+    }
+
+    pub fn div_rem_mod(
+        &self,
+        b: &Poly<BigInt>,
+        m: &BigInt,
+    ) -> Option<(Poly<BigInt>, Poly<BigInt>)> {
+        let b = b.reduce_mod(m);
+        if b.is_zero() {
+            return None;
+        }
+        let deg_b = b.degree();
+        let inv_lc = crate::arith::mod_inv(&b.leading_coeff(), m)?;
+        let mut r = self.reduce_mod(m);
+        let mut q = vec![BigInt::zero(); (r.degree() - deg_b + 1).max(0) as usize];
+        while r.degree() >= deg_b {
+            let shift = (r.degree() - deg_b) as usize;
+            let coeff = (r.leading_coeff() * &inv_lc).mod_floor(m);
+            for (i, bi) in b.coeffs.iter().enumerate() {
+                let idx = i + shift;
+                r.coeffs[idx] = (&r.coeffs[idx] - bi * &coeff).mod_floor(m);
+            }
+            q[shift] = coeff;
+            r.trim();
+        }
+        Some((Poly::new(q), r))
+    }
+
+    pub fn rem_mod(&self, b: &Poly<BigInt>, m: &BigInt) -> Poly<BigInt> {
+        match self.div_rem_mod(b, m) {
+            Some((_q, r)) => r,
+            None => self.reduce_mod(m),
+        }
+    }
+}
+
+impl<F: Field> Poly<F> {
+    pub fn pow_mod(&self, exp: &BigInt, modulus: &Poly<F>) -> Poly<F> {
+        let mut result = Poly::one().quo_rem(modulus).1;
+        let base = self.quo_rem(modulus).1;
+        let e = exp.magnitude();
+        for i in (0..e.bits()).rev() {
+            result = (&result * &result).quo_rem(modulus).1;
+            if e.bit(i) {
+                result = (&result * &base).quo_rem(modulus).1;
+            }
+        }
+        result
+    }
+}
+
+impl<R: Clone + Zero> From<Vec<R>> for Poly<R> {
+    fn from(coeffs: Vec<R>) -> Self {
+        Poly::new(coeffs)
+    }
+}
+
+impl<'a, R> Add<&'a Poly<R>> for &'a Poly<R>
+where
+    R: Clone + Zero,
+{
+    type Output = Poly<R>;
+
+    fn add(self, other: &'a Poly<R>) -> Poly<R> {
+        let n = self.coeffs.len().max(other.coeffs.len());
+        let mut res = Vec::with_capacity(n);
+        for i in 0..n {
+            res.push(self.coeff(i) + other.coeff(i));
+        }
+        Poly::new(res)
+    }
+}
+
+impl<'a, R> Sub<&'a Poly<R>> for &'a Poly<R>
+where
+    R: Clone + Zero + Sub<Output = R>,
+{
+    type Output = Poly<R>;
+
+    fn sub(self, other: &'a Poly<R>) -> Poly<R> {
+        let n = self.coeffs.len().max(other.coeffs.len());
+        let mut res = Vec::with_capacity(n);
+        for i in 0..n {
+            res.push(self.coeff(i) - other.coeff(i));
+        }
+        Poly::new(res)
+    }
+}
+
+impl<'a, R> Mul<&'a Poly<R>> for &'a Poly<R>
+where
+    R: Clone + Zero + Mul<Output = R>,
+{
+    type Output = Poly<R>;
+
+    fn mul(self, other: &'a Poly<R>) -> Poly<R> {
+        if self.coeffs.is_empty() || other.coeffs.is_empty() {
+            return Poly { coeffs: Vec::new() };
+        }
+        let mut res = vec![R::zero(); self.coeffs.len() + other.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            if a.is_zero() {
+                continue;
+            }
+            for (j, b) in other.coeffs.iter().enumerate() {
+                res[i + j] = res[i + j].clone() + a.clone() * b.clone();
+            }
+        }
+        Poly::new(res)
+    }
+}
+
+impl<R> Neg for &Poly<R>
+where
+    R: Clone + Zero + Neg<Output = R>,
+{
+    type Output = Poly<R>;
+
+    fn neg(self) -> Poly<R> {
+        Poly::new(self.coeffs.iter().map(|c| -c.clone()).collect())
+    }
+}
+
+impl<R> Add for Poly<R>
+where
+    R: Clone + Zero,
+{
+    type Output = Poly<R>;
+
+    fn add(self, other: Poly<R>) -> Poly<R> {
+        &self + &other
+    }
+}
+
+impl<R> Sub for Poly<R>
+where
+    R: Clone + Zero + Sub<Output = R>,
+{
+    type Output = Poly<R>;
+
+    fn sub(self, other: Poly<R>) -> Poly<R> {
+        &self - &other
+    }
+}
+
+impl<R> Mul for Poly<R>
+where
+    R: Clone + Zero + Mul<Output = R>,
+{
+    type Output = Poly<R>;
+
+    fn mul(self, other: Poly<R>) -> Poly<R> {
+        &self * &other
+    }
+}
+
+impl<R> Neg for Poly<R>
+where
+    R: Clone + Zero + Neg<Output = R>,
+{
+    type Output = Poly<R>;
+
+    fn neg(self) -> Poly<R> {
+        -&self
+    }
+}
+
+impl<R> Zero for Poly<R>
+where
+    R: Clone + Zero,
+{
+    fn zero() -> Self {
+        Poly { coeffs: Vec::new() }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+}
+
+impl<R> One for Poly<R>
+where
+    R: Clone + Zero + One + Mul<Output = R>,
+{
+    fn one() -> Self {
+        Poly::constant(R::one())
+    }
+}
+
+impl<R> fmt::Display for Poly<R>
+where
+    R: Clone + Zero + One + PartialEq + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.coeffs.is_empty() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (k, c) in self.coeffs.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+            let s = c.to_string();
+            let (negative, body) = match s.strip_prefix('-') {
+                Some(rest) => (true, rest.to_string()),
+                None => (false, s),
+            };
+            if first {
+                if negative {
+                    write!(f, "-")?;
+                }
+            } else if negative {
+                write!(f, " - ")?;
+            } else {
+                write!(f, " + ")?;
+            }
+            first = false;
+            let unit = c.is_one() || body == "1";
+            if k == 0 {
+                write!(f, "{}", body)?;
+                continue;
+            }
+            if !unit {
+                if body.contains(['+', '-', ' ']) {
+                    write!(f, "({})*", body)?;
+                } else {
+                    write!(f, "{}*", body)?;
+                }
+            }
+            if k == 1 {
+                write!(f, "x")?;
+            } else {
+                write!(f, "x^{}", k)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_rational::BigRational;
+
+    fn poly(coeffs: &[i64]) -> Poly<BigInt> {
+        Poly::new(coeffs.iter().map(|&c| BigInt::from(c)).collect())
+    }
+
+    #[test]
+    fn zero_and_trailing_zeros() {
+        let zero: Poly<BigInt> = Poly::zero();
+        assert_eq!(zero.degree(), -1);
+        assert!(zero.leading_coeff().is_zero());
+        assert!(zero.coeffs().is_empty());
+        assert_eq!(poly(&[0, 0, 0]), zero);
+        assert_eq!(Poly::constant(BigInt::zero()), zero);
+        assert_eq!(Poly::monomial(BigInt::zero(), 5), zero);
+        assert_eq!(zero.mul_x_pow(3), zero);
+        assert_eq!(poly(&[1, 2, 0, 0]), poly(&[1, 2]));
+        assert_eq!(poly(&[1, 2, 0, 0]).degree(), 1);
+        assert_eq!(&poly(&[1, 0, 3]) - &poly(&[1, 0, 3]), zero);
+        assert_eq!((&poly(&[1, 1, 1]) + &poly(&[0, 0, -1])).degree(), 1);
+        assert!(poly(&[1, 2, 0]).coeff(7).is_zero());
+    }
+
+    #[test]
+    fn degree_leading_coefficient_and_monic() {
+        let f = poly(&[-6, 11, -6, 1]);
+        assert_eq!(f.degree(), 3);
+        assert_eq!(f.leading_coeff(), BigInt::one());
+        assert!(f.is_monic());
+        assert!(!poly(&[1, 2]).is_monic());
+        assert_eq!(Poly::<BigInt>::x().degree(), 1);
+        assert_eq!(poly(&[5]).degree(), 0);
+        assert_eq!(f.mul_x_pow(2), poly(&[0, 0, -6, 11, -6, 1]));
+    }
+
+    #[test]
+    fn content_and_primitive_part() {
+        let f = poly(&[6, -4, 10]);
+        assert_eq!(f.content(), BigInt::from(2));
+        assert_eq!(f.primitive_part(), poly(&[3, -2, 5]));
+        assert_eq!(poly(&[3, 5]).primitive_part(), poly(&[3, 5]));
+        assert!(Poly::<BigInt>::zero().content().is_zero());
+        assert!(Poly::<BigInt>::zero().primitive_part().is_zero());
+    }
+
+    #[test]
+    fn derivative_and_evaluation() {
+        let f = poly(&[-6, 11, -6, 1]);
+        assert_eq!(f.derivative(), poly(&[11, -12, 3]));
+        assert!(poly(&[7]).derivative().is_zero());
+        assert!(Poly::<BigInt>::zero().derivative().is_zero());
+        for (x, y) in [(0, -6), (1, 0), (2, 0), (3, 0), (4, 6), (-1, -24)] {
+            assert_eq!(f.eval(&BigInt::from(x)), BigInt::from(y));
+        }
+        assert!(Poly::<BigInt>::zero().eval(&BigInt::from(9)).is_zero());
+        // over the rationals, 2x^2 - 1/2 at 1/2 is 0
+        let half = BigRational::new(BigInt::one(), BigInt::from(2));
+        let g = Poly::new(vec![
+            -half.clone(),
+            BigRational::zero(),
+            BigRational::from_integer(BigInt::from(2)),
+        ]);
+        assert!(g.eval(&half).is_zero());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Poly::<BigInt>::zero().to_string(), "0");
+        assert_eq!(poly(&[1, -2, 1]).to_string(), "x^2 - 2*x + 1");
+        assert_eq!(poly(&[5, 0, 0, -1]).to_string(), "-x^3 + 5");
+        assert_eq!(poly(&[0, 3]).to_string(), "3*x");
+        assert_eq!(poly(&[-4]).to_string(), "-4");
+        let half = BigRational::new(BigInt::from(-1), BigInt::from(2));
+        assert_eq!(Poly::monomial(half, 1).to_string(), "-1/2*x");
+    }
+}
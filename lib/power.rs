@@ -0,0 +1,572 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::error::Error;
+use crate::group::GroupElement;
+
+// from this many bases on, multi_power uses buckets rather than interleaving
+const PIPPENGER_THRESHOLD: usize = 32;
+
+// widest window accepted: the odd power table has 2^(w-1) entries
+const MAX_WINDOW: usize = 16;
+
+// window width from the size of the exponent
+fn window(n: &BigInt) -> usize {
+    match n.bits() {
+        0..=32 => 3,
+        33..=128 => 4,
+        129..=512 => 5,
+        _ => 6,
+    }
+}
+
+// signed digits of n >= 0, least significant first, each zero or odd with
+// absolute value below 2^(w-1), and at most one nonzero in any w consecutive
+pub fn wnaf(n: &BigInt, w: usize) -> Result<Vec<i64>, Error> {
+    if !(2..=MAX_WINDOW).contains(&w) {
+        return Err(Error::InvalidArgument("window width must be in 2..=16"));
+    }
+    Ok(wnaf_digits(n, w))
+}
+
+fn wnaf_digits(n: &BigInt, w: usize) -> Vec<i64> {
+    let modulus = BigInt::from(1u64 << w);
+    let half = 1i64 << (w - 1);
+    let mut k = n.clone();
+    let mut digits = Vec::new();
+    while k.is_positive() {
+        let d = if k.is_odd() {
+            let mut d = k.mod_floor(&modulus).to_i64().unwrap();
+            if d >= half {
+                d -= 1 << w;
+            }
+            k -= d;
+            d
+        } else {
+            0
+        };
+        digits.push(d);
+        k >>= 1;
+    }
+    digits
+}
+
+// odd digits below 2^w, least significant first, each followed by at least
+// w - 1 zeros: the sliding window split of n >= 0 taken from the right
+fn window_digits(n: &BigInt, w: usize) -> Vec<i64> {
+    let modulus = BigInt::from(1u64 << w);
+    let mut k = n.clone();
+    let mut digits = Vec::new();
+    while k.is_positive() {
+        let d = if k.is_odd() {
+            let d = k.mod_floor(&modulus).to_i64().unwrap();
+            k -= d;
+            d
+        } else {
+            0
+        };
+        digits.push(d);
+        k >>= 1;
+    }
+    digits
+}
+
+// g, g^3, ..., g^(2^k - 1)
+fn odd_powers<G: GroupElement>(g: &G, k: usize) -> Vec<G> {
+    let g2 = g.mul(g);
+    let mut table = vec![g.clone()];
+    for i in 1..1usize << (k - 1) {
+        table.push(table[i - 1].mul(&g2));
+    }
+    table
+}
+
+// left to right over the width w NAF of n, worth it when inverses are cheap
+pub fn wnaf_power<G>(g: G, n: &BigInt, w: usize) -> Result<G, Error>
+where
+    G: GroupElement,
+{
+    if !(2..=MAX_WINDOW).contains(&w) {
+        return Err(Error::InvalidArgument("window width must be in 2..=16"));
+    }
+    Ok(wnaf_power_unchecked(g, n, w))
+}
+
+fn wnaf_power_unchecked<G>(g: G, n: &BigInt, w: usize) -> G
+where
+    G: GroupElement,
+{
+    let z = if n.is_negative() { g.inverse() } else { g };
+    let table = odd_powers(&z, w - 1);
+    let inverses: Vec<G> = table.iter().map(|t| t.inverse()).collect();
+
+    let mut y = G::identity();
+    for &d in wnaf_digits(&n.abs(), w).iter().rev() {
+        y = y.mul(&y);
+        if d > 0 {
+            y = y.mul(&table[(d / 2) as usize]);
+        } else if d < 0 {
+            y = y.mul(&inverses[(-d / 2) as usize]);
+        }
+    }
+    y
+}
+
+// left to right, each maximal run of at most w bits that starts and ends
+// with a one costing a single multiplication by an odd power
+pub fn sliding_window_power<G>(g: G, n: &BigInt, w: usize) -> Result<G, Error>
+where
+    G: GroupElement,
+{
+    if !(1..=MAX_WINDOW).contains(&w) {
+        return Err(Error::InvalidArgument("window width must be in 1..=16"));
+    }
+    Ok(sliding_window_power_unchecked(g, n, w))
+}
+
+fn sliding_window_power_unchecked<G>(g: G, n: &BigInt, w: usize) -> G
+where
+    G: GroupElement,
+{
+    let z = if n.is_negative() { g.inverse() } else { g };
+    let n = n.abs();
+    let table = odd_powers(&z, w);
+
+    let mut y = G::identity();
+    let mut i = n.bits() as i64 - 1;
+    while i >= 0 {
+        if !n.bit(i as u64) {
+            y = y.mul(&y);
+            i -= 1;
+            continue;
+        }
+        // the window n_i .. n_j with n_j = 1
+        let mut j = (i - w as i64 + 1).max(0);
+        while !n.bit(j as u64) {
+            j += 1;
+        }
+        let mut u = 0usize;
+        for b in (j..=i).rev() {
+            y = y.mul(&y);
+            u = 2 * u + n.bit(b as u64) as usize;
+        }
+        y = y.mul(&table[u / 2]);
+        i = j - 1;
+    }
+    y
+}
+
+// the Montgomery ladder keeps r1 = r0 g and does one multiplication and one
+// squaring per bit whatever its value, so the sequence of group operations
+// depends only on the bit length of n
+pub fn montgomery_ladder_power<G>(g: G, n: &BigInt) -> G
+where
+    G: GroupElement,
+{
+    let z = if n.is_negative() { g.inverse() } else { g };
+    let n = n.abs();
+    let mut r0 = G::identity();
+    let mut r1 = z;
+    for i in (0..n.bits()).rev() {
+        if n.bit(i) {
+            r0 = r0.mul(&r1);
+            r1 = r1.mul(&r1);
+        } else {
+            r1 = r0.mul(&r1);
+            r0 = r0.mul(&r0);
+        }
+    }
+    r0
+}
+
+// g^n by w-NAF when the group says inverses are cheap, by sliding windows
+// otherwise
+pub fn power<G>(g: G, n: &BigInt) -> G
+where
+    G: GroupElement,
+{
+    if n.is_zero() {
+        return G::identity();
+    }
+    let w = window(n);
+    if G::CHEAP_INVERSE {
+        wnaf_power_unchecked(g, n, w)
+    } else {
+        sliding_window_power_unchecked(g, n, w)
+    }
+}
+
+// prod g_i^n_i by Straus' interleaving: one shared chain of squarings, and
+// for every base its own table of odd powers and recoding of the exponent,
+// signed when inverses are cheap; with two bases and w = 1 this is Shamir's
+// trick
+pub fn multi_power_straus<G>(terms: &[(G, BigInt)]) -> G
+where
+    G: GroupElement,
+{
+    let mut tables = Vec::with_capacity(terms.len());
+    let mut digits = Vec::with_capacity(terms.len());
+    for (g, n) in terms {
+        let z = if n.is_negative() {
+            g.inverse()
+        } else {
+            g.clone()
+        };
+        let n = n.abs();
+        let w = window(&n).min(4);
+        let (table, d) = if G::CHEAP_INVERSE {
+            let table = odd_powers(&z, w - 1);
+            let inverses: Vec<G> = table.iter().map(|t| t.inverse()).collect();
+            ((table, inverses), wnaf_digits(&n, w))
+        } else {
+            ((odd_powers(&z, w), Vec::new()), window_digits(&n, w))
+        };
+        tables.push(table);
+        digits.push(d);
+    }
+
+    let len = digits.iter().map(|d| d.len()).max().unwrap_or(0);
+    let mut y = G::identity();
+    for i in (0..len).rev() {
+        y = y.mul(&y);
+        for (d, (table, inverses)) in digits.iter().zip(&tables) {
+            match d.get(i) {
+                Some(&d) if d > 0 => y = y.mul(&table[(d / 2) as usize]),
+                Some(&d) if d < 0 => y = y.mul(&inverses[(-d / 2) as usize]),
+                _ => {}
+            }
+        }
+    }
+    y
+}
+
+// prod g_i^n_i by Pippenger's bucket method: the exponents are cut into
+// c-bit windows, and within a window every base goes into the bucket of its
+// digit, after which sum_d d B_d costs only 2^(c+1) multiplications
+pub fn multi_power_pippenger<G>(terms: &[(G, BigInt)]) -> G
+where
+    G: GroupElement,
+{
+    let c = ((usize::BITS - terms.len().leading_zeros()) as usize)
+        .saturating_sub(2)
+        .clamp(2, 16);
+    let terms: Vec<(G, BigInt)> = terms
+        .iter()
+        .map(|(g, n)| {
+            if n.is_negative() {
+                (g.inverse(), -n)
+            } else {
+                (g.clone(), n.clone())
+            }
+        })
+        .collect();
+    let bits = terms.iter().map(|(_, n)| n.bits()).max().unwrap_or(0) as usize;
+    let windows = bits.div_ceil(c);
+
+    let mut y = G::identity();
+    for k in (0..windows).rev() {
+        for _ in 0..c {
+            y = y.mul(&y);
+        }
+        let mut buckets: Vec<Option<G>> = vec![None; 1 << c];
+        for (g, n) in &terms {
+            let d = ((n >> (k * c)) & BigInt::from((1u64 << c) - 1))
+                .to_usize()
+                .unwrap();
+            if d > 0 {
+                buckets[d] = Some(match &buckets[d] {
+                    Some(b) => b.mul(g),
+                    None => g.clone(),
+                });
+            }
+        }
+        // running = prod_{e >= d} B_e, and the product of the running values
+        // over d is prod_d B_d^d
+        let mut running = G::identity();
+        let mut sum = G::identity();
+        for b in buckets.iter().skip(1).rev() {
+            if let Some(b) = b {
+                running = running.mul(b);
+            }
+            sum = sum.mul(&running);
+        }
+        y = y.mul(&sum);
+    }
+    y
+}
+
+// prod g_i^n_i, by Straus for a few bases and by Pippenger for many
+pub fn multi_power<G>(terms: &[(G, BigInt)]) -> G
+where
+    G: GroupElement,
+{
+    if terms.len() < PIPPENGER_THRESHOLD {
+        multi_power_straus(terms)
+    } else {
+        multi_power_pippenger(terms)
+    }
+}
+
+// g^n for many n and a fixed g by the Lim-Lee comb: n < 2^bits is read as an
+// h x a array of bits, and column c contributes G[j]^(2^c) with j its bits and
+// G[j] = prod_{i in j} g^(2^(i a)); splitting the a columns into v blocks of b
+// with a table G^(2^(k b)) each leaves b squarings and a multiplications per
+// power for v 2^h stored elements
+pub struct FixedBase<G> {
+    g: G,
+    bits: usize,
+    h: usize,
+    a: usize,
+    b: usize,
+    tables: Vec<Vec<G>>,
+}
+
+impl<G: GroupElement> FixedBase<G> {
+    // tables for exponents of up to bits bits, with a default trade-off
+    pub fn new(g: G, bits: usize) -> FixedBase<G> {
+        let h = match bits {
+            0..=64 => 4,
+            65..=512 => 6,
+            _ => 8,
+        };
+        FixedBase::build(g, bits, h, 2)
+    }
+
+    // h rows and v tables: larger h and v cost memory and save time
+    pub fn with_params(g: G, bits: usize, h: usize, v: usize) -> Result<FixedBase<G>, Error> {
+        if !(1..24).contains(&h) {
+            return Err(Error::InvalidArgument("rows must be in 1..24"));
+        }
+        if v == 0 {
+            return Err(Error::InvalidArgument("number of tables must be positive"));
+        }
+        Ok(FixedBase::build(g, bits, h, v))
+    }
+
+    fn build(g: G, bits: usize, h: usize, v: usize) -> FixedBase<G> {
+        let bits = bits.max(1);
+        let a = bits.div_ceil(h);
+        let b = a.div_ceil(v);
+
+        // g^(2^(i a)) for i < h
+        let mut rows = vec![g.clone()];
+        for i in 1..h {
+            let mut x = rows[i - 1].clone();
+            for _ in 0..a {
+                x = x.mul(&x);
+            }
+            rows.push(x);
+        }
+        let mut first = vec![G::identity()];
+        for j in 1..1usize << h {
+            let top = usize::BITS - 1 - j.leading_zeros();
+            first.push(first[j ^ (1 << top)].mul(&rows[top as usize]));
+        }
+
+        let mut tables = vec![first];
+        for k in 1..v {
+            let next = tables[k - 1]
+                .iter()
+                .map(|x| {
+                    let mut x = x.clone();
+                    for _ in 0..b {
+                        x = x.mul(&x);
+                    }
+                    x
+                })
+                .collect();
+            tables.push(next);
+        }
+        FixedBase {
+            g,
+            bits,
+            h,
+            a,
+            b,
+            tables,
+        }
+    }
+
+    pub fn power(&self, n: &BigInt) -> G {
+        if n.is_negative() {
+            return self.power(&-n).inverse();
+        }
+        // exponents beyond the tables fall back to plain powering
+        if n.bits() as usize > self.bits {
+            return power(self.g.clone(), n);
+        }
+        let mut y = G::identity();
+        for t in (0..self.b).rev() {
+            y = y.mul(&y);
+            for (k, table) in self.tables.iter().enumerate().rev() {
+                let c = k * self.b + t;
+                if c >= self.a {
+                    continue;
+                }
+                let j = (0..self.h)
+                    .filter(|&i| n.bit((i * self.a + c) as u64))
+                    .fold(0usize, |j, i| j | 1 << i);
+                if j > 0 {
+                    y = y.mul(&table[j]);
+                }
+            }
+        }
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fp, FpContext};
+    use num_traits::One;
+
+    #[test]
+    fn windowed_powers_agree_with_modpow() {
+        let p = BigInt::from(1_000_003);
+        let ctx = FpContext::new(&p);
+        let g = ctx.elem(&BigInt::from(5));
+        for e in [0i64, 1, 2, 255, 65_537, 999_999_999_999, -12_345] {
+            let n = BigInt::from(e);
+            let expected = ctx.elem(&BigInt::from(5).modpow(&n.mod_floor(&(&p - 1u32)), &p));
+            for w in 2..=MAX_WINDOW {
+                assert_eq!(wnaf_power(g.clone(), &n, w).unwrap(), expected);
+                assert_eq!(sliding_window_power(g.clone(), &n, w).unwrap(), expected);
+            }
+            assert_eq!(montgomery_ladder_power(g.clone(), &n), expected);
+            assert_eq!(power(g.clone(), &n), expected);
+        }
+    }
+
+    #[test]
+    fn wide_windows_are_rejected() {
+        let ctx = FpContext::new(&BigInt::from(101));
+        let g = ctx.elem(&BigInt::from(2));
+        let n = BigInt::from(77);
+        assert!(wnaf(&n, MAX_WINDOW + 1).is_err());
+        assert!(wnaf_power(g.clone(), &n, 32).is_err());
+        assert!(sliding_window_power(g, &n, 32).is_err());
+    }
+
+    // Z / 1000003 under addition, so that the signed recodings are taken
+    #[derive(Clone, Debug, PartialEq)]
+    struct Additive(i64);
+
+    const M: i64 = 1_000_003;
+
+    impl GroupElement for Additive {
+        const CHEAP_INVERSE: bool = true;
+
+        fn identity() -> Self {
+            Additive(0)
+        }
+
+        fn mul(&self, other: &Self) -> Self {
+            Additive((self.0 + other.0) % M)
+        }
+
+        fn inverse(&self) -> Self {
+            Additive((M - self.0) % M)
+        }
+    }
+
+    // exponents of up to about 200 bits of either sign, every seventh zero
+    fn exponents(count: usize) -> Vec<BigInt> {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        (0..count)
+            .map(|i| {
+                let mut n = BigInt::zero();
+                for _ in 0..i % 4 + 1 {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    n = (n << 50u32) + (state >> 14);
+                }
+                match i % 7 {
+                    0 => BigInt::zero(),
+                    1 | 4 => -n,
+                    _ => n,
+                }
+            })
+            .collect()
+    }
+
+    fn check_multi_power<G: GroupElement + std::fmt::Debug>(bases: &[G]) {
+        for count in [0, 1, 2, 3, 31, 32, 33, 100] {
+            let terms: Vec<(G, BigInt)> = bases
+                .iter()
+                .cycle()
+                .cloned()
+                .zip(exponents(count))
+                .collect();
+            let expected = terms
+                .iter()
+                .fold(G::identity(), |y, (g, n)| y.mul(&power(g.clone(), n)));
+            assert_eq!(multi_power(&terms), expected, "{} terms", count);
+            assert_eq!(multi_power_straus(&terms), expected, "{} terms", count);
+            assert_eq!(multi_power_pippenger(&terms), expected, "{} terms", count);
+        }
+    }
+
+    #[test]
+    fn multi_powers_agree_with_products_of_powers() {
+        let ctx = FpContext::new(&BigInt::from(1_000_000_007));
+        let bases: Vec<Fp> = [2, 3, 5, 7, 999_999_999, 123_456_789, 1]
+            .iter()
+            .map(|&a| ctx.elem(&BigInt::from(a)))
+            .collect();
+        check_multi_power(&bases);
+        let bases: Vec<Additive> = [1, 2, 17, 999_999, 424_242, 0]
+            .iter()
+            .map(|&a| Additive(a))
+            .collect();
+        check_multi_power(&bases);
+    }
+
+    #[test]
+    fn fixed_base_agrees_with_power() {
+        let ctx = FpContext::new(&BigInt::from(1_000_000_007));
+        let g = ctx.elem(&BigInt::from(5));
+        let mut tables = vec![
+            FixedBase::new(g.clone(), 1),
+            FixedBase::new(g.clone(), 64),
+            FixedBase::new(g.clone(), 200),
+            FixedBase::new(g.clone(), 1_000),
+        ];
+        for (bits, h, v) in [
+            (1, 1, 1),
+            (60, 1, 1),
+            (60, 3, 2),
+            (64, 4, 4),
+            (100, 5, 3),
+            (200, 7, 1),
+            (200, 2, 50),
+            (37, 12, 3),
+        ] {
+            tables.push(FixedBase::with_params(g.clone(), bits, h, v).unwrap());
+        }
+        let mut ns = exponents(40);
+        ns.extend([1, -1, 2, -2, 1_000_000_006].map(BigInt::from));
+        ns.push(BigInt::one() << 999u32);
+        ns.push(-(BigInt::one() << 1_000u32) + 1u32);
+        for table in &tables {
+            for n in &ns {
+                // those past table.bits take the fallback
+                assert_eq!(table.power(n), power(g.clone(), n), "n = {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_base_parameters_are_checked() {
+        let ctx = FpContext::new(&BigInt::from(101));
+        let g = ctx.elem(&BigInt::from(2));
+        for (h, v) in [(0, 1), (24, 1), (100, 2), (4, 0)] {
+            assert!(matches!(
+                FixedBase::with_params(g.clone(), 64, h, v),
+                Err(Error::InvalidArgument(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::arith::int_sqrt;
+use crate::kronecker_binary;
+
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+// the first twelve primes are a complete set of witnesses for every n < 2^64
+const DETERMINISTIC_BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+// strong Fermat test to base a, n odd and > 2
+pub fn is_strong_probable_prime(n: &BigInt, a: &BigInt) -> bool {
+    let n_minus_1 = n - 1u32;
+    let a = a.mod_floor(n);
+    if a.is_zero() || a.is_one() || a == n_minus_1 {
+        return true;
+    }
+    let s = n_minus_1.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_1 >> s;
+
+    let mut b = a.modpow(&d, n);
+    if b.is_one() || b == n_minus_1 {
+        return true;
+    }
+    for _ in 1..s {
+        b = (&b * &b) % n;
+        if b == n_minus_1 {
+            return true;
+        }
+        if b.is_one() {
+            return false;
+        }
+    }
+    false
+}
+
+// deterministic below 2^64, otherwise a strong test to base 2 and then
+// `rounds` more to pseudo-random bases, at least one as base 2 alone passes
+// every composite Mersenne number
+pub fn miller_rabin(n: &BigInt, rounds: usize) -> bool {
+    if let Some(small) = trial_division(n) {
+        return small;
+    }
+    if n.bits() <= 64 {
+        return DETERMINISTIC_BASES
+            .iter()
+            .all(|&a| is_strong_probable_prime(n, &BigInt::from(a)));
+    }
+
+    if !is_strong_probable_prime(n, &BigInt::from(2)) {
+        return false;
+    }
+    // splitmix64 seeded from n, so the result is reproducible
+    let mut state = n.iter_u64_digits().next().unwrap_or(0);
+    let range = n - 3u32;
+    (0..rounds.max(1)).all(|_| {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        let a = BigInt::from(z).mod_floor(&range) + 2u32;
+        is_strong_probable_prime(n, &a)
+    })
+}
+
+// strong Lucas test with Selfridge's parameters: D the first of 5, -7, 9, -11, ...
+// with (D/n) = -1, P = 1, Q = (1 - D) / 4
+pub fn is_strong_lucas_probable_prime(n: &BigInt) -> bool {
+    if let Some(small) = trial_division(n) {
+        return small;
+    }
+    // no suitable D exists for squares
+    let r = int_sqrt(n);
+    if &r * &r == *n {
+        return false;
+    }
+
+    let mut d = BigInt::from(5);
+    loop {
+        let j = kronecker_binary(d.clone(), n.clone());
+        if j == -1 {
+            break;
+        }
+        if j == 0 && d.abs() != *n {
+            return false;
+        }
+        d = if d.is_positive() { -(d + 2u32) } else { -d + 2u32 };
+    }
+    let p = BigInt::one();
+    let q: BigInt = (BigInt::one() - &d) / 4;
+
+    let n_plus_1 = n + 1u32;
+    let s = n_plus_1.trailing_zeros().unwrap_or(0);
+    let k = &n_plus_1 >> s;
+
+    let half = |x: BigInt| -> BigInt {
+        let x = if x.is_odd() { x + n } else { x };
+        (x >> 1u32).mod_floor(n)
+    };
+
+    // left-to-right computation of U_k, V_k and Q^k
+    let mut u = BigInt::one();
+    let mut v = p.clone();
+    let mut qk = q.mod_floor(n);
+    for i in (0..k.bits() - 1).rev() {
+        u = (&u * &v).mod_floor(n);
+        v = (&v * &v - &qk - &qk).mod_floor(n);
+        qk = (&qk * &qk).mod_floor(n);
+        if k.bit(i) {
+            let u2 = half(&p * &u + &v);
+            v = half(&d * &u + &p * &v);
+            u = u2;
+            qk = (&qk * &q).mod_floor(n);
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = (&v * &v - &qk - &qk).mod_floor(n);
+        if v.is_zero() {
+            return true;
+        }
+        qk = (&qk * &qk).mod_floor(n);
+    }
+    false
+}
+
+// Baillie-PSW: no known counterexample, and none exists below 2^64
+pub fn is_probable_prime(n: &BigInt) -> bool {
+    if let Some(small) = trial_division(n) {
+        return small;
+    }
+    is_strong_probable_prime(n, &BigInt::from(2)) && is_strong_lucas_probable_prime(n)
+}
+
+// Some(answer) when small primes decide n, None when n needs a real test
+fn trial_division(n: &BigInt) -> Option<bool> {
+    if *n < BigInt::from(2) {
+        return Some(false);
+    }
+    for &p in SMALL_PRIMES.iter() {
+        if (n % p).is_zero() {
+            return Some(n.to_u32() == Some(p));
+        }
+    }
+    let last = SMALL_PRIMES[SMALL_PRIMES.len() - 1];
+    if *n < BigInt::from(last * last) {
+        return Some(true);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime_brute(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn small_numbers_match_trial_division() {
+        for n in 0..20_000u64 {
+            let big = BigInt::from(n);
+            let expected = is_prime_brute(n);
+            assert_eq!(is_probable_prime(&big), expected, "{}", n);
+            assert_eq!(miller_rabin(&big, 1), expected, "{}", n);
+        }
+    }
+
+    #[test]
+    fn strong_pseudoprimes_are_composite() {
+        // strong pseudoprimes to base 2, to bases 2, 3, 5, 7, and to every
+        // prime base up to 37 but 29, 31 and 37
+        for n in [2047u64, 3_215_031_751, 3_825_123_056_546_413_051] {
+            let big = BigInt::from(n);
+            assert!(is_strong_probable_prime(&big, &BigInt::from(2)));
+            assert!(!miller_rabin(&big, 0));
+            assert!(!is_probable_prime(&big));
+        }
+        assert!(is_strong_probable_prime(
+            &BigInt::from(3_215_031_751u64),
+            &BigInt::from(7)
+        ));
+        // strong Lucas pseudoprimes with no factor below 100, caught by the
+        // base 2 test
+        for n in [22_499u64, 25_199, 40_309, 58_519] {
+            let big = BigInt::from(n);
+            assert!(is_strong_lucas_probable_prime(&big));
+            assert!(!is_probable_prime(&big));
+        }
+    }
+
+    #[test]
+    fn large_numbers_with_zero_rounds() {
+        let m61 = (BigInt::one() << 61u32) - 1u32;
+        let m89 = (BigInt::one() << 89u32) - 1u32;
+        let m127 = (BigInt::one() << 127u32) - 1u32;
+        assert!(miller_rabin(&m89, 0));
+        assert!(miller_rabin(&m127, 5));
+        assert!(is_probable_prime(&m127));
+        let composite = &m61 * &m89;
+        assert!(!miller_rabin(&composite, 0));
+        assert!(!is_probable_prime(&composite));
+        // 2^67 - 1 = 193707721 * 761838257287
+        assert!(!miller_rabin(&((BigInt::one() << 67u32) - 1u32), 0));
+    }
+}
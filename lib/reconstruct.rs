@@ -0,0 +1,305 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use crate::arith::mod_inv;
+use crate::chinese_remainder_inductive;
+use crate::error::Error;
+use crate::matrix::Matrix;
+use crate::sieve::next_prime;
+
+// the multi-modular drivers use primes from here upwards
+const FIRST_PRIME_BITS: u64 = 62;
+
+// the drivers give up after this many primes in a row that the caller rejects
+const MAX_UNLUCKY: usize = 64;
+
+// a / b with a = b u (mod m), |a| <= n and 0 < b <= d, found by running the
+// extended Euclid algorithm on m and u until the remainder drops to n: when
+// 2 n d < m there is at most one such fraction in lowest terms, and if it
+// exists it is this one
+pub fn rational_reconstruction_bounded(
+    u: &BigInt,
+    m: &BigInt,
+    n: &BigInt,
+    d: &BigInt,
+) -> Result<Option<BigRational>, Error> {
+    if !m.is_positive() {
+        return Err(Error::InvalidArgument("modulus must be positive"));
+    }
+    if n.is_negative() || !d.is_positive() || 2 * n * d >= *m {
+        return Err(Error::InvalidArgument("bounds must satisfy 2 n d < m"));
+    }
+
+    // r_i = t_i u (mod m) throughout
+    let (mut r0, mut r1) = (m.clone(), u.mod_floor(m));
+    let (mut t0, mut t1) = (BigInt::zero(), BigInt::one());
+    while r1 > *n {
+        let (q, r) = r0.div_rem(&r1);
+        let t = &t0 - &q * &t1;
+        r0 = std::mem::replace(&mut r1, r);
+        t0 = std::mem::replace(&mut t1, t);
+    }
+    if t1.is_zero() || t1.abs() > *d || !r1.gcd(&t1).is_one() || !t1.gcd(m).is_one() {
+        return Ok(None);
+    }
+    if t1.is_negative() {
+        r1 = -r1;
+        t1 = -t1;
+    }
+    Ok(Some(BigRational::new_raw(r1, t1)))
+}
+
+// as rational_reconstruction_bounded with n = d = floor(sqrt((m - 1) / 2)),
+// the balanced choice when nothing is known about the sizes of a and b
+pub fn rational_reconstruction(u: &BigInt, m: &BigInt) -> Result<Option<BigRational>, Error> {
+    if !m.is_positive() {
+        return Err(Error::InvalidArgument("modulus must be positive"));
+    }
+    let n = ((m - 1u32) / 2u32).sqrt();
+    if n.is_zero() {
+        return Ok(None);
+    }
+    rational_reconstruction_bounded(u, m, &n, &n)
+}
+
+// every entry with the balanced bounds n = d = floor(sqrt((m - 1) / 2)),
+// carrying the lcm l of the denominators found so far: once l holds most of
+// the common denominator, u_i l (mod m) is a fraction with denominator at
+// most d / l and is found with that bound and the numerator bound it allows;
+// the result stands only if it also meets the balanced bounds, and otherwise
+// u_i is reconstructed on its own
+pub fn rational_reconstruction_vector(
+    us: &[BigInt],
+    m: &BigInt,
+) -> Result<Option<Vec<BigRational>>, Error> {
+    if !m.is_positive() {
+        return Err(Error::InvalidArgument("modulus must be positive"));
+    }
+    let n = ((m - 1u32) / 2u32).sqrt();
+    let mut l = BigInt::one();
+    let mut result = Vec::with_capacity(us.len());
+    for u in us {
+        let mut x = None;
+        let dl = &n / &l;
+        if dl.is_positive() {
+            let nl = (m - 1u32) / (2 * &dl);
+            if let Some(y) = rational_reconstruction_bounded(&(u * &l), m, &nl, &dl)? {
+                let y = y / BigRational::from_integer(l.clone());
+                if y.numer().abs() <= n && *y.denom() <= n {
+                    x = Some(y);
+                }
+            }
+        }
+        if x.is_none() {
+            x = rational_reconstruction(u, m)?;
+        }
+        match x {
+            Some(x) => {
+                l = l.lcm(x.denom());
+                result.push(x);
+            }
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(result))
+}
+
+// entry by entry, as rational_reconstruction_vector over the rows in order
+pub fn rational_reconstruction_matrix(
+    a: &Matrix<BigInt>,
+    m: &BigInt,
+) -> Result<Option<Matrix<BigRational>>, Error> {
+    let (rows, cols) = a.shape();
+    let entries: Vec<BigInt> = a.rows().flat_map(|row| row.iter().cloned()).collect();
+    match rational_reconstruction_vector(&entries, m)? {
+        Some(data) => Matrix::from_vec(rows, cols, data).map(Some),
+        None => Ok(None),
+    }
+}
+
+// x mod p for a rational x whose denominator p does not divide
+fn reduce(x: &BigRational, p: &BigInt) -> Option<BigInt> {
+    let inv = mod_inv(&x.denom().mod_floor(p), p)?;
+    Some((x.numer() * inv).mod_floor(p))
+}
+
+// residue vectors modulo one prime after another, from f(p), which returns
+// None for primes that are unlucky for the computation
+struct Residues<F> {
+    f: F,
+    p: BigInt,
+    len: Option<usize>,
+}
+
+impl<F> Residues<F>
+where
+    F: FnMut(&BigInt) -> Option<Vec<BigInt>>,
+{
+    fn new(f: F) -> Residues<F> {
+        Residues {
+            f,
+            p: BigInt::one() << FIRST_PRIME_BITS,
+            len: None,
+        }
+    }
+
+    fn next(&mut self) -> Result<(BigInt, Vec<BigInt>), Error> {
+        for _ in 0..MAX_UNLUCKY {
+            self.p = next_prime(&self.p);
+            let Some(xs) = (self.f)(&self.p) else {
+                continue;
+            };
+            if *self.len.get_or_insert(xs.len()) != xs.len() {
+                return Err(Error::DimensionMismatch);
+            }
+            return Ok((self.p.clone(), xs));
+        }
+        Err(Error::NoConvergence)
+    }
+}
+
+// x_i = y_i (mod p) on top of x_i (mod m), by chinese_remainder_inductive
+fn combine(m: &BigInt, xs: &[BigInt], p: &BigInt, ys: &[BigInt]) -> Result<Vec<BigInt>, Error> {
+    xs.iter()
+        .zip(ys)
+        .map(|(x, y)| chinese_remainder_inductive(&[m.clone(), p.clone()], &[x.clone(), y.clone()]))
+        .collect()
+}
+
+// a vector of rationals from its images modulo primes, f(p) giving the
+// images modulo p or None when p is unlucky: primes are added until the
+// reconstruction from the ones so far also agrees modulo a fresh prime
+pub fn multimodular_rational<F>(f: F) -> Result<Vec<BigRational>, Error>
+where
+    F: FnMut(&BigInt) -> Option<Vec<BigInt>>,
+{
+    let mut residues = Residues::new(f);
+    let (mut m, mut xs) = residues.next()?;
+    let mut candidate = rational_reconstruction_vector(&xs, &m)?;
+    loop {
+        let (p, ys) = residues.next()?;
+        if let Some(c) = &candidate {
+            if c.iter()
+                .zip(&ys)
+                .all(|(x, y)| reduce(x, &p) == Some(y.mod_floor(&p)))
+            {
+                return Ok(candidate.unwrap());
+            }
+        }
+        xs = combine(&m, &xs, &p, &ys)?;
+        m *= p;
+        candidate = rational_reconstruction_vector(&xs, &m)?;
+    }
+}
+
+// a vector of integers from its images modulo primes, as
+// multimodular_rational: the symmetric lifts stop once a fresh prime no
+// longer changes them
+pub fn multimodular_integer<F>(f: F) -> Result<Vec<BigInt>, Error>
+where
+    F: FnMut(&BigInt) -> Option<Vec<BigInt>>,
+{
+    let symmetric = |x: &BigInt, m: &BigInt| if x * 2u32 > *m { x - m } else { x.clone() };
+    let mut residues = Residues::new(f);
+    let (mut m, mut xs) = residues.next()?;
+    loop {
+        let (p, ys) = residues.next()?;
+        let candidate: Vec<BigInt> = xs.iter().map(|x| symmetric(x, &m)).collect();
+        if candidate
+            .iter()
+            .zip(&ys)
+            .all(|(x, y)| x.mod_floor(&p) == y.mod_floor(&p))
+        {
+            return Ok(candidate);
+        }
+        xs = combine(&m, &xs, &p, &ys)?;
+        m *= p;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(p: i64, q: i64) -> BigRational {
+        BigRational::new(BigInt::from(p), BigInt::from(q))
+    }
+
+    // the fraction a / b in lowest terms with |a| <= n, 0 < b <= d, b prime
+    // to m and a = b u (mod m), found by enumeration
+    fn brute(u: i64, m: i64, n: i64, d: i64) -> Option<BigRational> {
+        (1..=d)
+            .filter(|b| b.gcd(&m) == 1)
+            .flat_map(|b| (-n..=n).map(move |a| (a, b)))
+            .find(|&(a, b)| a.gcd(&b) == 1 && (a - b * u).rem_euclid(m) == 0)
+            .map(|(a, b)| ratio(a, b))
+    }
+
+    fn int_sqrt_i64(x: i64) -> i64 {
+        (0..).take_while(|r| r * r <= x).last().unwrap()
+    }
+
+    #[test]
+    fn scalar_reconstruction_matches_enumeration() {
+        for m in [1_009i64, 1_024, 2_310] {
+            let n = int_sqrt_i64((m - 1) / 2);
+            for u in 0..m {
+                let (bu, bm) = (BigInt::from(u), BigInt::from(m));
+                assert_eq!(
+                    rational_reconstruction(&bu, &bm),
+                    Ok(brute(u, m, n, n)),
+                    "{} mod {}",
+                    u,
+                    m
+                );
+            }
+            for u in (0..m).step_by(7) {
+                let (n, d) = (3, (m - 1) / 8);
+                let got = rational_reconstruction_bounded(
+                    &BigInt::from(u),
+                    &BigInt::from(m),
+                    &BigInt::from(n),
+                    &BigInt::from(d),
+                );
+                assert_eq!(got, Ok(brute(u, m, n, d)));
+            }
+        }
+    }
+
+    #[test]
+    fn vectors_and_matrices_share_a_denominator() {
+        let m = next_prime(&(BigInt::one() << 80u32));
+        let xs = [
+            ratio(1, 3),
+            ratio(-7, 3),
+            ratio(5, 12),
+            ratio(1_000_001, 999_983),
+        ];
+        let us: Vec<BigInt> = xs.iter().map(|x| reduce(x, &m).unwrap()).collect();
+        assert_eq!(
+            rational_reconstruction_vector(&us, &m),
+            Ok(Some(xs.to_vec()))
+        );
+        let a = Matrix::from_vec(2, 2, us).unwrap();
+        let expected = Matrix::from_vec(2, 2, xs.to_vec()).unwrap();
+        assert_eq!(rational_reconstruction_matrix(&a, &m), Ok(Some(expected)));
+    }
+
+    #[test]
+    fn multimodular_drivers_recover_large_values() {
+        let big = BigInt::one() << 200u32;
+        let xs = vec![
+            BigRational::new(&big + 1u32, BigInt::from(3)),
+            BigRational::new(-BigInt::from(7), &big - 1u32),
+            BigRational::from_integer(BigInt::zero()),
+        ];
+        let found = multimodular_rational(|p| xs.iter().map(|x| reduce(x, p)).collect());
+        assert_eq!(found, Ok(xs));
+
+        let ns = vec![-(&big * &big), big.clone(), BigInt::from(5)];
+        let found = multimodular_integer(|p| Some(ns.iter().map(|x| x.mod_floor(p)).collect()));
+        assert_eq!(found, Ok(ns));
+    }
+}
@@ -0,0 +1,101 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use crate::poly::Poly;
+
+pub trait Ring:
+    Clone
+    + PartialEq
+    + Zero
+    + One
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+}
+
+pub trait EuclideanDomain: Ring {
+    fn quo_rem(&self, other: &Self) -> (Self, Self);
+
+    // unit u such that self * u is the canonical associate of self
+    fn normal_unit(&self) -> Self;
+
+    fn normalize(&self) -> Self {
+        self.clone() * self.normal_unit()
+    }
+}
+
+pub trait Field: EuclideanDomain + Div<Output = Self> {
+    fn inv(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(Self::one() / self.clone())
+        }
+    }
+}
+
+macro_rules! impl_integer_ring {
+    ($($t:ty),*) => {
+        $(
+            impl Ring for $t {}
+
+            impl EuclideanDomain for $t {
+                fn quo_rem(&self, other: &Self) -> (Self, Self) {
+                    self.div_mod_floor(other)
+                }
+
+                fn normal_unit(&self) -> Self {
+                    if self.is_negative() {
+                        -<$t>::one()
+                    } else {
+                        <$t>::one()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_ring!(i8, i16, i32, i64, i128, isize, BigInt);
+
+// floating point matrices (Cholesky, Fincke-Pohst) only need ring arithmetic
+impl Ring for f64 {}
+
+impl Ring for BigRational {}
+
+impl EuclideanDomain for BigRational {
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        (self / other, BigRational::zero())
+    }
+
+    fn normal_unit(&self) -> Self {
+        if self.is_zero() {
+            BigRational::one()
+        } else {
+            self.recip()
+        }
+    }
+}
+
+impl Field for BigRational {}
+
+impl<R: Ring> Ring for Poly<R> {}
+
+impl<F: Field> EuclideanDomain for Poly<F> {
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        crate::euclidean_division(self.clone(), other.clone()).expect("division by zero polynomial")
+    }
+
+    fn normal_unit(&self) -> Self {
+        match self.leading_coeff().inv() {
+            Some(inv) => Poly::constant(inv),
+            None => Poly::one(),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::arith::mod_inv;
+use crate::crt::chinese_remainder_general;
+use crate::error::Error;
+use crate::factor::factor;
+use crate::poly::Poly;
+use crate::primality::is_probable_prime;
+use crate::roots_mod_p;
+
+// below this every residue is tried instead of splitting over F_p
+const SMALL_PRIME: u64 = 64;
+
+// the roots of a polynomial modulo n as disjoint residue classes: (r, m) with
+// m | n stands for the n / m roots x = r (mod m), a node of the p-adic tree
+// below which every branch is a root, so that x^2 modulo p^k need not list
+// all p^(k/2) of its roots
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModularRoots {
+    modulus: BigInt,
+    classes: Vec<(BigInt, BigInt)>,
+}
+
+impl ModularRoots {
+    pub fn modulus(&self) -> &BigInt {
+        &self.modulus
+    }
+
+    pub fn classes(&self) -> &[(BigInt, BigInt)] {
+        &self.classes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    // the number of roots modulo n
+    pub fn count(&self) -> BigInt {
+        self.classes.iter().map(|(_, m)| &self.modulus / m).sum()
+    }
+
+    pub fn contains(&self, x: &BigInt) -> bool {
+        self.classes.iter().any(|(r, m)| x.mod_floor(m) == *r)
+    }
+
+    // every root in [0, n), in increasing order
+    pub fn to_vec(&self) -> Vec<BigInt> {
+        let mut roots = Vec::new();
+        for (r, m) in &self.classes {
+            let mut x = r.clone();
+            while x < self.modulus {
+                roots.push(x.clone());
+                x += m;
+            }
+        }
+        roots.sort();
+        roots
+    }
+}
+
+// f(x) modulo q for f given by its coefficients
+fn eval_mod(f: &[BigInt], x: &BigInt, q: &BigInt) -> BigInt {
+    f.iter()
+        .rev()
+        .fold(BigInt::zero(), |acc, c| (acc * x + c).mod_floor(q))
+}
+
+fn derivative(f: &[BigInt]) -> Vec<BigInt> {
+    f.iter().enumerate().skip(1).map(|(i, c)| c * i).collect()
+}
+
+// the coefficients of f(r + p x) modulo q, by a Taylor shift and a scaling
+fn substitute(f: &[BigInt], r: &BigInt, p: &BigInt, q: &BigInt) -> Vec<BigInt> {
+    let mut g = f.to_vec();
+    for i in 0..g.len() {
+        for j in (i..g.len() - 1).rev() {
+            let t = &g[j + 1] * r;
+            g[j] = (&g[j] + t).mod_floor(q);
+        }
+    }
+    let mut s = BigInt::one();
+    for c in &mut g {
+        *c = (&*c * &s).mod_floor(q);
+        s *= p;
+    }
+    g
+}
+
+// the distinct roots modulo p of f, which is not zero modulo p
+fn roots_mod_prime(f: &[BigInt], p: &BigInt) -> Result<Vec<BigInt>, Error> {
+    if let Some(small) = p.to_u64().filter(|&p| p < SMALL_PRIME) {
+        return Ok((0..small)
+            .map(BigInt::from)
+            .filter(|x| eval_mod(f, x, p).is_zero())
+            .collect());
+    }
+    let mut roots = roots_mod_p(p.clone(), Poly::new(f.to_vec()).reduce_mod(p))?;
+    for r in &mut roots {
+        *r = r.mod_floor(p);
+    }
+    roots.sort();
+    roots.dedup();
+    Ok(roots)
+}
+
+// the classes (r, j), standing for x = r (mod p^j), of the roots of f modulo
+// p^k: a root r modulo p with f'(r) a unit lifts uniquely by Newton's
+// iteration, and otherwise f(r + p x) is divisible by p, which is taken out
+// before its roots are found in turn modulo a smaller power of p
+fn lift(f: &[BigInt], p: &BigInt, k: u32) -> Result<Vec<(BigInt, u32)>, Error> {
+    let q = p.pow(k);
+    let mut f: Vec<BigInt> = f.iter().map(|c| c.mod_floor(&q)).collect();
+    if f.iter().all(|c| c.is_zero()) {
+        return Ok(vec![(BigInt::zero(), 0)]);
+    }
+    let mut k = k;
+    while f.iter().all(|c| c.is_multiple_of(p)) {
+        for c in &mut f {
+            *c /= p;
+        }
+        k -= 1;
+    }
+    let q = p.pow(k);
+    let df = derivative(&f);
+
+    let mut classes = Vec::new();
+    for r in roots_mod_prime(&f, p)? {
+        if !eval_mod(&df, &r, p).is_zero() {
+            // each step doubles the precision
+            let mut x = r;
+            let mut j = 1;
+            while j < k {
+                j = (2 * j).min(k);
+                let pj = p.pow(j);
+                let inv = mod_inv(&eval_mod(&df, &x, &pj), &pj).ok_or(Error::NotPrime)?;
+                x = (&x - eval_mod(&f, &x, &pj) * inv).mod_floor(&pj);
+            }
+            classes.push((x, k));
+        } else {
+            let g = substitute(&f, &r, p, &q);
+            for (x, j) in lift(&g, p, k)? {
+                classes.push(((&r + p * x).mod_floor(&p.pow(j + 1)), j + 1));
+            }
+        }
+    }
+    Ok(classes)
+}
+
+// the roots of f modulo p^k, p prime
+pub fn roots_mod_prime_power(f: &Poly<BigInt>, p: &BigInt, k: u32) -> Result<ModularRoots, Error> {
+    if k == 0 {
+        return Err(Error::InvalidArgument("exponent must be positive"));
+    }
+    if !is_probable_prime(p) {
+        return Err(Error::NotPrime);
+    }
+    let classes = lift(f.coeffs(), p, k)?
+        .into_iter()
+        .map(|(r, j)| (r, p.pow(j)))
+        .collect();
+    Ok(ModularRoots {
+        modulus: p.pow(k),
+        classes,
+    })
+}
+
+// the roots of f modulo n, from those modulo each prime power of n combined
+// class by class by the Chinese remainder theorem
+pub fn roots_mod(f: &Poly<BigInt>, n: &BigInt) -> Result<ModularRoots, Error> {
+    if !n.is_positive() {
+        return Err(Error::InvalidArgument("modulus must be positive"));
+    }
+    roots_mod_factored(f, &factor(n))
+}
+
+// as roots_mod for n given by its factorization
+pub fn roots_mod_factored(f: &Poly<BigInt>, fac: &[(BigInt, u32)]) -> Result<ModularRoots, Error> {
+    let mut result = ModularRoots {
+        modulus: fac.iter().map(|(p, e)| p.pow(*e)).product(),
+        classes: vec![(BigInt::zero(), BigInt::one())],
+    };
+    for (p, e) in fac {
+        let local = roots_mod_prime_power(f, p, *e)?;
+        let mut classes = Vec::with_capacity(result.classes.len() * local.classes.len());
+        for (r, m) in &result.classes {
+            for (s, l) in &local.classes {
+                classes.push(chinese_remainder_general(
+                    &[m.clone(), l.clone()],
+                    &[r.clone(), s.clone()],
+                )?);
+            }
+        }
+        result.classes = classes;
+        if result.classes.is_empty() {
+            break;
+        }
+    }
+    result.classes.sort();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(c: &[i64]) -> Poly<BigInt> {
+        Poly::new(c.iter().map(|&x| BigInt::from(x)).collect())
+    }
+
+    fn brute(f: &Poly<BigInt>, n: i64) -> Vec<BigInt> {
+        let n = BigInt::from(n);
+        (0..)
+            .map(BigInt::from)
+            .take_while(|x| *x < n)
+            .filter(|x| eval_mod(f.coeffs(), x, &n).is_zero())
+            .collect()
+    }
+
+    fn check(f: &Poly<BigInt>, n: i64) {
+        let roots = roots_mod(f, &BigInt::from(n)).unwrap();
+        let expected = brute(f, n);
+        assert_eq!(roots.to_vec(), expected, "{:?} mod {}", f.coeffs(), n);
+        assert_eq!(roots.count(), BigInt::from(expected.len()));
+        for x in [0i64, 1, 5, n - 1] {
+            let x = BigInt::from(x);
+            assert_eq!(
+                roots.contains(&x),
+                expected.contains(&x.mod_floor(&n.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn roots_mod_matches_exhaustive_search() {
+        let polys = [
+            poly(&[0, 0, 1]),
+            poly(&[-1, 0, 1]),
+            poly(&[2, -3, 1]),
+            poly(&[0, 0, 0, 1]),
+            poly(&[-8, 0, 0, 1]),
+            poly(&[6, 11, 6, 1]),
+            poly(&[1, 1, 1, 1, 1]),
+            poly(&[12, 0, 0, 0, 0, 0, 1]),
+            poly(&[4]),
+            poly(&[0]),
+        ];
+        for f in &polys {
+            for n in 1..130 {
+                check(f, n);
+            }
+        }
+    }
+
+    #[test]
+    fn large_primes_and_their_powers() {
+        let f = poly(&[-2, 0, 1]);
+        for n in [
+            127i64,
+            127 * 127,
+            4 * 7 * 7 * 17 * 17,
+            2 * 3 * 5 * 7 * 11 * 13,
+        ] {
+            check(&f, n);
+        }
+        let f = poly(&[-1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        check(&f, 131 * 131);
+        assert_eq!(
+            roots_mod_prime_power(&f, &BigInt::from(15), 2),
+            Err(Error::NotPrime)
+        );
+    }
+
+    #[test]
+    fn repeated_roots_are_kept_as_classes() {
+        // x^2 has the p^(k/2) roots modulo p^k that are multiples of p^(k/2)
+        let roots = roots_mod_prime_power(&poly(&[0, 0, 1]), &BigInt::from(3), 40).unwrap();
+        assert_eq!(roots.count(), BigInt::from(3).pow(20u32));
+        assert_eq!(
+            roots.classes(),
+            &[(BigInt::zero(), BigInt::from(3).pow(20u32))]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::ops::Range;
+
+use num_bigint::BigInt;
+use num_integer::{Integer, Roots};
+
+use crate::primality::is_probable_prime;
+
+const SEGMENT: u64 = 1 << 16;
+
+// base primes are kept only up to this bound; segments beyond its square are
+// sieved by them and the survivors settled by BPSW, which is exact below 2^64
+const BASE_CAP: u64 = 1 << 24;
+
+// primes p <= n
+pub fn primes_up_to(n: u64) -> Vec<u64> {
+    primes_in_range(2..n.saturating_add(1)).collect()
+}
+
+// primes in lo..hi, sieved one segment at a time so memory stays
+// O(min(sqrt(hi), BASE_CAP))
+pub fn primes_in_range(range: Range<u64>) -> PrimeRange {
+    PrimeRange {
+        base: Vec::new(),
+        base_limit: 1,
+        lo: range.start.max(2),
+        hi: range.end,
+        segment: Vec::new(),
+        pos: 0,
+    }
+}
+
+pub struct PrimeRange {
+    // the primes up to base_limit, enough to sieve any segment below base_limit^2
+    base: Vec<u64>,
+    base_limit: u64,
+    lo: u64,
+    hi: u64,
+    segment: Vec<u64>,
+    pos: usize,
+}
+
+impl PrimeRange {
+    fn next_segment(&mut self) {
+        let lo = self.lo;
+        let hi = lo.saturating_add(SEGMENT).min(self.hi);
+        let root = (hi - 1).sqrt();
+        if root > self.base_limit && self.base_limit < BASE_CAP {
+            self.base_limit = root.max(2 * self.base_limit).min(BASE_CAP);
+            // the base primes come from a segmented sieve of their own,
+            // which in turn only needs the primes up to base_limit^(1/2)
+            self.base = primes_up_to(self.base_limit);
+        }
+
+        let mut composite = vec![false; (hi - lo) as usize];
+        for &p in &self.base {
+            if p > root {
+                break;
+            }
+            let start = lo.div_ceil(p).checked_mul(p).map(|j| j.max(p * p));
+            let mut j = match start {
+                Some(j) => j,
+                None => continue,
+            };
+            while j < hi {
+                composite[(j - lo) as usize] = true;
+                j = match j.checked_add(p) {
+                    Some(j) => j,
+                    None => break,
+                };
+            }
+        }
+        let complete = root <= self.base_limit;
+        self.segment = composite
+            .iter()
+            .enumerate()
+            .filter(|(_, &c)| !c)
+            .map(|(i, _)| lo + i as u64)
+            .filter(|&n| complete || is_probable_prime(&BigInt::from(n)))
+            .collect();
+        self.pos = 0;
+        self.lo = hi;
+    }
+}
+
+impl Iterator for PrimeRange {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.pos >= self.segment.len() {
+            if self.lo >= self.hi {
+                return None;
+            }
+            self.next_segment();
+        }
+        self.pos += 1;
+        Some(self.segment[self.pos - 1])
+    }
+}
+
+// smallest prime > n
+pub fn next_prime(n: &BigInt) -> BigInt {
+    if *n < BigInt::from(2) {
+        return BigInt::from(2);
+    }
+    let mut p = n + 1u32;
+    if p.is_even() && p != BigInt::from(2) {
+        p += 1u32;
+    }
+    while !is_probable_prime(&p) {
+        p += 2u32;
+    }
+    p
+}
+
+// largest prime < n
+pub fn prev_prime(n: &BigInt) -> Option<BigInt> {
+    if *n <= BigInt::from(3) {
+        return if *n == BigInt::from(3) {
+            Some(BigInt::from(2))
+        } else {
+            None
+        };
+    }
+    let mut p = n - 1u32;
+    if p.is_even() {
+        p -= 1u32;
+    }
+    while !is_probable_prime(&p) {
+        p -= 2u32;
+    }
+    Some(p)
+}
+
+// pi(n) by the Lucy Hedgehog recursion in O(n^(3/4)): S(v) counts the
+// integers in 2..=v left after sieving by the primes below p, and each prime
+// p removes S(v/p) - S(p-1) of them
+pub fn prime_pi(n: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    let r = n.sqrt() as usize;
+    // small[i] = S(i), large[i] = S(n / i)
+    let mut small: Vec<u64> = (0..=r as u64).map(|i| i.saturating_sub(1)).collect();
+    let mut large: Vec<u64> = (0..=r as u64)
+        .map(|i| n.checked_div(i).map_or(0, |v| v - 1))
+        .collect();
+
+    for p in 2..=r {
+        if small[p] == small[p - 1] {
+            continue;
+        }
+        let sp = small[p - 1];
+        let p2 = (p * p) as u64;
+        for i in 1..=r {
+            let v = n / i as u64;
+            if v < p2 {
+                break;
+            }
+            let s = if i * p <= r {
+                large[i * p]
+            } else {
+                small[(v / p as u64) as usize]
+            };
+            large[i] -= s - sp;
+        }
+        for v in (p * p..=r).rev() {
+            small[v] -= small[v / p] - sp;
+        }
+    }
+    large[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime_brute(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn sieve_matches_trial_division() {
+        let expected: Vec<u64> = (0..20_000).filter(|&n| is_prime_brute(n)).collect();
+        assert_eq!(primes_up_to(19_999), expected);
+        let window: Vec<u64> = (1_000_000..1_200_000)
+            .filter(|&n| is_prime_brute(n))
+            .collect();
+        assert_eq!(
+            primes_in_range(1_000_000..1_200_000).collect::<Vec<_>>(),
+            window
+        );
+        assert_eq!(prime_pi(19_999), expected.len() as u64);
+        assert_eq!(prime_pi(1_000_000), 78_498);
+    }
+
+    #[test]
+    fn ranges_near_the_top_of_u64() {
+        let top: Vec<u64> = primes_in_range(u64::MAX - 100..u64::MAX).collect();
+        // 2^64 - 59 is the largest 64-bit prime, 2^64 - 83 and 2^64 - 95 the
+        // ones below it
+        assert_eq!(top, vec![u64::MAX - 94, u64::MAX - 82, u64::MAX - 58]);
+        let wide: Vec<u64> = primes_in_range(u64::MAX - 200_000..u64::MAX).collect();
+        assert!(wide.iter().all(|&p| is_probable_prime(&BigInt::from(p))));
+        assert_eq!(wide.last(), Some(&(u64::MAX - 58)));
+    }
+
+    #[test]
+    fn next_and_prev_prime() {
+        assert_eq!(next_prime(&BigInt::from(0)), BigInt::from(2));
+        assert_eq!(next_prime(&BigInt::from(2)), BigInt::from(3));
+        assert_eq!(next_prime(&BigInt::from(7919)), BigInt::from(7927));
+        assert_eq!(prev_prime(&BigInt::from(2)), None);
+        assert_eq!(prev_prime(&BigInt::from(7927)), Some(BigInt::from(7919)));
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::primality::is_probable_prime;
+use crate::sieve::primes_up_to;
+use crate::{kernel_basis_mod2, kronecker_binary, sqrt_mod_prime};
+
+// (decimal digits, factor base size, sieve half-width M)
+const PARAMETERS: [(usize, usize, usize); 12] = [
+    (24, 100, 8_192),
+    (30, 200, 16_384),
+    (36, 400, 16_384),
+    (42, 600, 32_768),
+    (48, 1_000, 32_768),
+    (54, 1_600, 65_536),
+    (60, 2_400, 65_536),
+    (66, 3_600, 65_536),
+    (72, 5_000, 98_304),
+    (78, 7_000, 131_072),
+    (84, 10_000, 196_608),
+    (90, 14_000, 262_144),
+];
+
+const MULTIPLIERS: [u64; 30] = [
+    1, 2, 3, 5, 6, 7, 10, 11, 13, 14, 15, 17, 19, 21, 22, 23, 26, 29, 30, 31, 33, 34, 35, 37, 38,
+    39, 41, 42, 43, 46,
+];
+
+// cofactors below this multiple of the largest factor base prime are kept
+// as single large prime partial relations
+const LARGE_PRIME_MULTIPLIER: u64 = 64;
+
+// relations beyond the factor base size, each extra one roughly halves the
+// chance that no dependency splits n
+const EXTRA_RELATIONS: usize = 24;
+
+struct Prime {
+    p: u64,
+    // sqrt(kN) mod p
+    t: u64,
+    log: u8,
+}
+
+// y^2 = (-1)^e0 * prod fb[i]^e_i * large^2 (mod n)
+struct Relation {
+    y: BigInt,
+    // 0 for -1 and i + 1 for fb[i], with multiplicity
+    factors: Vec<usize>,
+    large: u64,
+}
+
+// Knuth-Schroeppel: the k making small primes most likely to divide the values
+fn choose_multiplier(n: &BigInt, primes: &[u64]) -> u64 {
+    let mut best = (f64::MIN, 1);
+    for &k in MULTIPLIERS.iter() {
+        let kn = n * k;
+        let mut score = -0.5 * (k as f64).ln();
+        score += match (&kn % 8u32).to_u32() {
+            Some(1) => 2.0 * 2f64.ln(),
+            Some(5) => 2f64.ln(),
+            Some(3) | Some(7) => 0.5 * 2f64.ln(),
+            _ => 0.0,
+        };
+        for &p in primes.iter().skip(1) {
+            let lp = (p as f64).ln();
+            if k % p == 0 {
+                score += lp / p as f64;
+            } else if kronecker_binary(kn.clone(), BigInt::from(p)) == 1 {
+                score += 2.0 * lp / (p - 1) as f64;
+            }
+        }
+        if score > best.0 {
+            best = (score, k);
+        }
+    }
+    best.1
+}
+
+fn inv_mod(a: u64, p: u64) -> u64 {
+    let (mut r0, mut r1) = (a as i64 % p as i64, p as i64);
+    let (mut s0, mut s1) = (1i64, 0i64);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(p as i64) as u64
+}
+
+fn mod_u64(x: &BigInt, p: u64) -> u64 {
+    x.mod_floor(&BigInt::from(p)).to_u64().unwrap_or(0)
+}
+
+// xorshift, only used to vary the choice of A
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+// the self-initialising quadratic sieve with single large primes, for n odd,
+// composite and not a perfect power; returns a nontrivial factor, and None
+// for n even, prime or below 9
+pub fn siqs(n: &BigInt) -> Option<BigInt> {
+    if n.is_even() || *n < BigInt::from(9) || is_probable_prime(n) {
+        return None;
+    }
+    let digits = n.to_string().len();
+    let &(_, fb_size, m) = PARAMETERS
+        .iter()
+        .find(|&&(d, _, _)| digits <= d)
+        .unwrap_or(&PARAMETERS[PARAMETERS.len() - 1]);
+
+    let k = choose_multiplier(n, &primes_up_to(1000));
+    let kn = n * k;
+
+    // factor base: 2, then the odd primes p with (kN/p) != -1
+    let mut fb = vec![Prime {
+        p: 2,
+        t: mod_u64(&kn, 2),
+        log: 1,
+    }];
+    let mut bound = 16 * fb_size as u64;
+    while fb.len() < fb_size {
+        fb.truncate(1);
+        for p in primes_up_to(bound).into_iter().skip(1) {
+            if fb.len() >= fb_size {
+                break;
+            }
+            let r = mod_u64(&kn, p);
+            if r == 0 {
+                if n % p == BigInt::zero() {
+                    return Some(BigInt::from(p));
+                }
+            } else if kronecker_binary(BigInt::from(r), BigInt::from(p)) != 1 {
+                continue;
+            }
+            let t = sqrt_mod_prime(BigInt::from(r), BigInt::from(p)).ok()??;
+            let log = (p as f64).log2().round() as u8;
+            fb.push(Prime {
+                p,
+                t: t.to_u64()?,
+                log,
+            });
+        }
+        bound *= 2;
+    }
+    let pmax = fb[fb.len() - 1].p;
+    let large_bound = pmax * LARGE_PRIME_MULTIPLIER;
+
+    // A ~ sqrt(2kN) / M, built from s primes near target^(1/s) in the factor base
+    let target = (&kn * 2u32).sqrt() / m;
+    let target_bits = target.bits() as f64;
+    let s = ((target_bits / 11.0).round() as usize).clamp(1, 12);
+    let q_ideal = 2f64.powf(target_bits / s as f64);
+    let lo = fb.iter().position(|q| q.p > 3).unwrap_or(1);
+    let centre = fb
+        .iter()
+        .position(|q| q.p as f64 >= q_ideal)
+        .unwrap_or(fb.len() - 1)
+        .max(lo);
+    let window = (centre - lo).clamp(s + 1, 4 * s + 8);
+    let (win_lo, win_hi) = (
+        centre.saturating_sub(window / 2).max(lo),
+        (centre + window / 2).min(fb.len() - 1),
+    );
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d ^ digits as u64);
+    let mut used_a: HashSet<Vec<usize>> = HashSet::new();
+
+    // threshold for log2 |g(x)| after sieving, allowing for a large prime and
+    // the unsieved powers of 2
+    let thresh =
+        ((m as f64).log2() + kn.bits() as f64 / 2.0 - 0.5 - (large_bound as f64).log2() - 2.0)
+            .max(1.0) as u8;
+
+    let needed = fb.len() + 1 + EXTRA_RELATIONS;
+    let mut relations: Vec<Relation> = Vec::new();
+    let mut seen: HashSet<BigInt> = HashSet::new();
+    let mut partials: HashMap<u64, Relation> = HashMap::new();
+    let mut sieve = vec![0u8; 2 * m];
+    let mut a_tries = 0;
+
+    while relations.len() < needed {
+        a_tries += 1;
+        if a_tries > 10_000 {
+            return None;
+        }
+
+        // pick the primes of A
+        let mut qs: Vec<usize> = Vec::new();
+        let mut a = BigInt::one();
+        let mut attempts = 0;
+        while qs.len() + 1 < s && attempts < 100 {
+            attempts += 1;
+            let i = win_lo + rng.next(win_hi - win_lo + 1);
+            if !qs.contains(&i) && fb[i].t != 0 {
+                a *= fb[i].p;
+                qs.push(i);
+            }
+        }
+        let ratio = (&target / &a).to_f64().unwrap_or(f64::MAX);
+        let last = (lo..fb.len())
+            .filter(|i| !qs.contains(i) && fb[*i].t != 0)
+            .min_by(|&i, &j| {
+                let di = ((fb[i].p as f64) / ratio).ln().abs();
+                let dj = ((fb[j].p as f64) / ratio).ln().abs();
+                di.partial_cmp(&dj).unwrap()
+            })?;
+        a *= fb[last].p;
+        qs.push(last);
+        qs.sort();
+        if !used_a.insert(qs.clone()) {
+            continue;
+        }
+
+        // B_l = (A/q_l) * (t_l (A/q_l)^-1 mod q_l), so that b^2 = kN (mod A)
+        let mut big_b: Vec<BigInt> = Vec::new();
+        for &i in &qs {
+            let q = fb[i].p;
+            let a_l = &a / q;
+            let mut gamma = fb[i].t * inv_mod(mod_u64(&a_l, q), q) % q;
+            if gamma > q / 2 {
+                gamma = q - gamma;
+            }
+            big_b.push(a_l * gamma);
+        }
+        let mut b: BigInt = big_b.iter().sum();
+
+        // per prime data: A^-1, 2 B_l A^-1 and the two sieve roots
+        let in_a: HashSet<usize> = qs.iter().cloned().collect();
+        let mut ainv = vec![0u64; fb.len()];
+        let mut b_ainv2: Vec<Vec<u64>> = vec![vec![0u64; fb.len()]; qs.len()];
+        for (i, pr) in fb.iter().enumerate().skip(1) {
+            if in_a.contains(&i) {
+                continue;
+            }
+            let p = pr.p;
+            ainv[i] = inv_mod(mod_u64(&a, p), p);
+            for (l, bl) in big_b.iter().enumerate() {
+                b_ainv2[l][i] = 2 * mod_u64(bl, p) % p * ainv[i] % p;
+            }
+        }
+        let mut roots = vec![(0u64, 0u64); fb.len()];
+        for (i, pr) in fb.iter().enumerate().skip(1) {
+            if in_a.contains(&i) {
+                continue;
+            }
+            let p = pr.p;
+            let bm = mod_u64(&b, p);
+            let mo = m as u64 % p;
+            roots[i] = (
+                (ainv[i] * ((pr.t + p - bm) % p) + mo) % p,
+                (ainv[i] * ((2 * p - pr.t - bm) % p) + mo) % p,
+            );
+        }
+
+        // walk the 2^(s-1) choices of signs of B_2..B_s in Gray code order
+        let mut signs = vec![1i32; qs.len()];
+        for poly in 0..1usize << (qs.len() - 1) {
+            if poly > 0 {
+                let l = poly.trailing_zeros() as usize + 1;
+                signs[l] = -signs[l];
+                // b changes by 2 sigma B_l, each root moves by -2 sigma B_l A^-1
+                if signs[l] > 0 {
+                    b += &big_b[l] * 2u32;
+                } else {
+                    b -= &big_b[l] * 2u32;
+                }
+                for (i, pr) in fb.iter().enumerate().skip(1) {
+                    if in_a.contains(&i) {
+                        continue;
+                    }
+                    let p = pr.p;
+                    let d = b_ainv2[l][i];
+                    let (r1, r2) = roots[i];
+                    roots[i] = if signs[l] > 0 {
+                        ((r1 + p - d) % p, (r2 + p - d) % p)
+                    } else {
+                        ((r1 + d) % p, (r2 + d) % p)
+                    };
+                }
+            }
+            let c = (&b * &b - &kn) / &a;
+
+            sieve.iter_mut().for_each(|v| *v = 0);
+            for (i, pr) in fb.iter().enumerate().skip(1) {
+                if in_a.contains(&i) || pr.p < 5 {
+                    continue;
+                }
+                let p = pr.p as usize;
+                let (r1, r2) = roots[i];
+                let mut j = r1 as usize;
+                while j < 2 * m {
+                    sieve[j] = sieve[j].saturating_add(pr.log);
+                    j += p;
+                }
+                if r2 != r1 {
+                    let mut j = r2 as usize;
+                    while j < 2 * m {
+                        sieve[j] = sieve[j].saturating_add(pr.log);
+                        j += p;
+                    }
+                }
+            }
+
+            for (j, &v) in sieve.iter().enumerate() {
+                if v < thresh {
+                    continue;
+                }
+                let x = j as i64 - m as i64;
+                let xb = BigInt::from(x);
+                let mut g = (&a * &xb + &b * 2u32) * &xb + &c;
+                if g.is_zero() {
+                    continue;
+                }
+                let mut factors: Vec<usize> = qs.iter().map(|&i| i + 1).collect();
+                if g.is_negative() {
+                    factors.push(0);
+                    g = -g;
+                }
+                for (i, pr) in fb.iter().enumerate() {
+                    let p = pr.p;
+                    let hit = if i == 0 || in_a.contains(&i) || pr.t == 0 {
+                        true
+                    } else {
+                        let xm = j as u64 % p;
+                        xm == roots[i].0 || xm == roots[i].1
+                    };
+                    if !hit {
+                        continue;
+                    }
+                    while (&g % p).is_zero() {
+                        g /= p;
+                        factors.push(i + 1);
+                    }
+                }
+                let y = (&a * &xb + &b).mod_floor(n);
+                if !seen.insert(y.clone()) {
+                    continue;
+                }
+                let large = match g.to_u64() {
+                    Some(1) => 1,
+                    Some(l) if l < large_bound => l,
+                    _ => continue,
+                };
+                let rel = Relation { y, factors, large };
+                if large == 1 {
+                    relations.push(rel);
+                } else if let Some(other) = partials.remove(&large) {
+                    // two partials with the same large prime make a full relation
+                    let mut factors = other.factors;
+                    factors.extend(rel.factors);
+                    relations.push(Relation {
+                        y: (&other.y * &rel.y).mod_floor(n),
+                        factors,
+                        large,
+                    });
+                } else {
+                    partials.insert(large, rel);
+                }
+            }
+            if relations.len() >= needed {
+                break;
+            }
+        }
+    }
+
+    // exponent parities: row r is the factor base entry r (0 for -1), column
+    // j the relation j
+    let rows = fb.len() + 1;
+    let cols = relations.len();
+    let words = cols.div_ceil(64);
+    let mut matrix = vec![vec![0u64; words]; rows];
+    for (j, rel) in relations.iter().enumerate() {
+        for &f in &rel.factors {
+            matrix[f][j / 64] ^= 1 << (j % 64);
+        }
+    }
+
+    for dep in kernel_basis_mod2(&matrix, cols) {
+        let mut x = BigInt::one();
+        let mut y = BigInt::one();
+        let mut exponents = vec![0u32; rows];
+        for (j, rel) in relations.iter().enumerate() {
+            if (dep[j / 64] >> (j % 64)) & 1 == 0 {
+                continue;
+            }
+            x = (x * &rel.y).mod_floor(n);
+            y = (y * rel.large).mod_floor(n);
+            for &f in &rel.factors {
+                exponents[f] += 1;
+            }
+        }
+        for (r, &e) in exponents.iter().enumerate().skip(1) {
+            y = (y * BigInt::from(fb[r - 1].p).modpow(&BigInt::from(e / 2), n)).mod_floor(n);
+        }
+        let d = (x - y).gcd(n);
+        if !d.is_one() && d != *n {
+            return Some(d);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_semiprimes() {
+        for (p, q) in [
+            (300_000_000_000_089u64, 7_000_000_000_000_037u64),
+            (31_415_926_535_897_999, 2_718_281_828_459_045_269),
+        ] {
+            let (p, q) = (BigInt::from(p), BigInt::from(q));
+            let d = siqs(&(&p * &q)).unwrap();
+            assert!(d == p || d == q, "{}", d);
+        }
+    }
+
+    #[test]
+    fn trivial_inputs_give_none() {
+        for n in [0i64, 1, 3, 4, 97, 1_000_000, 1_000_000_007] {
+            assert_eq!(siqs(&BigInt::from(n)), None, "n = {}", n);
+        }
+    }
+}
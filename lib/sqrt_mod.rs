@@ -0,0 +1,295 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+use crate::arith::mod_inv;
+use crate::crt::CrtBasis;
+use crate::error::Error;
+use crate::factor::factor;
+use crate::fp::{Fp, FpContext};
+use crate::primality::is_probable_prime;
+use crate::{kronecker, sqrt_mod_prime};
+
+// a square root of a modulo the odd prime p by Cipolla: for t with t^2 - a a
+// non-residue, (t + w)^((p + 1) / 2) in F_p[w] / (w^2 - t^2 + a) lies in F_p
+// and squares to a
+pub fn cipolla(a: &BigInt, p: &BigInt) -> Result<Option<BigInt>, Error> {
+    if *p <= BigInt::from(2) || p.is_even() {
+        return Err(Error::InvalidArgument("p must be an odd prime"));
+    }
+    let ctx = FpContext::new(p);
+    let a = ctx.elem(a);
+    if a.is_zero() {
+        return Ok(Some(BigInt::zero()));
+    }
+    if kronecker(ctx.residue(&a), p.clone()) != 1 {
+        return Ok(None);
+    }
+
+    // t = 0 is the only choice for p = 3
+    let mut t = BigInt::zero();
+    let w = loop {
+        if t >= *p {
+            return Err(Error::NotPrime);
+        }
+        let w = &ctx.elem(&(&t * &t)) - &a;
+        if kronecker(ctx.residue(&w), p.clone()) == -1 {
+            break w;
+        }
+        t += 1;
+    };
+
+    // (x, y) stands for x + y w
+    let mul = |(x1, y1): &(Fp, Fp), (x2, y2): &(Fp, Fp)| {
+        (&(x1 * x2) + &(&(y1 * y2) * &w), &(x1 * y2) + &(x2 * y1))
+    };
+    let e: BigInt = (p + 1u32) >> 1;
+    let base = (ctx.elem(&t), ctx.elem(&BigInt::one()));
+    let mut y = (ctx.elem(&BigInt::one()), ctx.elem(&BigInt::zero()));
+    for i in (0..e.bits()).rev() {
+        y = mul(&y, &y);
+        if e.bit(i) {
+            y = mul(&y, &base);
+        }
+    }
+    let (x, _) = y;
+    if &x * &x != a {
+        return Err(Error::NotPrime);
+    }
+    Ok(Some(ctx.residue(&x)))
+}
+
+// a square root of the unit a modulo the odd prime p: Tonelli-Shanks costs
+// O(e^2) multiplications for p - 1 = 2^e q against Cipolla's O(log p), so
+// the latter is used once e^2 exceeds the size of p
+fn sqrt_unit_mod_prime(a: &BigInt, p: &BigInt) -> Result<Option<BigInt>, Error> {
+    let e = (p - 1u32).trailing_zeros().unwrap_or(0);
+    if e * e > p.bits() {
+        cipolla(a, p)
+    } else {
+        sqrt_mod_prime(a.clone(), p.clone())
+    }
+}
+
+// a square root of the unit a modulo p^k, p odd, by Newton's iteration
+// x <- x - (x^2 - a) / 2x, which doubles the precision at each step
+fn sqrt_unit_mod_odd_prime_power(a: &BigInt, p: &BigInt, k: u32) -> Result<Option<BigInt>, Error> {
+    let Some(mut x) = sqrt_unit_mod_prime(a, p)? else {
+        return Ok(None);
+    };
+    let mut j = 1;
+    while j < k {
+        j = (2 * j).min(k);
+        let q = p.pow(j);
+        let inv = mod_inv(&(2 * &x), &q).ok_or(Error::NotPrime)?;
+        x = (&x - (&x * &x - a) * inv).mod_floor(&q);
+    }
+    Ok(Some(x))
+}
+
+// the square roots of the odd a modulo 2^k: none unless a = 1 (mod 8) for
+// k >= 3, and then x, -x, x + 2^(k-1) and -x + 2^(k-1) for the x found bit by
+// bit, x^2 = a (mod 2^(j+1)) being kept by adding 2^(j-1) when needed
+fn sqrt_unit_mod_power_of_two(a: &BigInt, k: u32) -> Vec<BigInt> {
+    let q = BigInt::one() << k;
+    let a = a.mod_floor(&q);
+    match k {
+        1 => return vec![BigInt::one()],
+        2 => {
+            return if (&a & BigInt::from(3)).is_one() {
+                vec![BigInt::one(), BigInt::from(3)]
+            } else {
+                Vec::new()
+            }
+        }
+        _ => {}
+    }
+    if !(&a & BigInt::from(7)).is_one() {
+        return Vec::new();
+    }
+    let mut x = BigInt::one();
+    for j in 3..k {
+        let m = BigInt::one() << (j + 1);
+        if (&x * &x - &a).mod_floor(&m) != BigInt::zero() {
+            x += BigInt::one() << (j - 1);
+        }
+    }
+    let half = BigInt::one() << (k - 1);
+    let mut roots = vec![
+        x.clone(),
+        (-&x).mod_floor(&q),
+        (&x + &half).mod_floor(&q),
+        (&half - &x).mod_floor(&q),
+    ];
+    roots.sort();
+    roots
+}
+
+// all x in [0, p^k) with x^2 = a (mod p^k), p prime: with a = p^(2w) a' and
+// a' a unit, x = p^w y where y^2 = a' (mod p^(k-2w)) and y is taken modulo
+// p^(k-w), so each root of the unit part gives p^w roots; when p^k | a these
+// are the multiples of p^ceil(k/2)
+pub fn sqrt_mod_prime_power(a: &BigInt, p: &BigInt, k: u32) -> Result<Vec<BigInt>, Error> {
+    if k == 0 {
+        return Err(Error::InvalidArgument("exponent must be positive"));
+    }
+    if !is_probable_prime(p) {
+        return Err(Error::NotPrime);
+    }
+    let q = p.pow(k);
+    let mut a = a.mod_floor(&q);
+    if a.is_zero() {
+        let step = p.pow(k.div_ceil(2));
+        return Ok(multiples(&step, &q));
+    }
+
+    let mut v = 0;
+    while a.is_multiple_of(p) {
+        a /= p;
+        v += 1;
+    }
+    if v % 2 == 1 {
+        return Ok(Vec::new());
+    }
+    let w = v / 2;
+    let k1 = k - v;
+    let units = if *p == BigInt::from(2) {
+        sqrt_unit_mod_power_of_two(&a, k1)
+    } else {
+        match sqrt_unit_mod_odd_prime_power(&a, p, k1)? {
+            Some(x) => {
+                let q1 = p.pow(k1);
+                vec![x.clone(), (-x).mod_floor(&q1)]
+            }
+            None => Vec::new(),
+        }
+    };
+
+    // y = u + t p^k1 for t < p^w, then x = p^w y
+    let q1 = p.pow(k1);
+    let pw = p.pow(w);
+    let mut roots = Vec::new();
+    for u in &units {
+        for t in multiples(&q1, &(&q1 * &pw)) {
+            roots.push(&pw * (u + t));
+        }
+    }
+    roots.sort();
+    roots.dedup();
+    Ok(roots)
+}
+
+// 0, step, 2 step, ... below bound
+fn multiples(step: &BigInt, bound: &BigInt) -> Vec<BigInt> {
+    let mut result = Vec::new();
+    let mut x = BigInt::zero();
+    while x < *bound {
+        result.push(x.clone());
+        x += step;
+    }
+    result
+}
+
+// all x in [0, n) with x^2 = a (mod n), from the roots modulo each prime power
+// of n combined by the Chinese remainder theorem
+pub fn sqrt_mod(a: &BigInt, n: &BigInt) -> Result<Vec<BigInt>, Error> {
+    if !n.is_positive() {
+        return Err(Error::InvalidArgument("modulus must be positive"));
+    }
+    sqrt_mod_factored(a, &factor(n))
+}
+
+// as sqrt_mod for n given by its factorization
+pub fn sqrt_mod_factored(a: &BigInt, f: &[(BigInt, u32)]) -> Result<Vec<BigInt>, Error> {
+    if f.is_empty() {
+        return Ok(vec![BigInt::zero()]);
+    }
+    let mut moduli = Vec::with_capacity(f.len());
+    let mut local = Vec::with_capacity(f.len());
+    for (p, e) in f {
+        let roots = sqrt_mod_prime_power(a, p, *e)?;
+        if roots.is_empty() {
+            return Ok(Vec::new());
+        }
+        moduli.push(p.pow(*e));
+        local.push(roots);
+    }
+    let basis = CrtBasis::new(&moduli)?;
+
+    // every choice of one root per prime power, odometer style
+    let mut index = vec![0usize; local.len()];
+    let mut roots = Vec::new();
+    loop {
+        let xi: Vec<BigInt> = index
+            .iter()
+            .zip(&local)
+            .map(|(&i, r)| r[i].clone())
+            .collect();
+        roots.push(basis.lift(&xi)?);
+        let mut j = 0;
+        while j < index.len() {
+            index[j] += 1;
+            if index[j] < local[j].len() {
+                break;
+            }
+            index[j] = 0;
+            j += 1;
+        }
+        if j == index.len() {
+            break;
+        }
+    }
+    roots.sort();
+    Ok(roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute(a: i64, n: i64) -> Vec<BigInt> {
+        (0..n)
+            .filter(|x| (x * x - a).rem_euclid(n) == 0)
+            .map(BigInt::from)
+            .collect()
+    }
+
+    #[test]
+    fn sqrt_mod_matches_exhaustive_search() {
+        for n in 1..150i64 {
+            for a in -3..n {
+                let got = sqrt_mod(&BigInt::from(a), &BigInt::from(n)).unwrap();
+                assert_eq!(got, brute(a, n), "{} mod {}", a, n);
+            }
+        }
+    }
+
+    #[test]
+    fn prime_powers_match_exhaustive_search() {
+        for (p, k) in [(2i64, 1u32), (2, 6), (2, 9), (3, 5), (5, 4), (7, 3)] {
+            let q = p.pow(k);
+            for a in 0..q {
+                let got = sqrt_mod_prime_power(&BigInt::from(a), &BigInt::from(p), k).unwrap();
+                assert_eq!(got, brute(a, q), "{} mod {}^{}", a, p, k);
+            }
+        }
+        assert_eq!(
+            sqrt_mod_prime_power(&BigInt::one(), &BigInt::from(9), 2),
+            Err(Error::NotPrime)
+        );
+    }
+
+    #[test]
+    fn cipolla_squares_back_to_a() {
+        for p in [3i64, 5, 13, 17, 97, 65_537] {
+            let bp = BigInt::from(p);
+            for a in 0..p.min(500) {
+                let expected = brute(a, p);
+                match cipolla(&BigInt::from(a), &bp).unwrap() {
+                    Some(x) => assert!(expected.contains(&x), "{} mod {}", a, p),
+                    None => assert!(expected.is_empty(), "{} mod {}", a, p),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+use crate::arith::sigma_factored;
+use crate::cornacchia;
+use crate::error::Error;
+use crate::factor::factor;
+use crate::primality::is_probable_prime;
+
+// Gaussian integers x + y i as pairs
+fn gauss_mul((a, b): &(BigInt, BigInt), (c, d): &(BigInt, BigInt)) -> (BigInt, BigInt) {
+    (a * c - b * d, a * d + b * c)
+}
+
+// z^0, z^1, ..., z^k
+fn gauss_powers(z: &(BigInt, BigInt), k: u32) -> Vec<(BigInt, BigInt)> {
+    let mut powers = vec![(BigInt::one(), BigInt::zero())];
+    for i in 0..k as usize {
+        powers.push(gauss_mul(&powers[i], z));
+    }
+    powers
+}
+
+// x + y i of norm p for a prime p = 2 or p = 1 (mod 4)
+fn gauss_prime(p: &BigInt) -> Result<(BigInt, BigInt), Error> {
+    if *p == BigInt::from(2) {
+        return Ok((BigInt::one(), BigInt::one()));
+    }
+    cornacchia(p.clone(), BigInt::one())?.ok_or(Error::NotPrime)
+}
+
+// every (x, y) with 0 <= x <= y and x^2 + y^2 = n: with n = 2^e prod p^k
+// prod q^(2l) over p = 1 and q = 3 (mod 4), the x + y i of norm n are the
+// units times (1 + i)^e prod q^l prod pi^s conj(pi)^(k - s), p = pi conj(pi)
+// by Cornacchia, for 0 <= s <= k; any q to an odd power leaves none
+pub fn two_squares(n: &BigInt) -> Result<Vec<(BigInt, BigInt)>, Error> {
+    if n.is_negative() {
+        return Ok(Vec::new());
+    }
+    if n.is_zero() {
+        return Ok(vec![(BigInt::zero(), BigInt::zero())]);
+    }
+    two_squares_factored(&factor(n))
+}
+
+// as two_squares for n given by its factorization
+pub fn two_squares_factored(f: &[(BigInt, u32)]) -> Result<Vec<(BigInt, BigInt)>, Error> {
+    let mut zs = vec![(BigInt::one(), BigInt::zero())];
+    for (p, e) in f {
+        let r = p.mod_floor(&BigInt::from(4));
+        let choices = if r == BigInt::from(3) {
+            if e % 2 == 1 {
+                return Ok(Vec::new());
+            }
+            vec![(p.pow(e / 2), BigInt::zero())]
+        } else if r == BigInt::from(2) {
+            gauss_powers(&gauss_prime(p)?, *e).split_off(*e as usize)
+        } else {
+            let pi = gauss_prime(p)?;
+            let pis = gauss_powers(&pi, *e);
+            let conjs = gauss_powers(&(pi.0.clone(), -&pi.1), *e);
+            pis.iter()
+                .zip(conjs.iter().rev())
+                .map(|(a, b)| gauss_mul(a, b))
+                .collect()
+        };
+        zs = zs
+            .iter()
+            .flat_map(|z| choices.iter().map(move |c| gauss_mul(z, c)))
+            .collect();
+    }
+    let mut reps: Vec<(BigInt, BigInt)> = zs
+        .into_iter()
+        .map(|(x, y)| {
+            let (x, y) = (x.abs(), y.abs());
+            if x <= y {
+                (x, y)
+            } else {
+                (y, x)
+            }
+        })
+        .collect();
+    reps.sort();
+    reps.dedup();
+    Ok(reps)
+}
+
+// r_2(n), the number of (x, y) in Z^2 with x^2 + y^2 = n: 4 prod (k + 1) over
+// the p^k || n with p = 1 (mod 4), or 0 if some q = 3 (mod 4) divides n to an
+// odd power
+pub fn r2(n: &BigInt) -> BigInt {
+    if n.is_negative() {
+        return BigInt::zero();
+    }
+    if n.is_zero() {
+        return BigInt::one();
+    }
+    r2_factored(&factor(n))
+}
+
+// as r2 for n given by its factorization
+pub fn r2_factored(f: &[(BigInt, u32)]) -> BigInt {
+    let mut count = BigInt::from(4);
+    for (p, e) in f {
+        match p.mod_floor(&BigInt::from(4)) {
+            r if r == BigInt::one() => count *= e + 1,
+            r if r == BigInt::from(3) && e % 2 == 1 => return BigInt::zero(),
+            _ => {}
+        }
+    }
+    count
+}
+
+// r_4(n), the number of (x, y, z, w) in Z^4 with x^2 + y^2 + z^2 + w^2 = n,
+// by Jacobi: 8 sigma(n) for n odd and 24 sigma(m) for n = 2^e m even
+pub fn r4(n: &BigInt) -> BigInt {
+    if n.is_negative() {
+        return BigInt::zero();
+    }
+    if n.is_zero() {
+        return BigInt::one();
+    }
+    r4_factored(&factor(n))
+}
+
+// as r4 for n given by its factorization
+pub fn r4_factored(f: &[(BigInt, u32)]) -> BigInt {
+    let two = BigInt::from(2);
+    let odd: Vec<(BigInt, u32)> = f.iter().filter(|(p, _)| *p != two).cloned().collect();
+    let factor = if odd.len() == f.len() { 8 } else { 24 };
+    factor * sigma_factored(1, &odd)
+}
+
+// n = 4^e m with 4 not dividing m, for n > 0, as (e, m)
+fn split_four(n: &BigInt) -> (u32, BigInt) {
+    let mut m = n.clone();
+    let mut e = 0;
+    while (&m & BigInt::from(3)).is_zero() {
+        m >>= 2;
+        e += 1;
+    }
+    (e, m)
+}
+
+// x^2 + y^2 = m when m is 1 or a prime = 1 (mod 4), by Cornacchia
+fn two_squares_prime(m: &BigInt) -> Result<Option<(BigInt, BigInt)>, Error> {
+    if m.is_one() {
+        return Ok(Some((BigInt::zero(), BigInt::one())));
+    }
+    if m.mod_floor(&BigInt::from(4)).is_one() && is_probable_prime(m) {
+        return cornacchia(m.clone(), BigInt::one());
+    }
+    Ok(None)
+}
+
+// (x, y, z), 0 <= x <= y <= z, with x^2 + y^2 + z^2 = n, which exists unless
+// n = 4^e (8k + 7) by Legendre: with n = 4^e m, x is chosen so that m - x^2
+// is 1 or a prime = 1 (mod 4), or twice one of these when m = 3 (mod 8),
+// which Cornacchia then splits
+pub fn three_squares(n: &BigInt) -> Result<Option<(BigInt, BigInt, BigInt)>, Error> {
+    if n.is_negative() {
+        return Ok(None);
+    }
+    if n.is_zero() {
+        return Ok(Some((BigInt::zero(), BigInt::zero(), BigInt::zero())));
+    }
+    let (e, m) = split_four(n);
+    let r = m.mod_floor(&BigInt::from(8));
+    if r == BigInt::from(7) {
+        return Ok(None);
+    }
+
+    // m - x^2 = 2 (mod 8) for x odd when m = 3 (mod 8), and m - x^2 = 1
+    // (mod 4) for x of the other parity than m otherwise
+    let doubled = r == BigInt::from(3);
+    let x_odd = doubled || m.is_even();
+    let mut found = None;
+    let mut x = m.sqrt();
+    loop {
+        if x.is_odd() == x_odd {
+            let t = &m - &x * &x;
+            if doubled {
+                if let Some((u, v)) = two_squares_prime(&(&t / 2u32))? {
+                    found = Some((x.clone(), &u + &v, (&u - &v).abs()));
+                }
+            } else if let Some((u, v)) = two_squares_prime(&t)? {
+                found = Some((x.clone(), u, v));
+            }
+        }
+        if found.is_some() || x.is_zero() {
+            break;
+        }
+        x -= 1;
+    }
+
+    // small m need not have a prime of the right shape, but do have some
+    // split of m - x^2 into two squares
+    if found.is_none() {
+        let mut x = BigInt::zero();
+        while &x * &x <= m {
+            if let Some((u, v)) = two_squares(&(&m - &x * &x))?.pop() {
+                found = Some((x, u, v));
+                break;
+            }
+            x += 1;
+        }
+    }
+    let (x, y, z) = found.ok_or(Error::NoConvergence)?;
+    let mut s = [x << e, y << e, z << e];
+    s.sort();
+    let [x, y, z] = s;
+    Ok(Some((x, y, z)))
+}
+
+// (x, y, z, w), 0 <= x <= y <= z <= w, with x^2 + y^2 + z^2 + w^2 = n, after
+// Rabin and Shallit with a scan in place of their random choices: with
+// n = 4^e m, w = 1 takes m = 7 (mod 8) to m - 1 = 6 (mod 8), and the rest
+// is three squares
+pub fn four_squares(n: &BigInt) -> Result<(BigInt, BigInt, BigInt, BigInt), Error> {
+    if n.is_negative() {
+        return Err(Error::InvalidArgument("n must be nonnegative"));
+    }
+    if n.is_zero() {
+        return Ok((
+            BigInt::zero(),
+            BigInt::zero(),
+            BigInt::zero(),
+            BigInt::zero(),
+        ));
+    }
+    let (e, m) = split_four(n);
+    let w = if m.mod_floor(&BigInt::from(8)) == BigInt::from(7) {
+        BigInt::one()
+    } else {
+        BigInt::zero()
+    };
+    let (x, y, z) = three_squares(&(&m - &w * &w))?.ok_or(Error::NoConvergence)?;
+    let mut s = [x << e, y << e, z << e, w << e];
+    s.sort();
+    let [x, y, z, w] = s;
+    Ok((x, y, z, w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn isqrt(n: i64) -> i64 {
+        (0..).take_while(|t| t * t <= n).last().unwrap()
+    }
+
+    // counts[k][n] is the number of points of Z^k on the sphere of radius^2 n
+    fn counts(k: usize, limit: i64) -> Vec<i64> {
+        let mut c = vec![0i64; limit as usize];
+        c[0] = 1;
+        for _ in 0..k {
+            let mut next = vec![0i64; limit as usize];
+            for (n, &v) in c.iter().enumerate() {
+                let b = isqrt(limit - 1 - n as i64);
+                for x in -b..=b {
+                    next[n + (x * x) as usize] += v;
+                }
+            }
+            c = next;
+        }
+        c
+    }
+
+    #[test]
+    fn r2_r4_match_enumeration() {
+        let (c2, c4) = (counts(2, 600), counts(4, 600));
+        for n in 0..600i64 {
+            let bn = BigInt::from(n);
+            assert_eq!(r2(&bn), BigInt::from(c2[n as usize]), "r2({})", n);
+            assert_eq!(r4(&bn), BigInt::from(c4[n as usize]), "r4({})", n);
+            if n > 0 {
+                let f = factor(&bn);
+                assert_eq!(r2_factored(&f), r2(&bn));
+                assert_eq!(r4_factored(&f), r4(&bn));
+            }
+        }
+        assert!(r2(&BigInt::from(-5)).is_zero());
+        assert!(r4(&BigInt::from(-5)).is_zero());
+    }
+
+    #[test]
+    fn two_squares_match_enumeration() {
+        for n in 0..600i64 {
+            let mut want = Vec::new();
+            for x in 0..=isqrt(n / 2) {
+                let y = isqrt(n - x * x);
+                if x * x + y * y == n {
+                    want.push((BigInt::from(x), BigInt::from(y)));
+                }
+            }
+            assert_eq!(two_squares(&BigInt::from(n)).unwrap(), want, "n = {}", n);
+        }
+        assert!(two_squares(&BigInt::from(-1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn three_and_four_squares_sum_to_n() {
+        let c3 = counts(3, 2_000);
+        for n in 0..2_000i64 {
+            let bn = BigInt::from(n);
+            match three_squares(&bn).unwrap() {
+                Some((x, y, z)) => {
+                    assert!(x >= BigInt::zero() && x <= y && y <= z);
+                    assert_eq!(&x * &x + &y * &y + &z * &z, bn);
+                }
+                None => assert_eq!(c3[n as usize], 0, "n = {}", n),
+            }
+            let (x, y, z, w) = four_squares(&bn).unwrap();
+            assert!(x >= BigInt::zero() && x <= y && y <= z && z <= w);
+            assert_eq!(&x * &x + &y * &y + &z * &z + &w * &w, bn);
+        }
+        let big = BigInt::from(10u64).pow(30) + 7;
+        let (x, y, z, w) = four_squares(&big).unwrap();
+        assert_eq!(&x * &x + &y * &y + &z * &z + &w * &w, big);
+        assert!(three_squares(&BigInt::from(-1)).unwrap().is_none());
+        assert!(four_squares(&BigInt::from(-1)).is_err());
+    }
+}